@@ -0,0 +1,494 @@
+//! [traq-bot-http](https://crates.io/crates/traq-bot-http)のderiveマクロです。
+//!
+//! このクレートを直接使用せず、`traq-bot-http`の`derive` featureを有効にしてください。
+
+use proc_macro2::{Span, TokenStream};
+use quote::{quote, ToTokens};
+use syn::ext::IdentExt;
+use syn::spanned::Spanned;
+use syn::{
+    parse_macro_input, Attribute, Data, DeriveInput, Error, Expr, ExprLit, Fields, GenericArgument,
+    Ident, Lit, LitStr, Meta, Path, PathArguments, Result, Type, Variant,
+};
+
+/// `#[derive(BotCommand)]`
+///
+/// 詳細は`traq_bot_http::command::BotCommand`のドキュメントを参照してください。
+#[proc_macro_derive(BotCommand, attributes(command))]
+pub fn derive_bot_command(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    bot_command(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn bot_command(input: &DeriveInput) -> Result<TokenStream> {
+    let container = ContainerAttrs::parse(&input.attrs)?;
+    let krate = &container.krate;
+    let description = doc_comment(&input.attrs);
+    let (parse, info) = match &input.data {
+        Data::Struct(data) => {
+            let command = Command::new(quote!(Self), &data.fields)?;
+            let parse = command.parse(krate);
+            let info = command.info(krate, &quote!(name), &description);
+            (parse, info)
+        }
+        Data::Enum(data) => {
+            let variants = data
+                .variants
+                .iter()
+                .map(SubCommand::new)
+                .collect::<Result<Vec<_>>>()?;
+            let parse = enum_parse(krate, &variants);
+            let info = enum_info(krate, &description, &variants);
+            (parse, info)
+        }
+        Data::Union(_) => {
+            return Err(Error::new(
+                Span::call_site(),
+                "BotCommand cannot be derived for unions",
+            ))
+        }
+    };
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics #krate::command::BotCommand for #name #ty_generics #where_clause {
+            fn parse(
+                args: &mut #krate::command::Arguments,
+            ) -> ::std::result::Result<Self, #krate::command::CommandError> {
+                #parse
+            }
+
+            fn info(name: &str) -> #krate::command::CommandInfo {
+                #info
+            }
+        }
+    })
+}
+
+struct ContainerAttrs {
+    krate: Path,
+}
+
+impl ContainerAttrs {
+    fn parse(attrs: &[Attribute]) -> Result<Self> {
+        let mut krate = None;
+        for attr in attrs.iter().filter(|a| a.path().is_ident("command")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("crate") {
+                    let path: LitStr = meta.value()?.parse()?;
+                    krate = Some(path.parse()?);
+                    return Ok(());
+                }
+                Err(meta.error("unsupported container attribute"))
+            })?;
+        }
+        let krate = krate.unwrap_or_else(|| syn::parse_quote!(::traq_bot_http));
+        Ok(Self { krate })
+    }
+}
+
+/// 埋め込みの種類
+#[derive(Clone, Copy)]
+enum Mention {
+    User,
+    Channel,
+    Group,
+}
+
+impl Mention {
+    fn ty(self, krate: &Path) -> TokenStream {
+        match self {
+            Self::User => quote!(#krate::command::UserMention),
+            Self::Channel => quote!(#krate::command::ChannelMention),
+            Self::Group => quote!(#krate::command::GroupMention),
+        }
+    }
+}
+
+#[derive(Default)]
+struct FieldAttrs {
+    rename: Option<String>,
+    mention: Option<Mention>,
+}
+
+impl FieldAttrs {
+    fn parse(attrs: &[Attribute]) -> Result<Self> {
+        let mut res = Self::default();
+        for attr in attrs.iter().filter(|a| a.path().is_ident("command")) {
+            attr.parse_nested_meta(|meta| {
+                let mention = if meta.path.is_ident("user") {
+                    Mention::User
+                } else if meta.path.is_ident("channel") {
+                    Mention::Channel
+                } else if meta.path.is_ident("group") {
+                    Mention::Group
+                } else if meta.path.is_ident("rename") {
+                    let name: LitStr = meta.value()?.parse()?;
+                    res.rename = Some(name.value());
+                    return Ok(());
+                } else {
+                    return Err(meta.error("unsupported field attribute"));
+                };
+                if res.mention.replace(mention).is_some() {
+                    return Err(meta.error("duplicate mention attribute"));
+                }
+                Ok(())
+            })?;
+        }
+        Ok(res)
+    }
+}
+
+/// 引数の個数の種類と、引数1つあたりの型
+enum Arity<'a> {
+    Required(&'a Type),
+    Optional(&'a Type),
+    Rest(&'a Type),
+}
+
+impl<'a> Arity<'a> {
+    fn of(ty: &'a Type) -> Self {
+        match generic_inner(ty) {
+            Some(("Option", inner)) => Self::Optional(inner),
+            Some(("Vec", inner)) => Self::Rest(inner),
+            _ => Self::Required(ty),
+        }
+    }
+}
+
+/// `Option<T>`や`Vec<T>`の外側の型名と`T`を返す
+fn generic_inner(ty: &Type) -> Option<(&'static str, &Type)> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    if path.qself.is_some() {
+        return None;
+    }
+    let segment = path.path.segments.last()?;
+    let outer = ["Option", "Vec"].into_iter().find(|o| segment.ident == o)?;
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    let mut args = args.args.iter();
+    let (Some(GenericArgument::Type(inner)), None) = (args.next(), args.next()) else {
+        return None;
+    };
+    Some((outer, inner))
+}
+
+/// 名前付きフィールド1つに対応する引数
+struct Argument<'a> {
+    ident: &'a Ident,
+    name: String,
+    description: String,
+    arity: Arity<'a>,
+    mention: Option<Mention>,
+}
+
+impl<'a> Argument<'a> {
+    fn new(field: &'a syn::Field) -> Result<Self> {
+        let ident = field.ident.as_ref().expect("named field");
+        let attrs = FieldAttrs::parse(&field.attrs)?;
+        let name = attrs
+            .rename
+            .unwrap_or_else(|| ident.unraw().to_string().replace('_', "-"));
+        Ok(Self {
+            ident,
+            name,
+            description: doc_comment(&field.attrs),
+            arity: Arity::of(&field.ty),
+            mention: attrs.mention,
+        })
+    }
+
+    /// 引数1つあたりの`FromArgument`を実装する型
+    fn arg_type(&self, krate: &Path) -> TokenStream {
+        match (self.mention, &self.arity) {
+            (Some(mention), _) => mention.ty(krate),
+            (None, Arity::Required(ty) | Arity::Optional(ty) | Arity::Rest(ty)) => {
+                ty.to_token_stream()
+            }
+        }
+    }
+
+    fn parse(&self, krate: &Path) -> TokenStream {
+        let Self { ident, name, .. } = self;
+        let ty = self.arg_type(krate);
+        let value = match (&self.arity, self.mention) {
+            (Arity::Required(_), None) => quote!(args.required::<#ty>(#name)?),
+            (Arity::Required(_), Some(_)) => quote!(args.required::<#ty>(#name)?.id),
+            (Arity::Optional(_), None) => quote!(args.optional::<#ty>(#name)?),
+            (Arity::Optional(_), Some(_)) => {
+                quote!(args.optional::<#ty>(#name)?.map(|mention| mention.id))
+            }
+            (Arity::Rest(_), None) => quote!(args.rest::<#ty>(#name)?),
+            (Arity::Rest(_), Some(_)) => quote! {
+                args.rest::<#ty>(#name)?
+                    .into_iter()
+                    .map(|mention| mention.id)
+                    .collect()
+            },
+        };
+        quote!(#ident: #value)
+    }
+
+    fn info(&self, krate: &Path) -> TokenStream {
+        let Self {
+            name, description, ..
+        } = self;
+        let ty = self.arg_type(krate);
+        let arity = match self.arity {
+            Arity::Required(_) => quote!(Required),
+            Arity::Optional(_) => quote!(Optional),
+            Arity::Rest(_) => quote!(Rest),
+        };
+        quote! {
+            #krate::command::ArgumentInfo::new::<#ty>(#name, #krate::command::Arity::#arity)
+                .description(#description)
+        }
+    }
+}
+
+/// struct, enumのvariantの中身
+enum Command<'a> {
+    Unit {
+        path: TokenStream,
+    },
+    Named {
+        path: TokenStream,
+        arguments: Vec<Argument<'a>>,
+    },
+    /// 1つの`BotCommand`を包む
+    Newtype {
+        path: TokenStream,
+        ty: &'a Type,
+    },
+}
+
+impl<'a> Command<'a> {
+    fn new(path: TokenStream, fields: &'a Fields) -> Result<Self> {
+        match fields {
+            Fields::Unit => Ok(Self::Unit { path }),
+            Fields::Named(fields) => {
+                let arguments = fields
+                    .named
+                    .iter()
+                    .map(Argument::new)
+                    .collect::<Result<_>>()?;
+                Ok(Self::Named { path, arguments })
+            }
+            Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                let field = &fields.unnamed[0];
+                if let Some(attr) = field.attrs.iter().find(|a| a.path().is_ident("command")) {
+                    return Err(Error::new(
+                        attr.span(),
+                        "attributes are not supported for newtype fields",
+                    ));
+                }
+                Ok(Self::Newtype {
+                    path,
+                    ty: &field.ty,
+                })
+            }
+            Fields::Unnamed(fields) => Err(Error::new(
+                fields.span(),
+                "BotCommand requires named fields or a single unnamed field",
+            )),
+        }
+    }
+
+    fn parse(&self, krate: &Path) -> TokenStream {
+        match self {
+            Self::Unit { path } => quote!(::std::result::Result::Ok(#path)),
+            Self::Named { path, arguments } => {
+                let fields = arguments.iter().map(|a| a.parse(krate));
+                quote!(::std::result::Result::Ok(#path { #(#fields,)* }))
+            }
+            Self::Newtype { path, ty } => quote! {
+                ::std::result::Result::Ok(#path(
+                    <#ty as #krate::command::BotCommand>::parse(args)?,
+                ))
+            },
+        }
+    }
+
+    fn info(&self, krate: &Path, name: &TokenStream, description: &str) -> TokenStream {
+        match self {
+            Self::Unit { .. } => quote! {
+                #krate::command::CommandInfo::new(#name).description(#description)
+            },
+            Self::Named { arguments, .. } => {
+                let arguments = arguments.iter().map(|a| a.info(krate));
+                quote! {
+                    #krate::command::CommandInfo::new(#name)
+                        .description(#description)
+                        #(.argument(#arguments))*
+                }
+            }
+            // 説明が無ければ包んでいる型の説明を使う
+            Self::Newtype { ty, .. } if description.is_empty() => {
+                quote!(<#ty as #krate::command::BotCommand>::info(#name))
+            }
+            Self::Newtype { ty, .. } => quote! {
+                <#ty as #krate::command::BotCommand>::info(#name).description(#description)
+            },
+        }
+    }
+}
+
+/// enumのvariantに対応するサブコマンド
+struct SubCommand<'a> {
+    name: String,
+    description: String,
+    command: Command<'a>,
+}
+
+impl<'a> SubCommand<'a> {
+    fn new(variant: &'a Variant) -> Result<Self> {
+        let mut name = None;
+        for attr in variant
+            .attrs
+            .iter()
+            .filter(|a| a.path().is_ident("command"))
+        {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    let rename: LitStr = meta.value()?.parse()?;
+                    name = Some(rename.value());
+                    return Ok(());
+                }
+                Err(meta.error("unsupported variant attribute"))
+            })?;
+        }
+        let ident = &variant.ident;
+        let command = Command::new(quote!(Self::#ident), &variant.fields)?;
+        Ok(Self {
+            name: name.unwrap_or_else(|| kebab_case(&ident.unraw().to_string())),
+            description: doc_comment(&variant.attrs),
+            command,
+        })
+    }
+}
+
+fn enum_parse(krate: &Path, variants: &[SubCommand<'_>]) -> TokenStream {
+    let arms = variants.iter().map(|v| {
+        let name = &v.name;
+        let parse = v.command.parse(krate);
+        quote!(#name => { #parse })
+    });
+    quote! {
+        let token = args
+            .next_token()
+            .ok_or(#krate::command::CommandError::MissingCommand)?;
+        match token.value.as_str() {
+            #(#arms)*
+            _ => ::std::result::Result::Err(
+                #krate::command::CommandError::UnknownCommand(token.value),
+            ),
+        }
+    }
+}
+
+fn enum_info(krate: &Path, description: &str, variants: &[SubCommand<'_>]) -> TokenStream {
+    let subcommands = variants.iter().map(|v| {
+        let name = v.name.to_token_stream();
+        v.command.info(krate, &name, &v.description)
+    });
+    quote! {
+        #krate::command::CommandInfo::new(name)
+            .description(#description)
+            #(.subcommand(#subcommands))*
+    }
+}
+
+/// `PascalCase`を`kebab-case`に変換する
+fn kebab_case(s: &str) -> String {
+    let mut res = String::with_capacity(s.len() + 4);
+    for (i, c) in s.char_indices() {
+        if c.is_uppercase() && i != 0 {
+            res.push('-');
+        }
+        res.extend(c.to_lowercase());
+    }
+    res
+}
+
+/// doc commentの最初の段落を1行にまとめて返す
+fn doc_comment(attrs: &[Attribute]) -> String {
+    let lines = attrs.iter().filter_map(|attr| {
+        let Meta::NameValue(meta) = &attr.meta else {
+            return None;
+        };
+        if !meta.path.is_ident("doc") {
+            return None;
+        }
+        let Expr::Lit(ExprLit {
+            lit: Lit::Str(s), ..
+        }) = &meta.value
+        else {
+            return None;
+        };
+        Some(s.value())
+    });
+    // `/** */`形式では1つの属性に複数行が含まれる
+    // `str::lines`は空文字列に対して何も返さないので`split`を使う
+    let lines: Vec<String> = lines
+        .flat_map(|l| {
+            l.split('\n')
+                .map(|l| l.trim().to_string())
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    lines
+        .iter()
+        .map(String::as_str)
+        .skip_while(|l| l.is_empty())
+        .take_while(|l| !l.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kebab() {
+        assert_eq!(kebab_case("Add"), "add");
+        assert_eq!(kebab_case("ListAll"), "list-all");
+    }
+
+    #[test]
+    fn doc() {
+        let input: DeriveInput = syn::parse_quote! {
+            ///
+            /// Repeat the text
+            /// several times.
+            ///
+            /// Details are omitted.
+            struct Repeat;
+        };
+        assert_eq!(doc_comment(&input.attrs), "Repeat the text several times.");
+    }
+
+    #[test]
+    fn arity() {
+        let ty: Type = syn::parse_quote!(Option<u32>);
+        assert!(matches!(Arity::of(&ty), Arity::Optional(_)));
+        let ty: Type = syn::parse_quote!(std::vec::Vec<String>);
+        assert!(matches!(Arity::of(&ty), Arity::Rest(_)));
+        let ty: Type = syn::parse_quote!(Uuid);
+        assert!(matches!(Arity::of(&ty), Arity::Required(_)));
+    }
+
+    #[test]
+    fn tuple_struct() {
+        let input: DeriveInput = syn::parse_quote! {
+            struct Pair(String, String);
+        };
+        assert!(bot_command(&input).is_err());
+    }
+}
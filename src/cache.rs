@@ -0,0 +1,60 @@
+//! 受け取ったイベントから組み立てる、traQの状態のキャッシュ
+//!
+//! このモジュールのコンポーネントは[`Observer`]を実装しています。
+//! `tower` featureでは[`Handler::observe`]で登録すると、イベントを受け取る度に自動で更新されます。
+//!
+//! [`Handler::observe`]: crate::Handler::observe
+
+use std::sync::{Arc, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use crate::Event;
+
+mod directory;
+mod joined;
+mod messages;
+mod user_groups;
+
+pub use directory::{Directory, Snapshot, Stamp};
+pub use joined::{ChannelChange, ChannelStorage, JoinedChannels, JsonFileStorage};
+#[cfg(feature = "tower")]
+pub use messages::WithHistory;
+pub use messages::{Deleted, HistoryRequest, MessageCache, PreviousMessage, Updated};
+pub use user_groups::UserGroups;
+
+/// イベントを観測して状態を更新するもの
+///
+/// [`Handler`]の複製間で状態を共有するため、`&self`を受け取ります。
+///
+/// [`Handler`]: crate::Handler
+pub trait Observer {
+    /// `event`を状態に反映します。
+    fn observe(&self, event: &Event);
+}
+
+impl<O> Observer for Arc<O>
+where
+    O: Observer + ?Sized,
+{
+    fn observe(&self, event: &Event) {
+        O::observe(self, event);
+    }
+}
+
+impl<O> Observer for &O
+where
+    O: Observer + ?Sized,
+{
+    fn observe(&self, event: &Event) {
+        O::observe(self, event);
+    }
+}
+
+// 更新中にpanicしても各コンポーネントの状態は壊れないため、poisonは無視する
+
+fn read<T>(lock: &RwLock<T>) -> RwLockReadGuard<'_, T> {
+    lock.read().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn write<T>(lock: &RwLock<T>) -> RwLockWriteGuard<'_, T> {
+    lock.write().unwrap_or_else(PoisonError::into_inner)
+}
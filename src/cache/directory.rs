@@ -0,0 +1,425 @@
+//! ユーザー, チャンネル, スタンプ, ユーザーグループの索引
+
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use serde::{Deserialize, Serialize};
+
+use super::{read, write, Observer};
+use crate::macros::payload_impl;
+use crate::payloads::types::{Channel, User, UserGroup, Uuid};
+use crate::payloads::StampCreatedPayload;
+use crate::Event;
+
+/// スタンプ
+///
+/// [`StampCreatedPayload`]から作られます。作成者は[`Directory::user`]で引けます。
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Stamp {
+    pub id: Uuid,
+    pub name: String,
+    pub file_id: Uuid,
+    pub creator_id: Uuid,
+}
+
+payload_impl! {Stamp}
+
+impl From<StampCreatedPayload> for Stamp {
+    fn from(payload: StampCreatedPayload) -> Self {
+        let StampCreatedPayload {
+            id,
+            name,
+            file_id,
+            creator,
+            ..
+        } = payload;
+        Self {
+            id,
+            name,
+            file_id,
+            creator_id: creator.id,
+        }
+    }
+}
+
+/// [`Directory`]の内容を書き出したもの
+///
+/// JSONから読み込み、[`Directory::seed`]で起動時の初期値として使うことができます。
+/// 各フィールドは省略可能です。
+///
+/// ## Example
+/// ```
+/// # fn main() -> Result<(), serde_json::Error> {
+/// use traq_bot_http::cache::{Directory, Snapshot};
+///
+/// let snapshot: Snapshot = r##"{
+///     "users": [{
+///         "id": "dfdff0c9-5de0-46ee-9721-2525e8bb3d45",
+///         "name": "takashi_trap",
+///         "displayName": "寺田 健二",
+///         "iconId": "2bc06cda-bdb9-4a68-8000-62f907f36a92",
+///         "bot": false
+///     }]
+/// }"##.parse()?;
+/// let directory = Directory::from_snapshot(snapshot);
+/// let user = directory.user_by_name("takashi_trap").unwrap();
+/// assert_eq!(user.display_name, "寺田 健二");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Snapshot {
+    #[serde(default)]
+    pub users: Vec<User>,
+    #[serde(default)]
+    pub channels: Vec<Channel>,
+    #[serde(default)]
+    pub stamps: Vec<Stamp>,
+    #[serde(default)]
+    pub user_groups: Vec<UserGroup>,
+}
+
+payload_impl! {Snapshot}
+
+/// IDと名前の両方から引ける表
+#[derive(Debug, Clone)]
+struct Index<T> {
+    by_id: HashMap<Uuid, T>,
+    by_name: HashMap<String, Uuid>,
+    name_of: fn(&T) -> &str,
+}
+
+impl<T: Clone> Index<T> {
+    fn new(name_of: fn(&T) -> &str) -> Self {
+        Self {
+            by_id: HashMap::new(),
+            by_name: HashMap::new(),
+            name_of,
+        }
+    }
+
+    fn insert(&mut self, id: Uuid, value: T) {
+        let name = (self.name_of)(&value).to_string();
+        if let Some(old) = self.by_id.get(&id) {
+            let old_name = (self.name_of)(old);
+            if old_name != name && self.by_name.get(old_name) == Some(&id) {
+                self.by_name.remove(old_name);
+            }
+        }
+        self.by_name.insert(name, id.clone());
+        self.by_id.insert(id, value);
+    }
+
+    fn get(&self, id: &Uuid) -> Option<T> {
+        self.by_id.get(id).cloned()
+    }
+
+    fn get_by_name(&self, name: &str) -> Option<T> {
+        self.by_name.get(name).and_then(|id| self.get(id))
+    }
+
+    /// IDの順に並べた値
+    fn values(&self) -> Vec<T> {
+        let mut entries: Vec<_> = self.by_id.iter().collect();
+        entries.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+        entries.into_iter().map(|(_, v)| v.clone()).collect()
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Inner {
+    users: Index<User>,
+    channels: Index<Channel>,
+    stamps: Index<Stamp>,
+    user_groups: Index<UserGroup>,
+}
+
+impl Default for Inner {
+    fn default() -> Self {
+        Self {
+            users: Index::new(|u| &u.name),
+            channels: Index::new(|c| &c.path),
+            stamps: Index::new(|s| &s.name),
+            user_groups: Index::new(|g| &g.name),
+        }
+    }
+}
+
+impl Inner {
+    fn insert_user(&mut self, user: User) {
+        self.users.insert(user.id.clone(), user);
+    }
+
+    fn insert_channel(&mut self, channel: Channel) {
+        self.insert_user(channel.creator.clone());
+        self.channels.insert(channel.id.clone(), channel);
+    }
+
+    fn insert_stamp(&mut self, stamp: Stamp) {
+        self.stamps.insert(stamp.id.clone(), stamp);
+    }
+
+    fn insert_user_group(&mut self, group: UserGroup) {
+        self.user_groups.insert(group.id.clone(), group);
+    }
+
+    fn seed(&mut self, snapshot: Snapshot) {
+        let Snapshot {
+            users,
+            channels,
+            stamps,
+            user_groups,
+        } = snapshot;
+        users.into_iter().for_each(|u| self.insert_user(u));
+        channels.into_iter().for_each(|c| self.insert_channel(c));
+        stamps.into_iter().for_each(|s| self.insert_stamp(s));
+        user_groups
+            .into_iter()
+            .for_each(|g| self.insert_user_group(g));
+    }
+}
+
+/// イベントから集めたユーザー, チャンネル, スタンプ, ユーザーグループの索引
+///
+/// 以下のイベントのペイロードに含まれる情報で更新されます。
+///
+/// - [`Event::UserCreated`]
+/// - [`Event::ChannelCreated`]
+/// - [`Event::ChannelTopicChanged`]
+/// - [`Event::StampCreated`]
+/// - [`Event::UserGroupCreated`]
+///
+/// 同じIDの値は後から観測したもので上書きされます。
+///
+/// ## Example
+/// ```
+/// # fn main() -> Result<(), serde_json::Error> {
+/// use traq_bot_http::cache::{Directory, Observer};
+/// use traq_bot_http::payloads::StampCreatedPayload;
+/// use traq_bot_http::Event;
+///
+/// let payload: StampCreatedPayload = r##"{
+///     "eventTime": "2019-05-08T08:31:06.566228282Z",
+///     "id": "2bc06cda-bdb9-4a68-8000-62f907f36a92",
+///     "name": "naruhodo",
+///     "fileId": "2bc06cda-bdb9-4a68-8000-62f907f36a92",
+///     "creator": {
+///         "id": "dfdff0c9-5de0-46ee-9721-2525e8bb3d45",
+///         "name": "takashi_trap",
+///         "displayName": "寺田 健二",
+///         "iconId": "2bc06cda-bdb9-4a68-8000-62f907f36a92",
+///         "bot": false
+///     }
+/// }"##.parse()?;
+/// let directory = Directory::new();
+/// directory.observe(&Event::StampCreated(payload));
+/// let stamp = directory.stamp_by_name("naruhodo").unwrap();
+/// let creator = directory.user(&stamp.creator_id).unwrap();
+/// assert_eq!(creator.name, "takashi_trap");
+/// # Ok(())
+/// # }
+/// ```
+#[must_use]
+#[derive(Debug, Default)]
+pub struct Directory {
+    inner: RwLock<Inner>,
+}
+
+impl Directory {
+    /// 空の`Directory`を作成します。
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `snapshot`の内容で初期化された`Directory`を作成します。
+    pub fn from_snapshot(snapshot: Snapshot) -> Self {
+        let directory = Self::new();
+        directory.seed(snapshot);
+        directory
+    }
+
+    /// `snapshot`の内容を追加します。同じIDの値は上書きされます。
+    pub fn seed(&self, snapshot: Snapshot) {
+        write(&self.inner).seed(snapshot);
+    }
+
+    /// 現在の内容を書き出します。各値はIDの順に並びます。
+    #[must_use]
+    pub fn snapshot(&self) -> Snapshot {
+        let inner = read(&self.inner);
+        Snapshot {
+            users: inner.users.values(),
+            channels: inner.channels.values(),
+            stamps: inner.stamps.values(),
+            user_groups: inner.user_groups.values(),
+        }
+    }
+
+    /// IDからユーザーを引きます。
+    #[must_use]
+    pub fn user(&self, id: &Uuid) -> Option<User> {
+        read(&self.inner).users.get(id)
+    }
+
+    /// `takashi_trap`のようなユーザー名からユーザーを引きます。
+    #[must_use]
+    pub fn user_by_name(&self, name: &str) -> Option<User> {
+        read(&self.inner).users.get_by_name(name)
+    }
+
+    /// IDからチャンネルを引きます。
+    #[must_use]
+    pub fn channel(&self, id: &Uuid) -> Option<Channel> {
+        read(&self.inner).channels.get(id)
+    }
+
+    /// `#a/po`のようなパスからチャンネルを引きます。
+    #[must_use]
+    pub fn channel_by_path(&self, path: &str) -> Option<Channel> {
+        read(&self.inner).channels.get_by_name(path)
+    }
+
+    /// IDからスタンプを引きます。
+    #[must_use]
+    pub fn stamp(&self, id: &Uuid) -> Option<Stamp> {
+        read(&self.inner).stamps.get(id)
+    }
+
+    /// 名前からスタンプを引きます。
+    #[must_use]
+    pub fn stamp_by_name(&self, name: &str) -> Option<Stamp> {
+        read(&self.inner).stamps.get_by_name(name)
+    }
+
+    /// IDからユーザーグループを引きます。
+    #[must_use]
+    pub fn user_group(&self, id: &Uuid) -> Option<UserGroup> {
+        read(&self.inner).user_groups.get(id)
+    }
+
+    /// 名前からユーザーグループを引きます。
+    #[must_use]
+    pub fn user_group_by_name(&self, name: &str) -> Option<UserGroup> {
+        read(&self.inner).user_groups.get_by_name(name)
+    }
+
+    /// ユーザーを追加します。同じIDのユーザーは上書きされます。
+    pub fn insert_user(&self, user: User) {
+        write(&self.inner).insert_user(user);
+    }
+
+    /// チャンネルを追加します。同じIDのチャンネルは上書きされます。
+    pub fn insert_channel(&self, channel: Channel) {
+        write(&self.inner).insert_channel(channel);
+    }
+
+    /// スタンプを追加します。同じIDのスタンプは上書きされます。
+    pub fn insert_stamp(&self, stamp: Stamp) {
+        write(&self.inner).insert_stamp(stamp);
+    }
+
+    /// ユーザーグループを追加します。同じIDのユーザーグループは上書きされます。
+    pub fn insert_user_group(&self, group: UserGroup) {
+        write(&self.inner).insert_user_group(group);
+    }
+}
+
+impl Observer for Directory {
+    fn observe(&self, event: &Event) {
+        match event {
+            Event::UserCreated(payload) => self.insert_user(payload.user.clone()),
+            Event::ChannelCreated(payload) => self.insert_channel(payload.channel.clone()),
+            Event::ChannelTopicChanged(payload) => {
+                let mut inner = write(&self.inner);
+                inner.insert_channel(payload.channel.clone());
+                inner.insert_user(payload.updater.clone());
+            }
+            Event::StampCreated(payload) => {
+                let mut inner = write(&self.inner);
+                inner.insert_user(payload.creator.clone());
+                inner.insert_stamp(payload.clone().into());
+            }
+            Event::UserGroupCreated(payload) => self.insert_user_group(payload.group.clone()),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{channel_a_po, takashi_trap, testdata, uuid};
+
+    use crate::payloads::{
+        ChannelCreatedPayload, ChannelTopicChangedPayload, UserCreatedPayload,
+        UserGroupCreatedPayload,
+    };
+
+    #[test]
+    fn observe_payloads() {
+        let directory = Directory::new();
+        let user: UserCreatedPayload = testdata("user/user_created.json");
+        directory.observe(&user.clone().into());
+        assert_eq!(directory.user(&user.user.id), Some(user.user.clone()));
+        assert_eq!(directory.user_by_name("takashi_trap"), Some(user.user));
+
+        let channel: ChannelCreatedPayload = testdata("channel/channel_created.json");
+        directory.observe(&channel.clone().into());
+        assert_eq!(
+            directory.channel_by_path(&channel.channel.path),
+            Some(channel.channel.clone())
+        );
+        assert_eq!(
+            directory.user(&channel.channel.creator.id),
+            Some(channel.channel.creator)
+        );
+
+        let stamp: StampCreatedPayload = testdata("stamp/stamp_created.json");
+        directory.observe(&stamp.clone().into());
+        assert_eq!(directory.stamp(&stamp.id), Some(stamp.clone().into()));
+        assert_eq!(
+            directory.stamp_by_name(&stamp.name).map(|s| s.creator_id),
+            Some(stamp.creator.id)
+        );
+
+        let group: UserGroupCreatedPayload = testdata("user-group/user_group_created.json");
+        directory.observe(&group.clone().into());
+        assert_eq!(
+            directory.user_group_by_name(&group.group.name),
+            Some(group.group)
+        );
+    }
+
+    #[test]
+    fn channel_renamed() {
+        let directory = Directory::new();
+        let topic_changed: ChannelTopicChangedPayload =
+            testdata("channel/channel_topic_changed.json");
+        directory.observe(&topic_changed.clone().into());
+        let mut channel = topic_changed.channel;
+        let old_path = channel.path.clone();
+        channel.path = "#renamed".to_string();
+        directory.insert_channel(channel.clone());
+        assert_eq!(directory.channel_by_path(&old_path), None);
+        assert_eq!(directory.channel_by_path("#renamed"), Some(channel));
+    }
+
+    #[test]
+    fn snapshot_round_trip() {
+        let snapshot = Snapshot {
+            users: vec![takashi_trap()],
+            channels: vec![channel_a_po()],
+            ..Default::default()
+        };
+        let directory = Directory::from_snapshot(snapshot.to_string().parse().unwrap());
+        assert_eq!(
+            directory.channel(&uuid("f86c925c-3002-4ba5-939a-c92344e534f9")),
+            Some(channel_a_po())
+        );
+        assert_eq!(directory.snapshot(), snapshot);
+        let empty: Snapshot = "{}".parse().unwrap();
+        assert_eq!(empty, Snapshot::default());
+    }
+}
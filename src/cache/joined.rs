@@ -0,0 +1,412 @@
+//! BOTが参加しているチャンネルの集合
+
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+use std::sync::RwLock;
+
+use super::{read, write, Observer};
+use crate::payloads::types::{Channel, Uuid};
+use crate::Event;
+
+/// [`JoinedChannels`]の内容を永続化する先
+///
+/// [`JoinedChannels`]は変化がある度に[`save`]を呼び出します。
+/// `()`は何も保存しない実装です。
+///
+/// [`save`]: ChannelStorage::save
+pub trait ChannelStorage {
+    type Error;
+
+    /// 保存されているチャンネルを読み込みます。
+    ///
+    /// # Errors
+    /// 読み込みに失敗した場合
+    fn load(&self) -> Result<Vec<Channel>, Self::Error>;
+
+    /// `channels`を保存します。以前に保存された内容は置き換えられます。
+    ///
+    /// # Errors
+    /// 保存に失敗した場合
+    fn save(&self, channels: &[Channel]) -> Result<(), Self::Error>;
+}
+
+impl ChannelStorage for () {
+    type Error = Infallible;
+
+    fn load(&self) -> Result<Vec<Channel>, Self::Error> {
+        Ok(Vec::new())
+    }
+
+    fn save(&self, _channels: &[Channel]) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// チャンネルの配列をJSONファイルに保存する[`ChannelStorage`]
+///
+/// ファイルが存在しない場合、[`load`]は空の配列を返します。
+/// [`save`]は同じディレクトリの一時ファイル(`{path}.tmp`)に書き込んでから置き換えるため、
+/// 書き込みの途中で失敗しても元のファイルは壊れません。
+///
+/// [`load`]: ChannelStorage::load
+/// [`save`]: ChannelStorage::save
+#[must_use]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsonFileStorage {
+    path: PathBuf,
+}
+
+impl JsonFileStorage {
+    /// `path`のファイルに保存する`JsonFileStorage`を作成します。
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl ChannelStorage for JsonFileStorage {
+    type Error = io::Error;
+
+    fn load(&self) -> Result<Vec<Channel>, Self::Error> {
+        match std::fs::read(&self.path) {
+            Ok(data) => Ok(serde_json::from_slice(&data)?),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn save(&self, channels: &[Channel]) -> Result<(), Self::Error> {
+        let data = serde_json::to_vec(channels)?;
+        let mut tmp = self.path.clone().into_os_string();
+        tmp.push(".tmp");
+        std::fs::write(&tmp, data)?;
+        std::fs::rename(&tmp, &self.path)
+    }
+}
+
+/// [`JoinedChannels`]の変化
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChannelChange {
+    /// チャンネルに参加した
+    Joined(Channel),
+    /// チャンネルから退出した
+    Left(Channel),
+}
+
+impl ChannelChange {
+    /// 変化したチャンネルを返します。
+    #[must_use]
+    pub fn channel(&self) -> &Channel {
+        match self {
+            Self::Joined(channel) | Self::Left(channel) => channel,
+        }
+    }
+}
+
+type ChangeListener = Box<dyn Fn(&ChannelChange) + Send + Sync>;
+type ErrorListener<E> = Box<dyn Fn(&E) + Send + Sync>;
+
+/// BOTが参加しているチャンネルの集合
+///
+/// [`Event::Joined`]と[`Event::Left`]で更新されます。
+/// 参加していないチャンネルからの退出など、集合が変化しないイベントは無視されます。
+///
+/// 集合が変化する度に[`on_change`]で登録した関数が呼ばれます。
+/// また、参加済みのチャンネルの情報が更新された場合も含め、保持する内容が変わる度に`Storage`に保存されます。
+///
+/// ## Example
+/// ```
+/// # fn main() -> Result<(), serde_json::Error> {
+/// use traq_bot_http::cache::{ChannelChange, JoinedChannels, Observer};
+/// use traq_bot_http::payloads::JoinedPayload;
+/// use traq_bot_http::Event;
+///
+/// let payload: JoinedPayload = r##"{
+///     "eventTime": "2019-05-08T13:49:13.769110201Z",
+///     "channel": {
+///         "id": "f86c925c-3002-4ba5-939a-c92344e534f9",
+///         "name": "po",
+///         "path": "#a/po",
+///         "parentId": "ea452867-553b-4808-a14f-a47ee0009ee6",
+///         "creator": {
+///             "id": "dfdff0c9-5de0-46ee-9721-2525e8bb3d45",
+///             "name": "takashi_trap",
+///             "displayName": "寺田 健二",
+///             "iconId": "2bc06cda-bdb9-4a68-8000-62f907f36a92",
+///             "bot": false
+///         },
+///         "createdAt": "2018-04-25T12:22:02Z",
+///         "updatedAt": "2018-04-25T12:22:02Z"
+///     }
+/// }"##.parse()?;
+/// let channel_id = payload.channel.id.clone();
+/// let joined = JoinedChannels::new().on_change(|change| {
+///     if let ChannelChange::Joined(channel) = change {
+///         println!("joined {}", channel.path);
+///     }
+/// });
+/// joined.observe(&Event::Joined(payload));
+/// assert!(joined.contains(&channel_id));
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`on_change`]: JoinedChannels::on_change
+#[must_use]
+pub struct JoinedChannels<Storage = ()>
+where
+    Storage: ChannelStorage,
+{
+    channels: RwLock<HashMap<Uuid, Channel>>,
+    storage: Storage,
+    change_listeners: Vec<ChangeListener>,
+    error_listeners: Vec<ErrorListener<Storage::Error>>,
+}
+
+impl JoinedChannels {
+    /// 永続化しない、空の`JoinedChannels`を作成します。
+    pub fn new() -> Self {
+        Self::from_parts(HashMap::new(), ())
+    }
+}
+
+impl Default for JoinedChannels {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Storage> JoinedChannels<Storage>
+where
+    Storage: ChannelStorage,
+{
+    fn from_parts(channels: HashMap<Uuid, Channel>, storage: Storage) -> Self {
+        Self {
+            channels: RwLock::new(channels),
+            storage,
+            change_listeners: Vec::new(),
+            error_listeners: Vec::new(),
+        }
+    }
+
+    /// `storage`から読み込んだチャンネルで初期化された`JoinedChannels`を作成します。
+    ///
+    /// # Errors
+    /// `storage`からの読み込みに失敗した場合
+    pub fn restore(storage: Storage) -> Result<Self, Storage::Error> {
+        let channels = storage
+            .load()?
+            .into_iter()
+            .map(|c| (c.id.clone(), c))
+            .collect();
+        Ok(Self::from_parts(channels, storage))
+    }
+
+    /// 集合が変化した時に呼ばれる関数を登録します。
+    ///
+    /// `listener`は保存の後、内部のロックを解放してから呼ばれます。
+    pub fn on_change<F>(mut self, listener: F) -> Self
+    where
+        F: Fn(&ChannelChange) + Send + Sync + 'static,
+    {
+        self.change_listeners.push(Box::new(listener));
+        self
+    }
+
+    /// `Storage`への保存に失敗した時に呼ばれる関数を登録します。
+    ///
+    /// 保存に失敗した場合も集合は更新され、[`on_change`]で登録した関数が呼ばれます。
+    ///
+    /// [`on_change`]: JoinedChannels::on_change
+    pub fn on_storage_error<F>(mut self, listener: F) -> Self
+    where
+        F: Fn(&Storage::Error) + Send + Sync + 'static,
+    {
+        self.error_listeners.push(Box::new(listener));
+        self
+    }
+
+    /// `channel_id`のチャンネルに参加しているかを返します。
+    #[must_use]
+    pub fn contains(&self, channel_id: &Uuid) -> bool {
+        read(&self.channels).contains_key(channel_id)
+    }
+
+    /// 参加している`channel_id`のチャンネルを返します。
+    #[must_use]
+    pub fn get(&self, channel_id: &Uuid) -> Option<Channel> {
+        read(&self.channels).get(channel_id).cloned()
+    }
+
+    /// 参加しているチャンネルをIDの順に返します。
+    #[must_use]
+    pub fn channels(&self) -> Vec<Channel> {
+        sorted(&read(&self.channels))
+    }
+
+    /// 参加しているチャンネルの数を返します。
+    #[must_use]
+    pub fn len(&self) -> usize {
+        read(&self.channels).len()
+    }
+
+    /// 参加しているチャンネルが無いかを返します。
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        read(&self.channels).is_empty()
+    }
+
+    /// `Storage`への参照を返します。
+    pub fn storage(&self) -> &Storage {
+        &self.storage
+    }
+
+    /// 変化を反映し、保持する内容が変わった場合は保存します。
+    ///
+    /// 集合が変化した場合はその内容を、保存した場合はその結果を返します。
+    fn apply(
+        &self,
+        change: ChannelChange,
+    ) -> (Option<ChannelChange>, Option<Result<(), Storage::Error>>) {
+        let mut channels = write(&self.channels);
+        let (changed, modified) = match &change {
+            ChannelChange::Joined(channel) => {
+                match channels.insert(channel.id.clone(), channel.clone()) {
+                    Some(previous) => (false, previous != *channel),
+                    None => (true, true),
+                }
+            }
+            ChannelChange::Left(channel) => {
+                let removed = channels.remove(&channel.id).is_some();
+                (removed, removed)
+            }
+        };
+        let saved = modified.then(|| self.storage.save(&sorted(&channels)));
+        (changed.then_some(change), saved)
+    }
+}
+
+fn sorted(channels: &HashMap<Uuid, Channel>) -> Vec<Channel> {
+    let mut channels: Vec<_> = channels.values().cloned().collect();
+    channels.sort_unstable_by(|a, b| a.id.cmp(&b.id));
+    channels
+}
+
+impl<Storage> Observer for JoinedChannels<Storage>
+where
+    Storage: ChannelStorage,
+{
+    fn observe(&self, event: &Event) {
+        let change = match event {
+            Event::Joined(payload) => ChannelChange::Joined(payload.channel.clone()),
+            Event::Left(payload) => ChannelChange::Left(payload.channel.clone()),
+            _ => return,
+        };
+        let (change, saved) = self.apply(change);
+        if let Some(Err(e)) = saved {
+            self.error_listeners.iter().for_each(|l| l(&e));
+        }
+        if let Some(change) = change {
+            self.change_listeners.iter().for_each(|l| l(&change));
+        }
+    }
+}
+
+impl<Storage> fmt::Debug for JoinedChannels<Storage>
+where
+    Storage: ChannelStorage + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JoinedChannels")
+            .field("channels", &self.channels)
+            .field("storage", &self.storage)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{channel_a_po, testdata};
+
+    use std::sync::{Arc, Mutex};
+
+    use crate::payloads::{JoinedPayload, LeftPayload};
+
+    #[test]
+    fn join_and_leave() {
+        let changes = Arc::new(Mutex::new(Vec::new()));
+        let joined_channels = JoinedChannels::new().on_change({
+            let changes = Arc::clone(&changes);
+            move |c| changes.lock().unwrap().push(c.clone())
+        });
+        let joined: JoinedPayload = testdata("system/joined.json");
+        let left: LeftPayload = testdata("system/left.json");
+        let channel = joined.channel.clone();
+        joined_channels.observe(&joined.clone().into());
+        joined_channels.observe(&joined.into());
+        assert!(joined_channels.contains(&channel.id));
+        assert_eq!(joined_channels.channels(), [channel.clone()]);
+        joined_channels.observe(&left.clone().into());
+        joined_channels.observe(&left.into());
+        assert!(joined_channels.is_empty());
+        assert_eq!(
+            *changes.lock().unwrap(),
+            [
+                ChannelChange::Joined(channel.clone()),
+                ChannelChange::Left(channel)
+            ]
+        );
+    }
+
+    #[test]
+    fn restore_from_file() {
+        let path =
+            std::env::temp_dir().join(format!("traq-bot-http-joined-{}.json", std::process::id()));
+        let storage = JsonFileStorage::new(&path);
+        let joined_channels = JoinedChannels::restore(storage.clone()).unwrap();
+        assert!(joined_channels.is_empty());
+        let mut joined: JoinedPayload = testdata("system/joined.json");
+        joined_channels.observe(&joined.clone().into());
+        let restored = JoinedChannels::restore(storage.clone()).unwrap();
+        assert_eq!(restored.channels(), [channel_a_po()]);
+
+        // 参加済みのチャンネルの情報が変わった場合も保存する
+        joined.channel.path = "#a/renamed".to_string();
+        joined_channels.observe(&joined.clone().into());
+        let restored = JoinedChannels::restore(storage).unwrap();
+        assert_eq!(restored.channels(), [joined.channel]);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    struct FailingStorage;
+
+    impl ChannelStorage for FailingStorage {
+        type Error = &'static str;
+
+        fn load(&self) -> Result<Vec<Channel>, Self::Error> {
+            Ok(Vec::new())
+        }
+
+        fn save(&self, _channels: &[Channel]) -> Result<(), Self::Error> {
+            Err("failed")
+        }
+    }
+
+    #[test]
+    fn storage_error() {
+        let errors = Arc::new(Mutex::new(Vec::new()));
+        let joined_channels = JoinedChannels::restore(FailingStorage)
+            .unwrap()
+            .on_storage_error({
+                let errors = Arc::clone(&errors);
+                move |e| errors.lock().unwrap().push(*e)
+            });
+        let joined: JoinedPayload = testdata("system/joined.json");
+        joined_channels.observe(&joined.into());
+        assert_eq!(*errors.lock().unwrap(), ["failed"]);
+        assert_eq!(joined_channels.len(), 1);
+    }
+}
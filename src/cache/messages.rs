@@ -0,0 +1,566 @@
+//! 受け取ったメッセージの履歴
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::RwLock;
+use std::time::Duration;
+
+use super::{read, write, Observer};
+use crate::message::{text_diff, TextDiff};
+use crate::payloads::types::{Message, Timestamp, Uuid};
+use crate::payloads::{
+    DirectMessageDeletedPayload, DirectMessageUpdatedPayload, MessageDeletedPayload,
+    MessageUpdatedPayload,
+};
+use crate::Event;
+
+/// 削除・更新される前のメッセージ
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PreviousMessage {
+    /// [`MessageCache`]に残っていたメッセージ
+    Known(Box<Message>),
+    /// 作成を観測していない、または[`MessageCache`]から追い出されたメッセージ
+    Unknown,
+}
+
+impl PreviousMessage {
+    /// [`PreviousMessage::Known`]であればそのメッセージを返します。
+    #[must_use]
+    pub fn known(&self) -> Option<&Message> {
+        match self {
+            Self::Known(message) => Some(message),
+            Self::Unknown => None,
+        }
+    }
+}
+
+/// 削除前のメッセージを付け加えたペイロード
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Deleted<Payload> {
+    pub payload: Payload,
+    pub previous: PreviousMessage,
+}
+
+/// 更新前のメッセージと本文の差分を付け加えたペイロード
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Updated<Payload> {
+    pub payload: Payload,
+    pub previous: PreviousMessage,
+    /// 更新前の本文から更新後の本文への差分。更新前のメッセージが分からない場合は`None`
+    pub diff: Option<TextDiff>,
+}
+
+impl<Payload> Updated<Payload> {
+    fn new(payload: Payload, message: &Message, previous: PreviousMessage) -> Self {
+        let diff = previous.known().map(|p| text_diff(&p.text, &message.text));
+        Self {
+            payload,
+            previous,
+            diff,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    message: Message,
+    /// 直前の更新で置き換えられたメッセージ
+    previous: Option<Message>,
+    deleted: bool,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    entries: HashMap<Uuid, Entry>,
+    /// キャッシュした順のIDと時刻
+    order: VecDeque<(Uuid, Timestamp)>,
+    /// 観測したイベントの最新の時刻
+    latest: Option<Timestamp>,
+}
+
+impl Inner {
+    fn touch(&mut self, at: Timestamp) {
+        self.latest = self.latest.max(Some(at));
+    }
+
+    fn push(&mut self, at: Timestamp, message: Message, previous: Option<Message>) {
+        self.order.push_back((message.id.clone(), at));
+        let entry = Entry {
+            message,
+            previous,
+            deleted: false,
+        };
+        self.entries.insert(entry.message.id.clone(), entry);
+    }
+
+    fn evict(&mut self, capacity: usize, max_age: Duration) {
+        let oldest = self.latest.and_then(|t| before(t, max_age));
+        while let Some((id, at)) = self.order.front() {
+            let expired = oldest.is_some_and(|o| *at < o);
+            if self.order.len() <= capacity && !expired {
+                break;
+            }
+            self.entries.remove(id);
+            self.order.pop_front();
+        }
+    }
+}
+
+/// `at`の`duration`前の時点。表現できる範囲の外になる場合は`None`
+fn before(at: Timestamp, duration: Duration) -> Option<Timestamp> {
+    let secs = at
+        .unix_seconds()
+        .checked_sub(i64::try_from(duration.as_secs()).ok()?)?;
+    let (secs, nanos) = match at.subsec_nanos().checked_sub(duration.subsec_nanos()) {
+        Some(nanos) => (secs, nanos),
+        None => (
+            secs.checked_sub(1)?,
+            at.subsec_nanos() + 1_000_000_000 - duration.subsec_nanos(),
+        ),
+    };
+    Timestamp::from_unix(secs, nanos).ok()
+}
+
+/// 受け取ったメッセージを保持し、削除・更新の前の内容を引けるようにするキャッシュ
+///
+/// [`Event::MessageCreated`]と[`Event::DirectMessageCreated`]のメッセージを保持します。
+/// 保持するメッセージは最大`capacity`件で、それを超えると古いものから追い出されます。
+/// また、観測した最新のイベントの`eventTime`から`max_age`より前にキャッシュしたものも追い出されます。
+///
+/// 削除・更新のイベントは何度反映しても同じ結果になるため、
+/// [`Observer`]としての登録と[`with_history`]による前の内容の取得を併用できます。
+///
+/// ## Example
+/// ```
+/// # fn main() -> Result<(), serde_json::Error> {
+/// use std::time::Duration;
+///
+/// use traq_bot_http::cache::{MessageCache, Observer, PreviousMessage};
+/// use traq_bot_http::payloads::{MessageCreatedPayload, MessageDeletedPayload};
+/// use traq_bot_http::Event;
+///
+/// let created: MessageCreatedPayload = r##"{
+///     "eventTime": "2019-05-08T13:33:51.690308239Z",
+///     "message": {
+///         "id": "bc9106b3-f9b2-4eca-9ba1-72b39b40954e",
+///         "user": {
+///             "id": "dfdff0c9-5de0-46ee-9721-2525e8bb3d45",
+///             "name": "takashi_trap",
+///             "displayName": "寺田 健二",
+///             "iconId": "2bc06cda-bdb9-4a68-8000-62f907f36a92",
+///             "bot": false
+///         },
+///         "channelId": "9aba50da-f605-4cd0-a428-5e4558cb911e",
+///         "text": "こんにちは",
+///         "plainText": "こんにちは",
+///         "embedded": [],
+///         "createdAt": "2019-05-08T13:33:51.632149265Z",
+///         "updatedAt": "2019-05-08T13:33:51.632149265Z"
+///     }
+/// }"##.parse()?;
+/// let deleted: MessageDeletedPayload = r##"{
+///     "eventTime": "2019-05-08T13:36:09.421492525Z",
+///     "message": {
+///         "id": "bc9106b3-f9b2-4eca-9ba1-72b39b40954e",
+///         "channelId": "9aba50da-f605-4cd0-a428-5e4558cb911e"
+///     }
+/// }"##.parse()?;
+/// let cache = MessageCache::new(1000, Duration::from_secs(60 * 60));
+/// cache.observe(&Event::MessageCreated(created));
+/// let previous = cache.delete(deleted.event_time, &deleted.message.id);
+/// assert_eq!(previous.known().unwrap().text, "こんにちは");
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`with_history`]: MessageCache::with_history
+#[must_use]
+#[derive(Debug)]
+pub struct MessageCache {
+    inner: RwLock<Inner>,
+    capacity: usize,
+    max_age: Duration,
+}
+
+impl MessageCache {
+    /// 最大`capacity`件のメッセージを`max_age`の間保持する`MessageCache`を作成します。
+    pub fn new(capacity: usize, max_age: Duration) -> Self {
+        Self {
+            inner: RwLock::default(),
+            capacity,
+            max_age,
+        }
+    }
+
+    /// 保持しているメッセージの数を返します。削除されたメッセージも含みます。
+    #[must_use]
+    pub fn len(&self) -> usize {
+        read(&self.inner).entries.len()
+    }
+
+    /// 保持しているメッセージが無い場合に`true`を返します。
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        read(&self.inner).entries.is_empty()
+    }
+
+    /// IDからメッセージを引きます。削除されたメッセージについては`None`を返します。
+    #[must_use]
+    pub fn get(&self, id: &Uuid) -> Option<Message> {
+        let inner = read(&self.inner);
+        let entry = inner.entries.get(id)?;
+        (!entry.deleted).then(|| entry.message.clone())
+    }
+
+    /// `at`の時点で作成された`message`を追加します。
+    pub fn insert(&self, at: Timestamp, message: Message) {
+        let mut inner = write(&self.inner);
+        inner.touch(at);
+        match inner.entries.get_mut(&message.id) {
+            Some(entry) => entry.message = message,
+            None => inner.push(at, message, None),
+        }
+        inner.evict(self.capacity, self.max_age);
+    }
+
+    /// `at`の時点でメッセージが`message`に更新されたことを反映し、更新前のメッセージを返します。
+    ///
+    /// 同じ更新を既に反映している場合は、その時の更新前のメッセージを返します。
+    pub fn update(&self, at: Timestamp, message: &Message) -> PreviousMessage {
+        let mut inner = write(&self.inner);
+        inner.touch(at);
+        let previous = match inner.entries.get_mut(&message.id) {
+            Some(entry) if &entry.message == message => entry.previous.clone(),
+            Some(entry) => {
+                let previous = std::mem::replace(&mut entry.message, message.clone());
+                entry.previous = Some(previous.clone());
+                Some(previous)
+            }
+            None => {
+                inner.push(at, message.clone(), None);
+                None
+            }
+        };
+        inner.evict(self.capacity, self.max_age);
+        previous.map_or(PreviousMessage::Unknown, |m| {
+            PreviousMessage::Known(Box::new(m))
+        })
+    }
+
+    /// `at`の時点でメッセージ`id`が削除されたことを反映し、削除前のメッセージを返します。
+    ///
+    /// 削除されたメッセージは追い出されるまで保持されるため、
+    /// 同じ削除を既に反映している場合も削除前のメッセージを返します。
+    pub fn delete(&self, at: Timestamp, id: &Uuid) -> PreviousMessage {
+        let mut inner = write(&self.inner);
+        inner.touch(at);
+        let previous = inner.entries.get_mut(id).map(|entry| {
+            entry.deleted = true;
+            entry.message.clone()
+        });
+        inner.evict(self.capacity, self.max_age);
+        previous.map_or(PreviousMessage::Unknown, |m| {
+            PreviousMessage::Known(Box::new(m))
+        })
+    }
+}
+
+impl Observer for MessageCache {
+    fn observe(&self, event: &Event) {
+        match event {
+            Event::MessageCreated(p) => self.insert(p.event_time, p.message.clone()),
+            Event::DirectMessageCreated(p) => self.insert(p.event_time, p.message.clone()),
+            Event::MessageUpdated(p) => {
+                self.update(p.event_time, &p.message);
+            }
+            Event::DirectMessageUpdated(p) => {
+                self.update(p.event_time, &p.message);
+            }
+            Event::MessageDeleted(p) => {
+                self.delete(p.event_time, &p.message.id);
+            }
+            Event::DirectMessageDeleted(p) => {
+                self.delete(p.event_time, &p.message.id);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// [`MessageCache`]で前の内容を付け加えられるリクエスト
+///
+/// [`WithHistory`]が受け取るリクエストの型です。
+pub trait HistoryRequest {
+    /// 前の内容を付け加えたリクエスト
+    type Output;
+
+    /// `cache`に変更を反映し、前の内容を付け加えます。
+    fn with_history(self, cache: &MessageCache) -> Self::Output;
+}
+
+impl HistoryRequest for MessageDeletedPayload {
+    type Output = Deleted<Self>;
+
+    fn with_history(self, cache: &MessageCache) -> Self::Output {
+        let previous = cache.delete(self.event_time, &self.message.id);
+        Deleted {
+            payload: self,
+            previous,
+        }
+    }
+}
+
+impl HistoryRequest for DirectMessageDeletedPayload {
+    type Output = Deleted<Self>;
+
+    fn with_history(self, cache: &MessageCache) -> Self::Output {
+        let previous = cache.delete(self.event_time, &self.message.id);
+        Deleted {
+            payload: self,
+            previous,
+        }
+    }
+}
+
+impl HistoryRequest for MessageUpdatedPayload {
+    type Output = Updated<Self>;
+
+    fn with_history(self, cache: &MessageCache) -> Self::Output {
+        let previous = cache.update(self.event_time, &self.message);
+        let message = self.message.clone();
+        Updated::new(self, &message, previous)
+    }
+}
+
+impl HistoryRequest for DirectMessageUpdatedPayload {
+    type Output = Updated<Self>;
+
+    fn with_history(self, cache: &MessageCache) -> Self::Output {
+        let previous = cache.update(self.event_time, &self.message);
+        let message = self.message.clone();
+        Updated::new(self, &message, previous)
+    }
+}
+
+impl<State, Req> HistoryRequest for (State, Req)
+where
+    Req: HistoryRequest,
+{
+    type Output = (State, Req::Output);
+
+    fn with_history(self, cache: &MessageCache) -> Self::Output {
+        let (state, req) = self;
+        (state, req.with_history(cache))
+    }
+}
+
+#[cfg(feature = "tower")]
+mod service {
+    use std::sync::Arc;
+    use std::task::{Context, Poll};
+
+    use tower_service::Service;
+
+    use super::{HistoryRequest, MessageCache};
+
+    /// 削除・更新のペイロードに[`MessageCache`]から前の内容を付け加えて、内部の[`Service`]を呼び出す[`Service`]です。
+    ///
+    /// [`MessageCache::with_history`]で作成します。
+    /// [`Handler::on_message_deleted`]などに登録すると、内部の`Service`は以下を受け取ります。
+    ///
+    /// - [`MessageDeletedPayload`], [`DirectMessageDeletedPayload`]: [`Deleted`]
+    /// - [`MessageUpdatedPayload`], [`DirectMessageUpdatedPayload`]: [`Updated`]
+    ///
+    /// [`Service`]: tower::Service
+    /// [`Handler::on_message_deleted`]: crate::Handler::on_message_deleted
+    /// [`MessageDeletedPayload`]: crate::payloads::MessageDeletedPayload
+    /// [`DirectMessageDeletedPayload`]: crate::payloads::DirectMessageDeletedPayload
+    /// [`MessageUpdatedPayload`]: crate::payloads::MessageUpdatedPayload
+    /// [`DirectMessageUpdatedPayload`]: crate::payloads::DirectMessageUpdatedPayload
+    /// [`Deleted`]: super::Deleted
+    /// [`Updated`]: super::Updated
+    #[must_use]
+    #[derive(Debug, Clone)]
+    pub struct WithHistory<Service> {
+        cache: Arc<MessageCache>,
+        service: Service,
+    }
+
+    impl<Srv, Req> Service<Req> for WithHistory<Srv>
+    where
+        Req: HistoryRequest,
+        Srv: Service<Req::Output>,
+    {
+        type Response = Srv::Response;
+        type Error = Srv::Error;
+        type Future = Srv::Future;
+
+        fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            self.service.poll_ready(cx)
+        }
+
+        fn call(&mut self, req: Req) -> Self::Future {
+            let req = req.with_history(&self.cache);
+            self.service.call(req)
+        }
+    }
+
+    impl MessageCache {
+        /// 削除・更新のペイロードに前の内容を付け加えて`service`に渡す[`WithHistory`]を作成します。
+        ///
+        /// # Example
+        ///
+        /// ```
+        /// use std::convert::Infallible;
+        /// use std::sync::Arc;
+        /// use std::time::Duration;
+        ///
+        /// use tower::service_fn;
+        /// use traq_bot_http::cache::{Deleted, MessageCache};
+        /// use traq_bot_http::{payloads, RequestParser};
+        ///
+        /// async fn on_deleted(
+        ///     deleted: Deleted<payloads::MessageDeletedPayload>,
+        /// ) -> Result<(), Infallible> {
+        ///     if let Some(message) = deleted.previous.known() {
+        ///         println!("deleted: {}", message.text);
+        ///     }
+        ///     Ok(())
+        /// }
+        ///
+        /// let cache = Arc::new(MessageCache::new(1000, Duration::from_secs(60 * 60)));
+        /// let parser = RequestParser::new("verification_token");
+        /// // 受け取るペイロードの型は推論できないため明示する
+        /// let handler = parser
+        ///     .into_handler()
+        ///     .on_message_deleted::<_, payloads::MessageDeletedPayload>(
+        ///         cache.with_history(service_fn(on_deleted)),
+        ///     )
+        ///     .observe(Arc::clone(&cache));
+        /// # let _ = handler;
+        /// ```
+        pub fn with_history<S>(self: &Arc<Self>, service: S) -> WithHistory<S> {
+            WithHistory {
+                cache: Arc::clone(self),
+                service,
+            }
+        }
+    }
+}
+
+#[cfg(feature = "tower")]
+pub use service::WithHistory;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{testdata, timestamp};
+
+    use crate::message::DiffLine;
+    use crate::payloads::MessageCreatedPayload;
+
+    fn with_text(mut message: Message, text: &str) -> Message {
+        text.clone_into(&mut message.text);
+        message
+    }
+
+    #[test]
+    fn update_and_delete() {
+        let cache = MessageCache::new(10, Duration::from_secs(60));
+        let mut created: MessageCreatedPayload = testdata("message/message_created.json");
+        created.message = with_text(created.message, "a\nb");
+        cache.observe(&created.clone().into());
+
+        let mut updated: MessageUpdatedPayload = testdata("message/message_updated.json");
+        updated.message = with_text(created.message.clone(), "a\nc");
+        // Observerとしての反映とwith_historyのどちらが先でも同じ結果になる
+        cache.observe(&updated.clone().into());
+        let updated = updated.with_history(&cache);
+        assert_eq!(
+            updated.previous,
+            PreviousMessage::Known(Box::new(created.message.clone()))
+        );
+        let diff = updated.diff.unwrap();
+        assert_eq!(
+            diff.lines,
+            [
+                DiffLine::Unchanged("a".to_string()),
+                DiffLine::Removed("b".to_string()),
+                DiffLine::Added("c".to_string()),
+            ]
+        );
+
+        let mut deleted: MessageDeletedPayload = testdata("message/message_deleted.json");
+        deleted.message.id.clone_from(&created.message.id);
+        let deleted = deleted.with_history(&cache);
+        assert_eq!(
+            deleted.previous.known().map(|m| m.text.as_str()),
+            Some("a\nc")
+        );
+        assert_eq!(cache.get(&created.message.id), None);
+    }
+
+    #[test]
+    fn unknown_message() {
+        let cache = MessageCache::new(10, Duration::from_secs(60));
+        let updated: MessageUpdatedPayload = testdata("message/message_updated.json");
+        let updated = updated.with_history(&cache);
+        assert_eq!(updated.previous, PreviousMessage::Unknown);
+        assert_eq!(updated.diff, None);
+        // 更新で初めて観測したメッセージも以降は保持される
+        let deleted: MessageDeletedPayload = testdata("message/message_deleted.json");
+        let deleted = deleted.with_history(&cache);
+        assert_eq!(
+            deleted.previous,
+            PreviousMessage::Known(Box::new(updated.payload.message))
+        );
+        let deleted = deleted.payload.with_history(&cache);
+        assert!(deleted.previous.known().is_some());
+        let cache = MessageCache::new(10, Duration::from_secs(60));
+        let deleted = deleted.payload.with_history(&cache);
+        assert_eq!(deleted.previous, PreviousMessage::Unknown);
+    }
+
+    #[test]
+    fn bounded_by_count() {
+        let cache = MessageCache::new(2, Duration::from_secs(60));
+        let created: MessageCreatedPayload = testdata("message/message_created.json");
+        let base = created.message;
+        let ids = [
+            "00000000-0000-0000-0000-000000000001",
+            "00000000-0000-0000-0000-000000000002",
+            "00000000-0000-0000-0000-000000000003",
+        ];
+        for id in ids {
+            let message = Message {
+                id: id.parse().unwrap(),
+                ..base.clone()
+            };
+            cache.insert(base.created_at, message);
+        }
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get(&ids[0].parse().unwrap()), None);
+        assert!(cache.get(&ids[2].parse().unwrap()).is_some());
+    }
+
+    #[test]
+    fn bounded_by_age() {
+        let cache = MessageCache::new(10, Duration::from_secs(60));
+        let created: MessageCreatedPayload = testdata("message/message_created.json");
+        cache.insert(timestamp("2019-05-08T13:33:00Z"), created.message.clone());
+        cache.delete(timestamp("2019-05-08T13:33:30Z"), &Uuid::default());
+        assert!(cache.get(&created.message.id).is_some());
+        cache.delete(timestamp("2019-05-08T13:34:01Z"), &Uuid::default());
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn before_subsec() {
+        let at = timestamp("2019-05-08T13:33:00.25Z");
+        let duration = Duration::from_millis(1_500);
+        assert_eq!(
+            before(at, duration),
+            Some(timestamp("2019-05-08T13:32:58.75Z"))
+        );
+        assert_eq!(before(at, Duration::MAX), None);
+    }
+}
@@ -0,0 +1,450 @@
+//! ユーザーグループのメンバーシップ
+
+use std::collections::{HashMap, HashSet};
+use std::sync::RwLock;
+
+use super::{read, write, Observer};
+use crate::payloads::types::{
+    GroupMember, Timestamp, UserGroup, UserGroupAdmin, UserGroupMember, UserGroupRole, Uuid,
+};
+use crate::Event;
+
+/// 最後に観測した時刻と値。`None`は削除されたことを表す
+#[derive(Debug, Clone)]
+struct Versioned<T> {
+    at: Timestamp,
+    value: Option<T>,
+}
+
+/// `at`の時点で`value`になったことを反映する。より新しい値が既にある場合は無視する
+fn apply<T>(
+    entries: &mut HashMap<Uuid, Versioned<T>>,
+    user_id: Uuid,
+    at: Timestamp,
+    value: Option<T>,
+) {
+    match entries.get_mut(&user_id) {
+        Some(entry) if entry.at > at => {}
+        Some(entry) => *entry = Versioned { at, value },
+        None => {
+            entries.insert(user_id, Versioned { at, value });
+        }
+    }
+}
+
+/// `at`の時点のスナップショットに含まれない、それより古いエントリーを削除されたものとする
+fn retain_snapshot<T>(
+    entries: &mut HashMap<Uuid, Versioned<T>>,
+    at: Timestamp,
+    snapshot: &HashSet<&Uuid>,
+) {
+    for (user_id, entry) in entries.iter_mut() {
+        if entry.at < at && !snapshot.contains(user_id) {
+            *entry = Versioned { at, value: None };
+        }
+    }
+}
+
+fn present<T>(entries: &HashMap<Uuid, Versioned<T>>) -> impl Iterator<Item = (&Uuid, &T)> {
+    entries
+        .iter()
+        .filter_map(|(id, e)| e.value.as_ref().map(|v| (id, v)))
+}
+
+#[derive(Debug, Clone, Default)]
+struct GroupEntry {
+    /// `UserGroupCreated`などで得たグループの情報。メンバーと管理者は`members`, `admins`が正
+    group: Option<UserGroup>,
+    members: HashMap<Uuid, Versioned<UserGroupRole>>,
+    admins: HashMap<Uuid, Versioned<()>>,
+}
+
+impl GroupEntry {
+    fn set_group(&mut self, at: Timestamp, group: UserGroup) {
+        let members = group.members.iter().map(|m| &m.user_id).collect();
+        retain_snapshot(&mut self.members, at, &members);
+        let admins = group.admins.iter().map(|a| &a.user_id).collect();
+        retain_snapshot(&mut self.admins, at, &admins);
+        for member in &group.members {
+            let role = member.role.clone();
+            apply(&mut self.members, member.user_id.clone(), at, Some(role));
+        }
+        for admin in &group.admins {
+            apply(&mut self.admins, admin.user_id.clone(), at, Some(()));
+        }
+        self.group = Some(group);
+    }
+
+    /// 役割が分からないメンバーの追加・更新。既知の役割は保たれる
+    fn member_present(&mut self, at: Timestamp, user_id: Uuid) {
+        let role = self
+            .members
+            .get(&user_id)
+            .and_then(|e| e.value.clone())
+            .unwrap_or_default();
+        apply(&mut self.members, user_id, at, Some(role));
+    }
+
+    fn materialize(&self) -> Option<UserGroup> {
+        let group = self.group.as_ref()?;
+        let mut members: Vec<_> = present(&self.members)
+            .map(|(user_id, role)| UserGroupMember {
+                group_id: group.id.clone(),
+                user_id: user_id.clone(),
+                role: role.clone(),
+            })
+            .collect();
+        members.sort_unstable_by(|a, b| a.user_id.cmp(&b.user_id));
+        let mut admins: Vec<_> = present(&self.admins)
+            .map(|(user_id, ())| UserGroupAdmin {
+                group_id: group.id.clone(),
+                user_id: user_id.clone(),
+            })
+            .collect();
+        admins.sort_unstable_by(|a, b| a.user_id.cmp(&b.user_id));
+        Some(UserGroup {
+            admins,
+            members,
+            ..group.clone()
+        })
+    }
+}
+
+/// エントリーが存在し、削除されていないか
+fn contains<T>(entries: &HashMap<Uuid, Versioned<T>>, user_id: &Uuid) -> bool {
+    entries.get(user_id).is_some_and(|e| e.value.is_some())
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    groups: HashMap<Uuid, GroupEntry>,
+    /// 削除されたグループ
+    deleted: HashSet<Uuid>,
+}
+
+impl Inner {
+    fn entry(&mut self, group_id: &Uuid) -> Option<&mut GroupEntry> {
+        if self.deleted.contains(group_id) {
+            return None;
+        }
+        Some(self.groups.entry(group_id.clone()).or_default())
+    }
+
+    fn member_event(&mut self, member: &GroupMember, f: impl FnOnce(&mut GroupEntry, Uuid)) {
+        if let Some(entry) = self.entry(&member.group_id) {
+            f(entry, member.user_id.clone());
+        }
+    }
+
+    fn delete(&mut self, group_id: &Uuid) {
+        self.groups.remove(group_id);
+        self.deleted.insert(group_id.clone());
+    }
+}
+
+/// `UserGroup*`イベントから組み立てる、ユーザーグループとそのメンバーシップ
+///
+/// 以下の規則で各イベントを反映します。
+///
+/// - メンバーと管理者の追加・削除は、ユーザー毎に`eventTime`が最も新しいものが優先されます。
+///   古いイベントが後から届いた場合は無視されます。
+/// - `UserGroupMemberAdded`と`UserGroupMemberUpdated`のペイロードには役割が含まれないため、
+///   既知の役割が保たれます。役割が分からないメンバーの役割は[`UserGroupRole::Unspecified`]です。
+/// - [`Event::UserGroupCreated`]と[`seed`]はグループ全体の状態として扱われ、
+///   それより古い時点で記録したメンバーと管理者のうち、含まれないものは削除されます。
+/// - [`Event::UserGroupCreated`]を観測していない(未知の)グループについても、メンバーシップは記録されます。
+///   未知のグループは[`group`]では`None`となりますが、[`groups_of`]や[`members`]には含まれます。
+/// - [`Event::UserGroupDeleted`]を観測したグループは取り除かれ、以降のイベントは全て無視されます。
+/// - [`Event::UserGroupUpdated`]は変更内容を含まないため無視されます。
+///
+/// ## Example
+/// ```
+/// # fn main() -> Result<(), serde_json::Error> {
+/// use traq_bot_http::cache::{Observer, UserGroups};
+/// use traq_bot_http::payloads::UserGroupMemberAddedPayload;
+/// use traq_bot_http::Event;
+///
+/// let payload: UserGroupMemberAddedPayload = r##"{
+///     "eventTime": "2023-08-25T04:04:32.962264Z",
+///     "groupMember": {
+///         "groupId": "f265bde2-04cc-4856-9008-3db1d953a539",
+///         "userId": "8e6a088f-9274-42c0-bb20-cee7913d144b"
+///     }
+/// }"##.parse()?;
+/// let group_id = payload.group_member.group_id.clone();
+/// let user_id = payload.group_member.user_id.clone();
+/// let user_groups = UserGroups::new();
+/// user_groups.observe(&Event::UserGroupMemberAdded(payload));
+/// assert_eq!(user_groups.groups_of(&user_id), [group_id.clone()]);
+/// // UserGroupCreatedを観測していないため、グループの情報は分からない
+/// assert!(user_groups.group(&group_id).is_none());
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`seed`]: UserGroups::seed
+/// [`group`]: UserGroups::group
+/// [`groups_of`]: UserGroups::groups_of
+/// [`members`]: UserGroups::members
+#[must_use]
+#[derive(Debug, Default)]
+pub struct UserGroups {
+    inner: RwLock<Inner>,
+}
+
+impl UserGroups {
+    /// 空の`UserGroups`を作成します。
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// APIから取得したものなど、既存のグループを追加します。
+    ///
+    /// 各グループのメンバーと管理者は`updated_at`の時点のものとして扱われます。
+    /// 既に記録しているメンバーと管理者のうち、`updated_at`より古く、`groups`に含まれないものは削除されます。
+    pub fn seed(&self, groups: impl IntoIterator<Item = UserGroup>) {
+        let mut inner = write(&self.inner);
+        for group in groups {
+            if let Some(entry) = inner.entry(&group.id) {
+                entry.set_group(group.updated_at, group);
+            }
+        }
+    }
+
+    /// IDからグループを引きます。メンバーと管理者はユーザーIDの順に並びます。
+    ///
+    /// 未知のグループと削除されたグループについては`None`を返します。
+    #[must_use]
+    pub fn group(&self, group_id: &Uuid) -> Option<UserGroup> {
+        read(&self.inner).groups.get(group_id)?.materialize()
+    }
+
+    /// [`Event::UserGroupCreated`]などでグループの情報を得ているかを返します。
+    #[must_use]
+    pub fn is_known(&self, group_id: &Uuid) -> bool {
+        read(&self.inner)
+            .groups
+            .get(group_id)
+            .is_some_and(|e| e.group.is_some())
+    }
+
+    /// 情報を得ている全てのグループをIDの順に返します。
+    #[must_use]
+    pub fn groups(&self) -> Vec<UserGroup> {
+        let inner = read(&self.inner);
+        let mut groups: Vec<_> = inner
+            .groups
+            .values()
+            .filter_map(GroupEntry::materialize)
+            .collect();
+        groups.sort_unstable_by(|a, b| a.id.cmp(&b.id));
+        groups
+    }
+
+    /// ユーザー`user_id`がメンバーであるグループのIDを返します。未知のグループも含みます。
+    #[must_use]
+    pub fn groups_of(&self, user_id: &Uuid) -> Vec<Uuid> {
+        self.collect_groups(|e| contains(&e.members, user_id))
+    }
+
+    /// ユーザー`user_id`が管理者であるグループのIDを返します。未知のグループも含みます。
+    #[must_use]
+    pub fn admin_groups_of(&self, user_id: &Uuid) -> Vec<Uuid> {
+        self.collect_groups(|e| contains(&e.admins, user_id))
+    }
+
+    /// グループ`group_id`のメンバーのユーザーIDを返します。未知のグループも含みます。
+    #[must_use]
+    pub fn members(&self, group_id: &Uuid) -> Vec<Uuid> {
+        let inner = read(&self.inner);
+        let Some(entry) = inner.groups.get(group_id) else {
+            return Vec::new();
+        };
+        let mut members: Vec<_> = present(&entry.members).map(|(id, _)| id.clone()).collect();
+        members.sort_unstable();
+        members
+    }
+
+    /// ユーザー`user_id`がグループ`group_id`のメンバーであるかを返します。
+    #[must_use]
+    pub fn is_member(&self, group_id: &Uuid, user_id: &Uuid) -> bool {
+        read(&self.inner)
+            .groups
+            .get(group_id)
+            .is_some_and(|e| contains(&e.members, user_id))
+    }
+
+    fn collect_groups(&self, f: impl Fn(&GroupEntry) -> bool) -> Vec<Uuid> {
+        let inner = read(&self.inner);
+        let mut groups: Vec<_> = inner
+            .groups
+            .iter()
+            .filter(|(_, e)| f(e))
+            .map(|(id, _)| id.clone())
+            .collect();
+        groups.sort_unstable();
+        groups
+    }
+}
+
+impl Observer for UserGroups {
+    fn observe(&self, event: &Event) {
+        let mut inner = write(&self.inner);
+        match event {
+            Event::UserGroupCreated(p) => {
+                if let Some(entry) = inner.entry(&p.group.id) {
+                    entry.set_group(p.event_time, p.group.clone());
+                }
+            }
+            Event::UserGroupDeleted(p) => inner.delete(&p.group_id),
+            Event::UserGroupMemberAdded(p) => inner.member_event(&p.group_member, |e, user| {
+                e.member_present(p.event_time, user);
+            }),
+            Event::UserGroupMemberUpdated(p) => inner.member_event(&p.group_member, |e, user| {
+                e.member_present(p.event_time, user);
+            }),
+            Event::UserGroupMemberRemoved(p) => inner.member_event(&p.group_member, |e, user| {
+                apply(&mut e.members, user, p.event_time, None);
+            }),
+            Event::UserGroupAdminAdded(p) => inner.member_event(&p.group_member, |e, user| {
+                apply(&mut e.admins, user, p.event_time, Some(()));
+            }),
+            Event::UserGroupAdminRemoved(p) => inner.member_event(&p.group_member, |e, user| {
+                apply(&mut e.admins, user, p.event_time, None);
+            }),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{testdata, timestamp, uuid};
+
+    use crate::payloads::{
+        UserGroupAdminRemovedPayload, UserGroupCreatedPayload, UserGroupDeletedPayload,
+        UserGroupMemberAddedPayload, UserGroupMemberRemovedPayload,
+    };
+
+    fn group_id() -> Uuid {
+        uuid("f265bde2-04cc-4856-9008-3db1d953a539")
+    }
+
+    fn user_id() -> Uuid {
+        uuid("8e6a088f-9274-42c0-bb20-cee7913d144b")
+    }
+
+    fn other_user_id() -> Uuid {
+        uuid("dfdff0c9-5de0-46ee-9721-2525e8bb3d45")
+    }
+
+    #[test]
+    fn created_and_membership() {
+        let user_groups = UserGroups::new();
+        let created: UserGroupCreatedPayload = testdata("user-group/user_group_created.json");
+        user_groups.observe(&created.clone().into());
+        assert_eq!(user_groups.group(&group_id()), Some(created.group.clone()));
+        assert_eq!(user_groups.admin_groups_of(&user_id()), [group_id()]);
+
+        let mut added: UserGroupMemberAddedPayload =
+            testdata("user-group/user_group_member_added.json");
+        added.group_member.user_id = other_user_id();
+        user_groups.observe(&added.into());
+        assert_eq!(user_groups.groups_of(&other_user_id()), [group_id()]);
+        let group = user_groups.group(&group_id()).unwrap();
+        assert_eq!(
+            group.member_role(&other_user_id()),
+            Some(&UserGroupRole::Unspecified)
+        );
+        assert_eq!(group.members.len(), 2);
+
+        let removed: UserGroupAdminRemovedPayload =
+            testdata("user-group/user_group_admin_removed.json");
+        user_groups.observe(&removed.into());
+        assert!(user_groups.admin_groups_of(&user_id()).is_empty());
+        assert!(user_groups.is_member(&group_id(), &user_id()));
+    }
+
+    #[test]
+    fn out_of_order() {
+        let user_groups = UserGroups::new();
+        let mut removed: UserGroupMemberRemovedPayload =
+            testdata("user-group/user_group_member_removed.json");
+        removed.event_time = timestamp("2023-08-25T04:10:00Z");
+        let mut added: UserGroupMemberAddedPayload =
+            testdata("user-group/user_group_member_added.json");
+        added.event_time = timestamp("2023-08-25T04:05:00Z");
+        user_groups.observe(&removed.into());
+        user_groups.observe(&added.into());
+        assert!(!user_groups.is_member(&group_id(), &user_id()));
+
+        // 作成より後に削除されたメンバーは、作成のイベントが遅れて届いても復活しない
+        let created: UserGroupCreatedPayload = testdata("user-group/user_group_created.json");
+        user_groups.observe(&created.into());
+        assert!(user_groups.is_known(&group_id()));
+        assert!(user_groups.group(&group_id()).unwrap().members.is_empty());
+    }
+
+    #[test]
+    fn unknown_group() {
+        let user_groups = UserGroups::new();
+        let added: UserGroupMemberAddedPayload =
+            testdata("user-group/user_group_member_added.json");
+        user_groups.observe(&added.into());
+        assert!(!user_groups.is_known(&group_id()));
+        assert_eq!(user_groups.group(&group_id()), None);
+        assert_eq!(user_groups.members(&group_id()), [user_id()]);
+        assert!(user_groups.groups().is_empty());
+    }
+
+    #[test]
+    fn deleted_group() {
+        let user_groups = UserGroups::new();
+        let created: UserGroupCreatedPayload = testdata("user-group/user_group_created.json");
+        user_groups.seed([created.group.clone()]);
+        let deleted: UserGroupDeletedPayload = testdata("user-group/user_group_deleted.json");
+        user_groups.observe(&deleted.into());
+        user_groups.observe(&created.into());
+        let added: UserGroupMemberAddedPayload =
+            testdata("user-group/user_group_member_added.json");
+        user_groups.observe(&added.into());
+        assert_eq!(user_groups.group(&group_id()), None);
+        assert!(user_groups.groups_of(&user_id()).is_empty());
+    }
+
+    #[test]
+    fn reseed_removes_missing() {
+        let user_groups = UserGroups::new();
+        let created: UserGroupCreatedPayload = testdata("user-group/user_group_created.json");
+        let mut group = created.group;
+        let mut other = group.members[0].clone();
+        other.user_id = other_user_id();
+        group.members.push(other);
+        group.updated_at = timestamp("2023-08-25T04:10:00Z");
+        user_groups.seed([group.clone()]);
+        assert!(user_groups.is_member(&group_id(), &other_user_id()));
+
+        // メンバーが減ったスナップショットで上書きする
+        group.members.retain(|m| m.user_id != other_user_id());
+        group.admins.clear();
+        group.updated_at = timestamp("2023-08-25T04:20:00Z");
+        user_groups.seed([group.clone()]);
+        assert!(!user_groups.is_member(&group_id(), &other_user_id()));
+        assert!(user_groups.groups_of(&other_user_id()).is_empty());
+        assert!(user_groups.admin_groups_of(&user_id()).is_empty());
+        assert_eq!(user_groups.members(&group_id()), [user_id()]);
+        assert_eq!(user_groups.group(&group_id()), Some(group.clone()));
+
+        // 古いスナップショットでは復活しない
+        let mut stale = group;
+        stale.members.push(UserGroupMember {
+            group_id: group_id(),
+            user_id: other_user_id(),
+            role: UserGroupRole::Unspecified,
+        });
+        stale.updated_at = timestamp("2023-08-25T04:15:00Z");
+        user_groups.seed([stale]);
+        assert!(!user_groups.is_member(&group_id(), &other_user_id()));
+    }
+}
@@ -0,0 +1,960 @@
+//! BOTへのコマンドを解釈するためのモジュール
+//!
+//! `@BOT_name command arg "quoted arg"` のようなメッセージから、
+//! 先頭のBOTへのメンションを取り除き、シェル風の規則で引数に分割し、
+//! 型付きの引数として解釈します。
+//!
+//! `tower` featureが有効な場合、登録したサブコマンドへ振り分ける [`Router`] が使用できます。
+
+use std::collections::VecDeque;
+use std::fmt;
+
+use crate::message::{segments, Segment};
+use crate::payloads::types::{EmbeddedInfo, Uuid};
+
+#[cfg(feature = "tower")]
+mod router;
+
+#[cfg(feature = "tower")]
+pub use router::{Rejection, Router};
+
+/// [`BotCommand`](trait@BotCommand)を実装するderiveマクロです。
+///
+/// - 名前付きフィールドを持つstructでは、各フィールドが定義順に引数となります
+///     - 引数名はフィールド名の`_`を`-`に置き換えたものです。`#[command(rename = "...")]`で変更できます
+///     - `Option<T>`のフィールドは省略可能な引数、`Vec<T>`のフィールドは残り全ての引数となります
+///     - `#[command(user)]`, `#[command(channel)]`, `#[command(group)]`を付けた[`Uuid`]のフィールドは、
+///       それぞれユーザーへのメンション、チャンネルへのリンク、ユーザーグループへのメンションのIDとなります
+/// - enumでは、各variantが`kebab-case`の名前のサブコマンドとなります
+///     - サブコマンド名は`#[command(rename = "...")]`で変更できます
+///     - variantは名前付きフィールドを持つもの、フィールドを持たないもの、
+///       [`BotCommand`](trait@BotCommand)を実装する型を1つ包むもののいずれかです
+/// - doc commentの最初の段落が[`CommandInfo`], [`ArgumentInfo`]の説明となります
+///
+/// ## Example
+/// ```
+/// use traq_bot_http::command::{Arguments, BotCommand};
+/// use traq_bot_http::message::MessageBuilder;
+/// # use traq_bot_http::payloads::types::{User, Uuid};
+///
+/// /// Manage roles
+/// #[derive(BotCommand)]
+/// enum Role {
+///     /// Grant a role to users
+///     Grant {
+///         /// Name of the role
+///         role: String,
+///         #[command(user)]
+///         users: Vec<Uuid>,
+///     },
+///     /// List all roles
+///     List,
+/// }
+///
+/// # let user: User = serde_json::from_value(serde_json::json!({
+/// #     "id": "dfdff0c9-5de0-46ee-9721-2525e8bb3d45",
+/// #     "name": "takashi_trap",
+/// #     "displayName": "寺田 健二",
+/// #     "iconId": "2bc06cda-bdb9-4a68-8000-62f907f36a92",
+/// #     "bot": false
+/// # })).unwrap();
+/// let text = MessageBuilder::new()
+///     .text("grant admin ")
+///     .mention_user(&user)
+///     .build();
+/// let mut args = Arguments::parse(&text).unwrap();
+/// let Role::Grant { role, users } = Role::parse(&mut args).unwrap() else {
+///     unreachable!()
+/// };
+/// assert_eq!(role, "admin");
+/// assert_eq!(users, [user.id]);
+/// assert_eq!(Role::info("role").usage(), "role <command>");
+/// ```
+#[cfg(feature = "derive")]
+pub use traq_bot_http_derive::BotCommand;
+
+/// コマンド引数の字句です。[`tokenize`]で得られます。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    /// クォートやエスケープを取り除いた値。埋め込みの場合は`raw`の値
+    pub value: String,
+    /// 字句全体が埋め込みだった場合、その埋め込み
+    pub embedded: Option<EmbeddedInfo>,
+}
+
+impl Token {
+    fn text(value: String) -> Self {
+        Self {
+            value,
+            embedded: None,
+        }
+    }
+
+    fn embedded(info: EmbeddedInfo) -> Self {
+        Self {
+            value: info.raw.clone(),
+            embedded: Some(info),
+        }
+    }
+}
+
+/// [`tokenize`]で発生するエラーです。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TokenizeError {
+    /// クォートが閉じられていない
+    UnclosedQuote(char),
+    /// 末尾にエスケープ対象の無い`\`がある
+    TrailingBackslash,
+}
+
+impl fmt::Display for TokenizeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnclosedQuote(q) => write!(f, "unclosed quote `{q}`"),
+            Self::TrailingBackslash => f.write_str("trailing backslash"),
+        }
+    }
+}
+
+impl std::error::Error for TokenizeError {}
+
+#[derive(Debug, Default)]
+struct Tokenizer {
+    tokens: Vec<Token>,
+    current: String,
+    in_word: bool,
+    quote: Option<char>,
+    escaped: bool,
+}
+
+impl Tokenizer {
+    fn flush(&mut self) {
+        if std::mem::take(&mut self.in_word) {
+            let value = std::mem::take(&mut self.current);
+            self.tokens.push(Token::text(value));
+        }
+    }
+
+    fn push_embedded(&mut self, info: EmbeddedInfo) {
+        self.escaped = false;
+        if self.quote.is_none() && !self.in_word {
+            self.tokens.push(Token::embedded(info));
+        } else {
+            self.current.push_str(&info.raw);
+            self.in_word = true;
+        }
+    }
+
+    fn push_char(&mut self, c: char) {
+        if std::mem::take(&mut self.escaped) {
+            self.current.push(c);
+            self.in_word = true;
+            return;
+        }
+        match (self.quote, c) {
+            (Some(q), c) if c == q => self.quote = None,
+            (Some('"'), '\\') => self.escaped = true,
+            (Some(_), c) => self.current.push(c),
+            (None, c) if c.is_whitespace() => self.flush(),
+            (None, '"' | '\'') => {
+                self.quote = Some(c);
+                self.in_word = true;
+            }
+            (None, '\\') => {
+                self.escaped = true;
+                self.in_word = true;
+            }
+            (None, c) => {
+                self.current.push(c);
+                self.in_word = true;
+            }
+        }
+    }
+
+    fn finish(mut self) -> Result<Vec<Token>, TokenizeError> {
+        if let Some(q) = self.quote {
+            return Err(TokenizeError::UnclosedQuote(q));
+        }
+        if self.escaped {
+            return Err(TokenizeError::TrailingBackslash);
+        }
+        self.flush();
+        Ok(self.tokens)
+    }
+}
+
+/// メッセージ本文をシェル風の規則で字句に分割します。
+///
+/// - 空白文字で区切られます
+/// - `"..."`, `'...'`で囲まれた部分は空白を含めて1つの字句になります
+/// - `\`は次の1文字をエスケープします (`'...'`の中を除く)
+/// - 字句全体が埋め込みの場合、[`Token::embedded`]にその情報が入ります
+///
+/// ## Example
+/// ```
+/// use traq_bot_http::command::tokenize;
+///
+/// let tokens = tokenize(r#"echo "hello world" it\'s"#).unwrap();
+/// let values: Vec<_> = tokens.iter().map(|t| t.value.as_str()).collect();
+/// assert_eq!(values, ["echo", "hello world", "it's"]);
+/// ```
+///
+/// ## Errors
+/// クォートが閉じられていない場合、末尾に`\`がある場合に[`TokenizeError`]を返します。
+pub fn tokenize(text: &str) -> Result<Vec<Token>, TokenizeError> {
+    let mut tokenizer = Tokenizer::default();
+    for segment in segments(text) {
+        match segment {
+            Segment::Embedded { info, .. } => tokenizer.push_embedded(info),
+            Segment::Text { text, .. } => text.chars().for_each(|c| tokenizer.push_char(c)),
+        }
+    }
+    tokenizer.finish()
+}
+
+/// メッセージ本文が`user_id`のユーザーへのメンションで始まる場合、メンション以降の本文を返します。
+///
+/// ## Example
+/// ```
+/// use traq_bot_http::command::strip_mention;
+///
+/// let bot_id = "dfdff0c9-5de0-46ee-9721-2525e8bb3d45".parse().unwrap();
+/// let text = r#"!{"type":"user","raw":"@BOT_po","id":"dfdff0c9-5de0-46ee-9721-2525e8bb3d45"} ping"#;
+/// assert_eq!(strip_mention(text, &bot_id), Some(" ping"));
+/// assert_eq!(strip_mention("ping", &bot_id), None);
+/// ```
+#[must_use]
+pub fn strip_mention<'a>(text: &'a str, user_id: &Uuid) -> Option<&'a str> {
+    let trimmed = text.trim_start();
+    let offset = text.len() - trimmed.len();
+    match segments(trimmed).next()? {
+        Segment::Embedded { range, info } if info.r#type == "user" && &info.id == user_id => {
+            Some(&text[offset + range.end..])
+        }
+        _ => None,
+    }
+}
+
+/// コマンドの解釈に失敗したことを表すエラーです。
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CommandError {
+    /// 字句への分割に失敗した
+    Tokenize(TokenizeError),
+    /// コマンド名が与えられなかった
+    MissingCommand,
+    /// 未知のコマンド名が与えられた
+    UnknownCommand(String),
+    /// 必須の引数が与えられなかった
+    MissingArgument(&'static str),
+    /// 引数の値を解釈できなかった
+    InvalidArgument {
+        name: &'static str,
+        value: String,
+        expected: &'static str,
+    },
+    /// 余分な引数が与えられた
+    UnexpectedArgument(String),
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Tokenize(e) => write!(f, "failed to parse arguments: {e}"),
+            Self::MissingCommand => f.write_str("no command given"),
+            Self::UnknownCommand(name) => write!(f, "unknown command `{name}`"),
+            Self::MissingArgument(name) => write!(f, "missing argument <{name}>"),
+            Self::InvalidArgument {
+                name,
+                value,
+                expected,
+            } => write!(
+                f,
+                "invalid value `{value}` for <{name}>: expected {expected}"
+            ),
+            Self::UnexpectedArgument(value) => write!(f, "unexpected argument `{value}`"),
+        }
+    }
+}
+
+impl std::error::Error for CommandError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Tokenize(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<TokenizeError> for CommandError {
+    fn from(e: TokenizeError) -> Self {
+        Self::Tokenize(e)
+    }
+}
+
+/// 1つの字句から値を得るための trait です。
+pub trait FromArgument: Sized {
+    /// 使い方の表示に使われる、期待する値の説明
+    const EXPECTED: &'static str;
+
+    /// 字句を値に変換します。変換できない場合は`None`を返します。
+    fn from_argument(token: Token) -> Option<Self>;
+}
+
+impl FromArgument for String {
+    const EXPECTED: &'static str = "string";
+
+    fn from_argument(token: Token) -> Option<Self> {
+        Some(token.value)
+    }
+}
+
+impl FromArgument for bool {
+    const EXPECTED: &'static str = "true or false";
+
+    fn from_argument(token: Token) -> Option<Self> {
+        token.value.parse().ok()
+    }
+}
+
+macro_rules! impl_from_argument_for_numbers {
+    ($expected:literal: $( $t:ty ),*) => {
+        $(
+            impl FromArgument for $t {
+                const EXPECTED: &'static str = $expected;
+
+                fn from_argument(token: Token) -> Option<Self> {
+                    token.value.parse().ok()
+                }
+            }
+        )*
+    };
+}
+
+impl_from_argument_for_numbers! {"integer": i8, i16, i32, i64, i128, isize}
+impl_from_argument_for_numbers! {"non-negative integer": u8, u16, u32, u64, u128, usize}
+impl_from_argument_for_numbers! {"number": f32, f64}
+
+macro_rules! mention_types {
+    ($(
+        $( #[$m:meta] )*
+        $name:ident = $kind:literal, $expected:literal;
+    )*) => {
+        $(
+            $( #[$m] )*
+            #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+            pub struct $name {
+                /// 埋め込みのID
+                pub id: Uuid,
+                /// 埋め込みの`raw`の値
+                pub raw: String,
+            }
+
+            impl FromArgument for $name {
+                const EXPECTED: &'static str = $expected;
+
+                fn from_argument(token: Token) -> Option<Self> {
+                    let EmbeddedInfo { raw, r#type, id } = token.embedded?;
+                    (r#type == $kind).then_some(Self { id, raw })
+                }
+            }
+        )*
+    };
+}
+
+mention_types! {
+    /// ユーザーへのメンションとして与えられた引数
+    UserMention = "user", "user mention";
+    /// チャンネルへのリンクとして与えられた引数
+    ChannelMention = "channel", "channel link";
+    /// ユーザーグループへのメンションとして与えられた引数
+    GroupMention = "group", "group mention";
+}
+
+/// コマンドの引数列です。
+///
+/// ## Example
+/// ```
+/// use traq_bot_http::command::Arguments;
+///
+/// let mut args = Arguments::parse(r#"3 "po po""#).unwrap();
+/// let count: u32 = args.required("count").unwrap();
+/// let text: Option<String> = args.optional("text").unwrap();
+/// args.finish().unwrap();
+/// assert_eq!(count, 3);
+/// assert_eq!(text.as_deref(), Some("po po"));
+/// ```
+#[must_use]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Arguments {
+    tokens: VecDeque<Token>,
+}
+
+impl Arguments {
+    /// 字句の列から`Arguments`を作成します。
+    pub fn new(tokens: Vec<Token>) -> Self {
+        Self {
+            tokens: tokens.into(),
+        }
+    }
+
+    /// メッセージ本文を[`tokenize`]して`Arguments`を作成します。
+    ///
+    /// ## Errors
+    /// [`tokenize`]に失敗した場合、[`CommandError::Tokenize`]を返します。
+    pub fn parse(text: &str) -> Result<Self, CommandError> {
+        Ok(Self::new(tokenize(text)?))
+    }
+
+    /// 次の字句を参照します。
+    #[must_use]
+    pub fn peek(&self) -> Option<&Token> {
+        self.tokens.front()
+    }
+
+    /// 次の字句を取り出します。
+    pub fn next_token(&mut self) -> Option<Token> {
+        self.tokens.pop_front()
+    }
+
+    /// 残っている字句の数を返します。
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.tokens.len()
+    }
+
+    /// 字句が残っていない場合に`true`を返します。
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.tokens.is_empty()
+    }
+
+    fn convert<T: FromArgument>(name: &'static str, token: Token) -> Result<T, CommandError> {
+        let value = token.value.clone();
+        T::from_argument(token).ok_or(CommandError::InvalidArgument {
+            name,
+            value,
+            expected: T::EXPECTED,
+        })
+    }
+
+    /// 必須の引数`name`を取り出します。
+    ///
+    /// ## Errors
+    /// 字句が残っていない場合は[`CommandError::MissingArgument`]、
+    /// 値を解釈できない場合は[`CommandError::InvalidArgument`]を返します。
+    pub fn required<T: FromArgument>(&mut self, name: &'static str) -> Result<T, CommandError> {
+        let token = self
+            .next_token()
+            .ok_or(CommandError::MissingArgument(name))?;
+        Self::convert(name, token)
+    }
+
+    /// 省略可能な引数`name`を取り出します。
+    ///
+    /// ## Errors
+    /// 値を解釈できない場合は[`CommandError::InvalidArgument`]を返します。
+    pub fn optional<T: FromArgument>(
+        &mut self,
+        name: &'static str,
+    ) -> Result<Option<T>, CommandError> {
+        self.next_token()
+            .map(|token| Self::convert(name, token))
+            .transpose()
+    }
+
+    /// 残りの字句を全て引数`name`として取り出します。
+    ///
+    /// ## Errors
+    /// 値を解釈できない字句がある場合は[`CommandError::InvalidArgument`]を返します。
+    pub fn rest<T: FromArgument>(&mut self, name: &'static str) -> Result<Vec<T>, CommandError> {
+        std::mem::take(&mut self.tokens)
+            .into_iter()
+            .map(|token| Self::convert(name, token))
+            .collect()
+    }
+
+    /// 字句が全て消費されたことを確認します。
+    ///
+    /// ## Errors
+    /// 字句が残っている場合は[`CommandError::UnexpectedArgument`]を返します。
+    pub fn finish(mut self) -> Result<(), CommandError> {
+        match self.next_token() {
+            Some(token) => Err(CommandError::UnexpectedArgument(token.value)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// 引数の個数の種類です。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Arity {
+    /// 必須
+    Required,
+    /// 省略可能
+    Optional,
+    /// 0個以上
+    Rest,
+}
+
+/// 使い方の表示に使われる引数の情報です。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArgumentInfo {
+    pub name: String,
+    pub expected: String,
+    pub description: String,
+    pub arity: Arity,
+}
+
+impl ArgumentInfo {
+    /// `T`型の引数の情報を作成します。
+    #[must_use]
+    pub fn new<T: FromArgument>(name: &str, arity: Arity) -> Self {
+        Self {
+            name: name.to_string(),
+            expected: T::EXPECTED.to_string(),
+            description: String::new(),
+            arity,
+        }
+    }
+
+    /// 説明を設定します。
+    #[must_use]
+    pub fn description(mut self, description: &str) -> Self {
+        self.description = description.to_string();
+        self
+    }
+
+    fn usage(&self) -> String {
+        match self.arity {
+            Arity::Required => format!("<{}>", self.name),
+            Arity::Optional => format!("[{}]", self.name),
+            Arity::Rest => format!("[{}...]", self.name),
+        }
+    }
+}
+
+/// 使い方の表示に使われるコマンドの情報です。
+///
+/// ## Example
+/// ```
+/// use traq_bot_http::command::{ArgumentInfo, Arity, CommandInfo};
+///
+/// let info = CommandInfo::new("echo")
+///     .description("Repeat the text")
+///     .argument(ArgumentInfo::new::<String>("text", Arity::Rest));
+/// assert_eq!(info.usage(), "echo [text...]");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandInfo {
+    pub name: String,
+    pub description: String,
+    pub arguments: Vec<ArgumentInfo>,
+    pub subcommands: Vec<CommandInfo>,
+}
+
+impl CommandInfo {
+    /// 名前`name`のコマンドの情報を作成します。
+    #[must_use]
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            description: String::new(),
+            arguments: Vec::new(),
+            subcommands: Vec::new(),
+        }
+    }
+
+    /// 説明を設定します。
+    #[must_use]
+    pub fn description(mut self, description: &str) -> Self {
+        self.description = description.to_string();
+        self
+    }
+
+    /// 引数を追加します。
+    #[must_use]
+    pub fn argument(mut self, argument: ArgumentInfo) -> Self {
+        self.arguments.push(argument);
+        self
+    }
+
+    /// サブコマンドを追加します。
+    #[must_use]
+    pub fn subcommand(mut self, subcommand: CommandInfo) -> Self {
+        self.subcommands.push(subcommand);
+        self
+    }
+
+    /// 1行の使い方を返します。
+    #[must_use]
+    pub fn usage(&self) -> String {
+        let mut usage = self.name.clone();
+        if !self.subcommands.is_empty() {
+            usage.push_str(" <command>");
+        }
+        for argument in &self.arguments {
+            usage.push(' ');
+            usage.push_str(&argument.usage());
+        }
+        usage
+    }
+
+    /// 複数行のヘルプを返します。
+    #[must_use]
+    pub fn help(&self) -> String {
+        use fmt::Write;
+
+        let mut help = format!("usage: {}\n", self.usage());
+        if !self.description.is_empty() {
+            let _ = write!(help, "\n{}\n", self.description);
+        }
+        if !self.arguments.is_empty() {
+            help.push_str("\narguments:\n");
+            for argument in &self.arguments {
+                let _ = write!(help, "  {} ({})", argument.usage(), argument.expected);
+                if !argument.description.is_empty() {
+                    let _ = write!(help, ": {}", argument.description);
+                }
+                help.push('\n');
+            }
+        }
+        if !self.subcommands.is_empty() {
+            help.push_str("\ncommands:\n");
+            for subcommand in &self.subcommands {
+                let _ = write!(help, "  {}", subcommand.usage());
+                if !subcommand.description.is_empty() {
+                    let _ = write!(help, ": {}", subcommand.description);
+                }
+                help.push('\n');
+            }
+        }
+        help
+    }
+}
+
+/// 引数列から解釈されるコマンドを表す trait です。
+///
+/// `derive` featureを有効にすると、`#[derive(BotCommand)]`が使用できます。
+///
+/// ## Example
+/// ```
+/// use traq_bot_http::command::{
+///     Arguments, ArgumentInfo, Arity, BotCommand, CommandError, CommandInfo,
+/// };
+///
+/// struct Repeat {
+///     count: u32,
+///     text: String,
+/// }
+///
+/// impl BotCommand for Repeat {
+///     fn parse(args: &mut Arguments) -> Result<Self, CommandError> {
+///         let count = args.required("count")?;
+///         let text = args.required("text")?;
+///         Ok(Self { count, text })
+///     }
+///
+///     fn info(name: &str) -> CommandInfo {
+///         CommandInfo::new(name)
+///             .argument(ArgumentInfo::new::<u32>("count", Arity::Required))
+///             .argument(ArgumentInfo::new::<String>("text", Arity::Required))
+///     }
+/// }
+///
+/// let mut args = Arguments::parse("3 po").unwrap();
+/// let repeat = Repeat::parse(&mut args).unwrap();
+/// assert_eq!(repeat.count, 3);
+/// assert_eq!(Repeat::info("repeat").usage(), "repeat <count> <text>");
+/// ```
+pub trait BotCommand: Sized {
+    /// 引数列からコマンドを解釈します。使用しなかった字句は`args`に残します。
+    ///
+    /// ## Errors
+    /// 引数を解釈できなかった場合に[`CommandError`]を返します。
+    fn parse(args: &mut Arguments) -> Result<Self, CommandError>;
+
+    /// 名前`name`で登録された時のコマンドの情報を返します。
+    #[must_use]
+    fn info(name: &str) -> CommandInfo {
+        CommandInfo::new(name)
+    }
+}
+
+/// 引数を取らないコマンド
+impl BotCommand for () {
+    fn parse(_args: &mut Arguments) -> Result<Self, CommandError> {
+        Ok(())
+    }
+}
+
+/// 1つの引数を取るコマンド
+impl<T: FromArgument> BotCommand for T {
+    fn parse(args: &mut Arguments) -> Result<Self, CommandError> {
+        args.required("arg")
+    }
+
+    fn info(name: &str) -> CommandInfo {
+        CommandInfo::new(name).argument(ArgumentInfo::new::<T>("arg", Arity::Required))
+    }
+}
+
+/// 残りの引数列をそのまま受け取るコマンド
+impl BotCommand for Arguments {
+    fn parse(args: &mut Arguments) -> Result<Self, CommandError> {
+        Ok(std::mem::take(args))
+    }
+
+    fn info(name: &str) -> CommandInfo {
+        CommandInfo::new(name).argument(ArgumentInfo::new::<String>("args", Arity::Rest))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message::MessageBuilder;
+    use crate::test_utils::{channel_a_po, embedded_takashi_trap, takashi_trap};
+
+    fn values(tokens: &[Token]) -> Vec<&str> {
+        tokens.iter().map(|t| t.value.as_str()).collect()
+    }
+
+    #[test]
+    fn tokenize_quotes() {
+        let tokens = tokenize(r#"  a "b c" 'd "e"' f\ g "h\"i" '' "#).unwrap();
+        assert_eq!(
+            values(&tokens),
+            ["a", "b c", r#"d "e""#, "f g", r#"h"i"#, ""]
+        );
+        assert!(tokens.iter().all(|t| t.embedded.is_none()));
+    }
+
+    #[test]
+    fn tokenize_error() {
+        assert_eq!(tokenize(r#"a "b"#), Err(TokenizeError::UnclosedQuote('"')));
+        assert_eq!(tokenize("a 'b"), Err(TokenizeError::UnclosedQuote('\'')));
+        assert_eq!(tokenize(r"a \"), Err(TokenizeError::TrailingBackslash));
+    }
+
+    #[test]
+    fn tokenize_embedded() {
+        let text = MessageBuilder::new()
+            .text("kick ")
+            .mention_user(&takashi_trap())
+            .text(" \"from ")
+            .channel(&channel_a_po())
+            .text("\"")
+            .build();
+        let tokens = tokenize(&text).unwrap();
+        assert_eq!(values(&tokens), ["kick", "@takashi_trap", "from #a/po"]);
+        assert_eq!(tokens[1].embedded, Some(embedded_takashi_trap()));
+        assert_eq!(tokens[2].embedded, None);
+    }
+
+    #[test]
+    fn strip_bot_mention() {
+        let bot = takashi_trap();
+        let text = MessageBuilder::new()
+            .text(" ")
+            .mention_user(&bot)
+            .text(" ping")
+            .build();
+        assert_eq!(strip_mention(&text, &bot.id), Some(" ping"));
+        let text = MessageBuilder::new()
+            .text("ping ")
+            .mention_user(&bot)
+            .build();
+        assert_eq!(strip_mention(&text, &bot.id), None);
+        let text = MessageBuilder::new().channel(&channel_a_po()).build();
+        assert_eq!(strip_mention(&text, &bot.id), None);
+    }
+
+    #[test]
+    fn arguments() {
+        let text = MessageBuilder::new()
+            .text("3 ")
+            .mention_user(&takashi_trap())
+            .text(" x y")
+            .build();
+        let mut args = Arguments::parse(&text).unwrap();
+        assert_eq!(args.len(), 4);
+        assert_eq!(args.required::<u8>("count"), Ok(3));
+        let user: UserMention = args.required("user").unwrap();
+        assert_eq!(user.id, takashi_trap().id);
+        assert_eq!(args.rest::<String>("rest").unwrap(), ["x", "y"]);
+        assert!(args.is_empty());
+        assert_eq!(args.optional::<String>("opt"), Ok(None));
+        assert_eq!(
+            args.required::<String>("text"),
+            Err(CommandError::MissingArgument("text"))
+        );
+        args.finish().unwrap();
+    }
+
+    #[test]
+    fn arguments_error() {
+        let mut args = Arguments::parse("x y").unwrap();
+        assert_eq!(
+            args.required::<i32>("count"),
+            Err(CommandError::InvalidArgument {
+                name: "count",
+                value: "x".to_string(),
+                expected: "integer",
+            })
+        );
+        assert_eq!(
+            args.required::<ChannelMention>("channel"),
+            Err(CommandError::InvalidArgument {
+                name: "channel",
+                value: "y".to_string(),
+                expected: "channel link",
+            })
+        );
+        let args = Arguments::parse("z").unwrap();
+        assert_eq!(
+            args.finish(),
+            Err(CommandError::UnexpectedArgument("z".to_string()))
+        );
+    }
+
+    #[test]
+    fn command_info_help() {
+        let info = CommandInfo::new("bot")
+            .description("A bot")
+            .argument(ArgumentInfo::new::<bool>("verbose", Arity::Optional).description("Verbose"))
+            .subcommand(CommandInfo::new("ping").description("Reply pong"));
+        assert_eq!(info.usage(), "bot <command> [verbose]");
+        assert_eq!(
+            info.help(),
+            concat!(
+                "usage: bot <command> [verbose]\n",
+                "\n",
+                "A bot\n",
+                "\n",
+                "arguments:\n",
+                "  [verbose] (true or false): Verbose\n",
+                "\n",
+                "commands:\n",
+                "  ping: Reply pong\n",
+            )
+        );
+    }
+
+    #[cfg(feature = "derive")]
+    mod derive {
+        use super::*;
+        use crate::test_utils::channel_a_po;
+
+        /// Add two integers
+        #[derive(Debug, PartialEq, BotCommand)]
+        #[command(crate = "crate")]
+        struct Add {
+            /// Left-hand side
+            lhs: i64,
+            #[command(rename = "right")]
+            rhs: Option<i64>,
+        }
+
+        /// Move users
+        #[derive(Debug, PartialEq, BotCommand)]
+        #[command(crate = "crate")]
+        enum Move {
+            /// Move users to a channel
+            ToChannel {
+                #[command(channel)]
+                channel: Uuid,
+                #[command(user)]
+                users: Vec<Uuid>,
+            },
+            #[command(rename = "add")]
+            Calc(Add),
+            Stay,
+        }
+
+        fn parse<C: BotCommand>(text: &str) -> Result<C, CommandError> {
+            let mut args = Arguments::parse(text)?;
+            let command = C::parse(&mut args)?;
+            args.finish()?;
+            Ok(command)
+        }
+
+        #[test]
+        fn derive_struct() {
+            assert_eq!(
+                parse("1 2"),
+                Ok(Add {
+                    lhs: 1,
+                    rhs: Some(2)
+                })
+            );
+            assert_eq!(parse("1"), Ok(Add { lhs: 1, rhs: None }));
+            assert_eq!(
+                parse::<Add>("x"),
+                Err(CommandError::InvalidArgument {
+                    name: "lhs",
+                    value: "x".to_string(),
+                    expected: "integer",
+                })
+            );
+            assert_eq!(
+                Add::info("add").help(),
+                concat!(
+                    "usage: add <lhs> [right]\n",
+                    "\n",
+                    "Add two integers\n",
+                    "\n",
+                    "arguments:\n",
+                    "  <lhs> (integer): Left-hand side\n",
+                    "  [right] (integer)\n",
+                )
+            );
+        }
+
+        #[test]
+        fn derive_enum() {
+            let (user, channel) = (takashi_trap(), channel_a_po());
+            let text = MessageBuilder::new()
+                .text("to-channel ")
+                .channel(&channel)
+                .text(" ")
+                .mention_user(&user)
+                .build();
+            assert_eq!(
+                parse(&text),
+                Ok(Move::ToChannel {
+                    channel: channel.id,
+                    users: vec![user.id],
+                })
+            );
+            assert_eq!(parse("add 1"), Ok(Move::Calc(Add { lhs: 1, rhs: None })));
+            assert_eq!(parse("stay"), Ok(Move::Stay));
+            assert_eq!(
+                parse::<Move>("to-channel #a/po"),
+                Err(CommandError::InvalidArgument {
+                    name: "channel",
+                    value: "#a/po".to_string(),
+                    expected: "channel link",
+                })
+            );
+            assert_eq!(
+                parse::<Move>("jump"),
+                Err(CommandError::UnknownCommand("jump".to_string()))
+            );
+            assert_eq!(parse::<Move>(""), Err(CommandError::MissingCommand));
+            assert_eq!(
+                Move::info("move").help(),
+                concat!(
+                    "usage: move <command>\n",
+                    "\n",
+                    "Move users\n",
+                    "\n",
+                    "commands:\n",
+                    "  to-channel <channel> [users...]: Move users to a channel\n",
+                    "  add <lhs> [right]: Add two integers\n",
+                    "  stay\n",
+                )
+            );
+        }
+    }
+}
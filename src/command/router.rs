@@ -0,0 +1,423 @@
+// #![cfg(feature = "tower")]
+
+use std::fmt;
+use std::future::{poll_fn, Future};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use tower_service::Service;
+
+use super::{strip_mention, Arguments, BotCommand, CommandError, CommandInfo};
+use crate::error::{Error, Result};
+use crate::handler::Sink;
+use crate::payloads::types::{Message, Uuid};
+use crate::payloads::{DirectMessageCreatedPayload, MessageCreatedPayload};
+
+type BoxFuture = Pin<Box<dyn Future<Output = Result<()>> + Send + 'static>>;
+
+type RouteCall =
+    dyn Fn(&mut Arguments, Message) -> Result<BoxFuture, CommandError> + Send + Sync + 'static;
+
+fn oneshot<S, Req>(mut service: S, request: Req) -> BoxFuture
+where
+    S: Service<Req, Response = ()> + Send + 'static,
+    S::Future: Send + 'static,
+    S::Error: Into<Box<dyn std::error::Error + Send + Sync + 'static>>,
+    Req: Send + 'static,
+{
+    Box::pin(async move {
+        poll_fn(|cx| service.poll_ready(cx))
+            .await
+            .map_err(Error::handler)?;
+        service.call(request).await.map_err(Error::handler)
+    })
+}
+
+#[derive(Clone)]
+struct Route {
+    info: CommandInfo,
+    call: Arc<RouteCall>,
+}
+
+impl fmt::Debug for Route {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Route")
+            .field("info", &self.info)
+            .finish_non_exhaustive()
+    }
+}
+
+/// [`Router`]がコマンドを実行できなかった理由です。
+///
+/// [`Display`]の出力はそのまま返信として使用できる形式になっています。
+///
+/// [`Display`]: std::fmt::Display
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Rejection {
+    /// `help`コマンドが与えられた。ヘルプの本文を含む
+    Help(String),
+    /// コマンドの解釈に失敗した。該当するコマンドのヘルプを含む
+    Usage { error: CommandError, help: String },
+}
+
+impl fmt::Display for Rejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Help(help) => f.write_str(help),
+            Self::Usage { error, help } => write!(f, "error: {error}\n\n{help}"),
+        }
+    }
+}
+
+/// メッセージをサブコマンドへ振り分ける[`Service`]です。
+///
+/// <code>[Service]<[MessageCreatedPayload]></code>と
+/// <code>[Service]<[DirectMessageCreatedPayload]></code>を実装するため、
+/// [`Handler::on_message_created`], [`Handler::on_direct_message_created`]に登録できます。
+/// 登録時にはリクエストの型を明示する必要があります。
+///
+/// - `MESSAGE_CREATED`では、BOTへのメンションで始まるメッセージのみをコマンドとして扱います
+/// - `DIRECT_MESSAGE_CREATED`では、BOTへのメンションは省略できます
+/// - 最初の字句をコマンド名として、[`Router::command`]で登録されたサービスへ振り分けます
+/// - `help`, `help <command>`に対しては[`Rejection::Help`]を、
+///   解釈に失敗した場合は[`Rejection::Usage`]を[`Router::on_reject`]で登録されたサービスへ渡します
+///
+/// ## Example
+/// ```
+/// use std::convert::Infallible;
+///
+/// use tower::service_fn;
+/// use traq_bot_http::command::{Rejection, Router};
+/// use traq_bot_http::payloads::types::Message;
+/// use traq_bot_http::payloads::{DirectMessageCreatedPayload, MessageCreatedPayload};
+/// use traq_bot_http::RequestParser;
+///
+/// async fn echo((text, message): (String, Message)) -> Result<(), Infallible> {
+///     println!("{}: {text}", message.user.name);
+///     Ok(())
+/// }
+///
+/// async fn reject((rejection, message): (Rejection, Message)) -> Result<(), Infallible> {
+///     println!("reply to {}: {rejection}", message.id);
+///     Ok(())
+/// }
+///
+/// let bot_user_id = "dfdff0c9-5de0-46ee-9721-2525e8bb3d45".parse().unwrap();
+/// let router = Router::new(bot_user_id)
+///     .command("echo", service_fn(echo))
+///     .on_reject(service_fn(reject));
+/// let handler = RequestParser::new("verification_token")
+///     .into_handler()
+///     .on_message_created::<_, MessageCreatedPayload>(router.clone())
+///     .on_direct_message_created::<_, DirectMessageCreatedPayload>(router);
+/// # let _ = handler;
+/// ```
+///
+/// [`Service`]: tower::Service
+/// [Service]: tower::Service
+/// [MessageCreatedPayload]: crate::payloads::MessageCreatedPayload
+/// [DirectMessageCreatedPayload]: crate::payloads::DirectMessageCreatedPayload
+/// [`Handler::on_message_created`]: crate::Handler::on_message_created
+/// [`Handler::on_direct_message_created`]: crate::Handler::on_direct_message_created
+#[must_use]
+#[derive(Debug, Clone)]
+pub struct Router<Reject = Sink> {
+    bot_id: Uuid,
+    routes: Vec<Route>,
+    reject: Reject,
+}
+
+impl Router {
+    /// 新しい`Router`を作成します。
+    ///
+    /// ## Arguments
+    /// * `bot_id` - BOTのユーザーID
+    pub fn new(bot_id: Uuid) -> Self {
+        Self {
+            bot_id,
+            routes: Vec::new(),
+            reject: Sink::new(),
+        }
+    }
+}
+
+impl<Reject> Router<Reject> {
+    /// コマンド`name`を処理するサービスを登録します。
+    ///
+    /// 引数は`C`として解釈され、`(C, Message)`がサービスに渡されます。
+    /// `C`が使用しなかった引数が残っている場合は[`CommandError::UnexpectedArgument`]となります。
+    pub fn command<C, S>(mut self, name: &str, service: S) -> Self
+    where
+        C: BotCommand + Send + 'static,
+        S: Service<(C, Message), Response = ()> + Clone + Send + Sync + 'static,
+        S::Future: Send + 'static,
+        S::Error: Into<Box<dyn std::error::Error + Send + Sync + 'static>>,
+    {
+        let call = move |args: &mut Arguments, message: Message| {
+            let command = C::parse(args)?;
+            std::mem::take(args).finish()?;
+            Ok(oneshot(service.clone(), (command, message)))
+        };
+        self.routes.push(Route {
+            info: C::info(name),
+            call: Arc::new(call),
+        });
+        self
+    }
+
+    /// コマンドを実行できなかった場合に`(Rejection, Message)`を受け取るサービスを登録します。
+    ///
+    /// 登録しない場合、[`Rejection`]は無視されます。
+    pub fn on_reject<R>(self, service: R) -> Router<R> {
+        let Self { bot_id, routes, .. } = self;
+        Router {
+            bot_id,
+            routes,
+            reject: service,
+        }
+    }
+
+    /// 登録されたコマンドの一覧を返します。
+    #[must_use]
+    pub fn help(&self) -> String {
+        use fmt::Write;
+
+        self.routes
+            .iter()
+            .fold(String::from("commands:\n"), |mut help, route| {
+                let _ = write!(help, "  {}", route.info.usage());
+                if !route.info.description.is_empty() {
+                    let _ = write!(help, ": {}", route.info.description);
+                }
+                help.push('\n');
+                help
+            })
+    }
+
+    fn find(&self, name: &str) -> Option<&Route> {
+        self.routes.iter().find(|r| r.info.name == name)
+    }
+
+    /// 引数列をコマンド名と引数に分け、該当するコマンドを探します。
+    fn prepare(&self, text: &str) -> Result<(&Route, Arguments), Rejection> {
+        let usage = |error| Rejection::Usage {
+            error,
+            help: self.help(),
+        };
+        let mut args = Arguments::parse(text).map_err(usage)?;
+        let name = args
+            .next_token()
+            .ok_or(CommandError::MissingCommand)
+            .map_err(usage)?
+            .value;
+        if let Some(route) = self.find(&name) {
+            return Ok((route, args));
+        }
+        if name != "help" {
+            return Err(usage(CommandError::UnknownCommand(name)));
+        }
+        let help = match args.next_token() {
+            None => self.help(),
+            Some(token) => self
+                .find(&token.value)
+                .ok_or(CommandError::UnknownCommand(token.value))
+                .map_err(usage)?
+                .info
+                .help(),
+        };
+        Err(Rejection::Help(help))
+    }
+
+    fn handle(&self, text: Option<&str>, message: Message) -> BoxFuture
+    where
+        Reject: Service<(Rejection, Message), Response = ()> + Clone + Send + 'static,
+        Reject::Future: Send + 'static,
+        Reject::Error: Into<Box<dyn std::error::Error + Send + Sync + 'static>>,
+    {
+        let Some(text) = text else {
+            return Box::pin(std::future::ready(Ok(())));
+        };
+        let res = self.prepare(text).and_then(|(route, mut args)| {
+            (route.call)(&mut args, message.clone()).map_err(|error| Rejection::Usage {
+                error,
+                help: route.info.help(),
+            })
+        });
+        match res {
+            Ok(call) => call,
+            Err(rejection) => oneshot(self.reject.clone(), (rejection, message)),
+        }
+    }
+}
+
+impl<Reject> Service<MessageCreatedPayload> for Router<Reject>
+where
+    Reject: Service<(Rejection, Message), Response = ()> + Clone + Send + 'static,
+    Reject::Future: Send + 'static,
+    Reject::Error: Into<Box<dyn std::error::Error + Send + Sync + 'static>>,
+{
+    type Response = ();
+    type Error = Error;
+    type Future = BoxFuture;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: MessageCreatedPayload) -> Self::Future {
+        let message = req.message;
+        let text = strip_mention(&message.text, &self.bot_id).map(str::to_string);
+        self.handle(text.as_deref(), message)
+    }
+}
+
+impl<Reject> Service<DirectMessageCreatedPayload> for Router<Reject>
+where
+    Reject: Service<(Rejection, Message), Response = ()> + Clone + Send + 'static,
+    Reject::Future: Send + 'static,
+    Reject::Error: Into<Box<dyn std::error::Error + Send + Sync + 'static>>,
+{
+    type Response = ();
+    type Error = Error;
+    type Future = BoxFuture;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: DirectMessageCreatedPayload) -> Self::Future {
+        let message = req.message;
+        let text = strip_mention(&message.text, &self.bot_id)
+            .unwrap_or(&message.text)
+            .to_string();
+        self.handle(Some(&text), message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+    use std::fs::read_to_string;
+    use std::sync::Mutex;
+
+    use futures::executor::block_on;
+    use tower::service_fn;
+    use tower::util::BoxCloneService;
+
+    use super::*;
+    use crate::command::{ArgumentInfo, Arity, UserMention};
+    use crate::message::MessageBuilder;
+    use crate::test_utils::takashi_trap;
+
+    struct Add {
+        lhs: i64,
+        rhs: i64,
+    }
+
+    impl BotCommand for Add {
+        fn parse(args: &mut Arguments) -> Result<Self, CommandError> {
+            let lhs = args.required("lhs")?;
+            let rhs = args.required("rhs")?;
+            Ok(Self { lhs, rhs })
+        }
+
+        fn info(name: &str) -> CommandInfo {
+            CommandInfo::new(name)
+                .description("Add two integers")
+                .argument(ArgumentInfo::new::<i64>("lhs", Arity::Required))
+                .argument(ArgumentInfo::new::<i64>("rhs", Arity::Required))
+        }
+    }
+
+    type Log = Arc<Mutex<Vec<String>>>;
+
+    fn router(log: &Log) -> Router<BoxCloneService<(Rejection, Message), (), Infallible>> {
+        let add_log = log.clone();
+        let greet_log = log.clone();
+        let reject_log = log.clone();
+        Router::new(takashi_trap().id)
+            .command(
+                "add",
+                service_fn(move |(add, _): (Add, Message)| {
+                    add_log
+                        .lock()
+                        .unwrap()
+                        .push((add.lhs + add.rhs).to_string());
+                    async { Ok::<_, Infallible>(()) }
+                }),
+            )
+            .command(
+                "greet",
+                service_fn(move |(user, _): (UserMention, Message)| {
+                    greet_log
+                        .lock()
+                        .unwrap()
+                        .push(format!("hello {}", user.raw));
+                    async { Ok::<_, Infallible>(()) }
+                }),
+            )
+            .on_reject(BoxCloneService::new(service_fn(
+                move |(rejection, _): (Rejection, Message)| {
+                    reject_log.lock().unwrap().push(rejection.to_string());
+                    async { Ok::<_, Infallible>(()) }
+                },
+            )))
+    }
+
+    fn payload(text: &str) -> MessageCreatedPayload {
+        let data = read_to_string("testdata/message/message_created.json").unwrap();
+        let mut payload: MessageCreatedPayload = data.parse().unwrap();
+        payload.message.text = text.to_string();
+        payload
+    }
+
+    fn mention(text: &str) -> String {
+        MessageBuilder::new()
+            .mention_user(&takashi_trap())
+            .text(text)
+            .build()
+    }
+
+    #[test]
+    fn route_commands() {
+        let log = Log::default();
+        let mut router = router(&log);
+        block_on(router.call(payload(&mention(" add 1 2")))).unwrap();
+        let greet = MessageBuilder::new()
+            .mention_user(&takashi_trap())
+            .text(" greet ")
+            .mention_user(&takashi_trap())
+            .build();
+        block_on(router.call(payload(&greet))).unwrap();
+        // メンションで始まらないメッセージは無視される
+        block_on(router.call(payload("add 1 2"))).unwrap();
+        // DMではメンションを省略できる
+        let dm: DirectMessageCreatedPayload = payload("add 3 4").into();
+        block_on(router.call(dm)).unwrap();
+        assert_eq!(*log.lock().unwrap(), ["3", "hello @takashi_trap", "7"]);
+    }
+
+    #[test]
+    fn reject_commands() {
+        let log = Log::default();
+        let mut router = router(&log);
+        let help = "commands:\n  add <lhs> <rhs>: Add two integers\n  greet <arg>\n";
+        block_on(router.call(payload(&mention(" help")))).unwrap();
+        block_on(router.call(payload(&mention(" unknown")))).unwrap();
+        block_on(router.call(payload(&mention(" add 1 x")))).unwrap();
+        block_on(router.call(payload(&mention(" add 1 2 3")))).unwrap();
+        block_on(router.call(payload(&mention(" help add")))).unwrap();
+        let add_help = Add::info("add").help();
+        assert_eq!(
+            *log.lock().unwrap(),
+            [
+                help.to_string(),
+                format!("error: unknown command `unknown`\n\n{help}"),
+                format!("error: invalid value `x` for <rhs>: expected integer\n\n{add_help}"),
+                format!("error: unexpected argument `3`\n\n{add_help}"),
+                add_help.clone(),
+            ]
+        );
+    }
+}
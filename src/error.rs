@@ -59,6 +59,8 @@ pub enum ErrorKind {
     ParseBodyFailed,
     /// イベントハンドラ実行中のエラー
     Handler,
+    /// シャットダウン中のためイベントを受け付けなかった
+    ShuttingDown,
 }
 
 /// type alias
@@ -104,6 +106,18 @@ impl Error {
     error_with_source! {pub(crate) ParseBodyFailed}
     // cfg(not(feature = "tower")) でdead_codeになる
     error_with_source! {#[allow(dead_code)] pub(crate) Handler}
+
+    /// 既に`Error`であればそのまま返し、そうでなければ[`ErrorKind::Handler`]として包みます。
+    #[cfg(feature = "tower")]
+    pub(crate) fn from_handler<E>(source: E) -> Self
+    where
+        E: Into<Box<dyn std::error::Error + Send + Sync + 'static>>,
+    {
+        match source.into().downcast::<Self>() {
+            Ok(error) => *error,
+            Err(source) => Self::new(ErrorKind::Handler, source),
+        }
+    }
 }
 
 impl From<ErrorKind> for Error {
@@ -140,6 +154,7 @@ impl ErrorKind {
             Self::ReadBodyFailed => "Failed to read request body",
             Self::ParseBodyFailed => "Failed to parse request body",
             Self::Handler => "Event handler raised an error",
+            Self::ShuttingDown => "Event handler is shutting down",
         }
     }
 }
@@ -8,9 +8,10 @@ use crate::payloads::{
     DirectMessageCreatedPayload, DirectMessageDeletedPayload, DirectMessageUpdatedPayload,
     JoinedPayload, LeftPayload, MessageCreatedPayload, MessageDeletedPayload,
     MessageUpdatedPayload, PingPayload, StampCreatedPayload, TagAddedPayload, TagRemovedPayload,
-    UserCreatedPayload, UserGroupAdminAddedPayload, UserGroupAdminRemovedPayload,
-    UserGroupCreatedPayload, UserGroupDeletedPayload, UserGroupMemberAddedPayload,
-    UserGroupMemberRemovedPayload, UserGroupMemberUpdatedPayload, UserGroupUpdatedPayload,
+    UserActivatedPayload, UserCreatedPayload, UserGroupAdminAddedPayload,
+    UserGroupAdminRemovedPayload, UserGroupCreatedPayload, UserGroupDeletedPayload,
+    UserGroupMemberAddedPayload, UserGroupMemberRemovedPayload, UserGroupMemberUpdatedPayload,
+    UserGroupUpdatedPayload,
 };
 
 /// イベント全てを網羅するenum ([non-exhaustive](https://doc.rust-lang.org/reference/attributes/type_system.html))
@@ -58,6 +59,8 @@ pub enum Event {
     ChannelTopicChanged(ChannelTopicChangedPayload),
     /// ユーザーが作成された
     UserCreated(UserCreatedPayload),
+    /// 凍結されていたユーザーが再び有効化された
+    UserActivated(UserActivatedPayload),
     /// スタンプが作成された
     StampCreated(StampCreatedPayload),
     /// BOTにタグが追加された
@@ -130,6 +133,8 @@ pub enum EventKind {
     ChannelTopicChanged,
     /// ユーザーが作成された
     UserCreated,
+    /// 凍結されていたユーザーが再び有効化された
+    UserActivated,
     /// スタンプが作成された
     StampCreated,
     /// BOTにタグが追加された
@@ -199,12 +204,158 @@ impl FromStr for EventKind {
     }
 }
 
+impl EventKind {
+    /// 全ての種類
+    const ALL: &'static [Self] = {
+        macro_rules! all_kinds {
+            ($($i:ident),*) => {
+                &[$(EventKind::$i),*]
+            };
+        }
+
+        all_events!(all_kinds)
+    };
+
+    const fn bit(self) -> u32 {
+        1 << self as u32
+    }
+}
+
+// `EventKindSet`は`u32`のビット集合で表現するため、種類が増えすぎたら型を広げる
+const _: () = assert!(EventKind::ALL.len() <= u32::BITS as usize);
+
+/// [`EventKind`]の集合
+///
+/// ## Example
+/// ```
+/// use traq_bot_http::{EventKind, EventKindSet};
+///
+/// let kinds = EventKindSet::new()
+///     .with(EventKind::MessageCreated)
+///     .with(EventKind::DirectMessageCreated);
+/// assert!(kinds.contains(EventKind::MessageCreated));
+/// assert!(!kinds.contains(EventKind::Ping));
+/// assert_eq!(kinds.len(), 2);
+/// ```
+#[must_use]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct EventKindSet {
+    bits: u32,
+}
+
+impl EventKindSet {
+    /// 空の集合を作成します。
+    pub const fn new() -> Self {
+        Self { bits: 0 }
+    }
+
+    /// 全ての[`EventKind`]を含む集合を作成します。
+    pub fn all() -> Self {
+        EventKind::ALL.iter().copied().collect()
+    }
+
+    /// `kind`を加えた集合を返します。
+    pub const fn with(self, kind: EventKind) -> Self {
+        Self {
+            bits: self.bits | kind.bit(),
+        }
+    }
+
+    /// `kind`を加えます。既に含まれていた場合は`false`を返します。
+    pub fn insert(&mut self, kind: EventKind) -> bool {
+        let contained = self.contains(kind);
+        self.bits |= kind.bit();
+        !contained
+    }
+
+    /// `kind`を取り除きます。含まれていなかった場合は`false`を返します。
+    pub fn remove(&mut self, kind: EventKind) -> bool {
+        let contained = self.contains(kind);
+        self.bits &= !kind.bit();
+        contained
+    }
+
+    /// `kind`が含まれていれば`true`を返します。
+    #[must_use]
+    pub const fn contains(&self, kind: EventKind) -> bool {
+        self.bits & kind.bit() != 0
+    }
+
+    /// 含まれる種類の数を返します。
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.bits.count_ones() as usize
+    }
+
+    /// 空であれば`true`を返します。
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.bits == 0
+    }
+
+    /// 含まれる種類を[`EventKind`]の定義順に返します。
+    pub fn iter(&self) -> impl Iterator<Item = EventKind> + '_ {
+        EventKind::ALL
+            .iter()
+            .copied()
+            .filter(|kind| self.contains(*kind))
+    }
+}
+
+impl From<EventKind> for EventKindSet {
+    fn from(kind: EventKind) -> Self {
+        Self::new().with(kind)
+    }
+}
+
+impl<const N: usize> From<[EventKind; N]> for EventKindSet {
+    fn from(kinds: [EventKind; N]) -> Self {
+        kinds.into_iter().collect()
+    }
+}
+
+impl FromIterator<EventKind> for EventKindSet {
+    fn from_iter<T: IntoIterator<Item = EventKind>>(iter: T) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl Extend<EventKind> for EventKindSet {
+    fn extend<T: IntoIterator<Item = EventKind>>(&mut self, iter: T) {
+        for kind in iter {
+            self.insert(kind);
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     use crate::macros::{all_events, test_event_convert, test_event_to_kind};
 
+    #[test]
+    fn event_kind_set() {
+        let mut set = EventKindSet::from([EventKind::Left, EventKind::Ping]);
+        assert_eq!(set.len(), 2);
+        assert_eq!(
+            set.iter().collect::<Vec<_>>(),
+            [EventKind::Ping, EventKind::Left]
+        );
+        assert!(!set.insert(EventKind::Ping));
+        assert!(set.remove(EventKind::Ping));
+        assert!(!set.contains(EventKind::Ping));
+        assert!(set.contains(EventKind::Left));
+        assert!(EventKindSet::new().is_empty());
+
+        let all = EventKindSet::all();
+        assert_eq!(all.len(), EventKind::ALL.len());
+        assert!(all.contains(EventKind::UserGroupAdminRemoved));
+        assert!(all.iter().eq(EventKind::ALL.iter().copied()));
+    }
+
     test_event_convert! {"system", Ping}
 
     test_event_convert! {"system", Joined}
@@ -231,6 +382,8 @@ mod tests {
 
     test_event_convert! {"user", UserCreated}
 
+    test_event_convert! {"user", UserActivated}
+
     test_event_convert! {"stamp", StampCreated}
 
     test_event_convert! {"tag", TagAdded}
@@ -279,6 +432,8 @@ mod tests {
 
     test_event_to_kind! {"user", UserCreated}
 
+    test_event_to_kind! {"user", UserActivated}
+
     test_event_to_kind! {"stamp", StampCreated}
 
     test_event_to_kind! {"tag", TagAdded}
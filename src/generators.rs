@@ -0,0 +1,873 @@
+//! `arbitrary`, `proptest` featureによる値の生成
+//!
+//! [`Event`], 各ペイロード, [`crate::payloads::types`]の型に対して
+//! [`arbitrary::Arbitrary`]と[`proptest::arbitrary::Arbitrary`]を実装します。
+//!
+//! 生成される値は、traQから送られうる妥当なものに限られます。
+//!
+//! - UUIDはバージョン4の形式
+//! - タイムスタンプは`0000-01-01T00:00:00Z`から`9999-12-30T22:00:00.999999999Z`の範囲 (`jiff::Timestamp`にも変換できる)
+//! - チャンネルの`path`は[`ChannelPath`]として正しく、`name`は`path`の末尾と一致
+//! - メッセージの`plainText`と`embedded`は本文`text`と一致し、埋め込みの`raw`は`type`に沿った形式
+//! - 作成日時は更新日時以前
+//! - ユーザーグループの管理者・メンバーの`groupId`はグループの`id`と一致し、ユーザーは重複しない
+//!
+//! [`arbitrary::Arbitrary`]: ::arbitrary::Arbitrary
+//! [`proptest::arbitrary::Arbitrary`]: ::proptest::arbitrary::Arbitrary
+
+use std::convert::identity;
+use std::fmt::Write;
+
+use crate::message::MessageBuilder;
+use crate::payloads::types::{
+    Channel, ChannelPath, DeletedDirectMessage, DeletedMessage, EmbeddedInfo, GroupMember, Message,
+    MessageStamp, Timestamp, User, UserGroup, UserGroupAdmin, UserGroupMember, UserGroupRole,
+    UserGroupType, Uuid,
+};
+use crate::payloads::{
+    BotMessageStampsUpdatedPayload, ChannelCreatedPayload, ChannelTopicChangedPayload,
+    DirectMessageCreatedPayload, DirectMessageDeletedPayload, DirectMessageUpdatedPayload,
+    JoinedPayload, LeftPayload, MessageCreatedPayload, MessageDeletedPayload,
+    MessageUpdatedPayload, PingPayload, StampCreatedPayload, TagAddedPayload, TagRemovedPayload,
+    UserActivatedPayload, UserCreatedPayload, UserGroupAdminAddedPayload,
+    UserGroupAdminRemovedPayload, UserGroupCreatedPayload, UserGroupDeletedPayload,
+    UserGroupMemberAddedPayload, UserGroupMemberRemovedPayload, UserGroupMemberUpdatedPayload,
+    UserGroupUpdatedPayload,
+};
+#[cfg(feature = "arbitrary")]
+use crate::Event;
+use crate::EventKind;
+
+/// ユーザー名, スタンプ名, チャンネル名に使える文字
+const NAME_CHARS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789_-";
+/// ユーザー名, スタンプ名の最大文字数
+const MAX_NAME_LEN: usize = 32;
+/// チャンネル名の最大文字数
+const MAX_CHANNEL_NAME_LEN: usize = 20;
+/// 生成するチャンネルの階層の最大の深さ
+const MAX_CHANNEL_DEPTH: usize = 3;
+/// 生成するメッセージ本文の断片の最大数
+const MAX_MESSAGE_PIECES: usize = 8;
+/// 生成する配列の最大の長さ
+const MAX_LIST_LEN: usize = 8;
+/// 埋め込みの`type`
+const EMBED_TYPES: &[&str] = &["user", "channel", "group"];
+
+/// メッセージ本文の断片
+#[derive(Debug, Clone)]
+enum Piece {
+    Text(String),
+    Embed(EmbeddedInfo),
+}
+
+fn build_text(pieces: Vec<Piece>) -> String {
+    pieces
+        .into_iter()
+        .fold(MessageBuilder::new(), |builder, piece| match piece {
+            Piece::Text(text) => builder.text(&text),
+            Piece::Embed(info) => builder.embed(info),
+        })
+        .build()
+}
+
+/// 16バイトからバージョン4のUUIDを作る
+fn format_uuid(mut bytes: [u8; 16]) -> Uuid {
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let mut s = String::with_capacity(36);
+    for (i, b) in bytes.iter().enumerate() {
+        if matches!(i, 4 | 6 | 8 | 10) {
+            s.push('-');
+        }
+        write!(s, "{b:02x}").expect("failed to write to String");
+    }
+    s.parse().expect("failed to parse generated uuid")
+}
+
+fn name_of(chars: Vec<u8>) -> String {
+    chars.into_iter().map(char::from).collect()
+}
+
+fn ordered(a: Timestamp, b: Timestamp) -> (Timestamp, Timestamp) {
+    if a <= b {
+        (a, b)
+    } else {
+        (b, a)
+    }
+}
+
+fn fix_channel(mut channel: Channel) -> Channel {
+    let name = channel
+        .path
+        .rsplit_once('/')
+        .map_or(&channel.path[1..], |(_, name)| name);
+    channel.name = name.to_string();
+    (channel.created_at, channel.updated_at) = ordered(channel.created_at, channel.updated_at);
+    channel
+}
+
+fn fix_embedded_info(mut info: EmbeddedInfo) -> EmbeddedInfo {
+    // `raw`はチャンネルのパスとして生成されている
+    if info.r#type != "channel" {
+        let name = info.raw.rsplit(['#', '/']).next().unwrap_or_default();
+        info.raw = format!("@{name}");
+    }
+    info
+}
+
+fn fix_message(mut message: Message) -> Message {
+    (message.created_at, message.updated_at) = ordered(message.created_at, message.updated_at);
+    let text = std::mem::take(&mut message.text);
+    message.with_text(text)
+}
+
+fn fix_message_stamp(mut stamp: MessageStamp) -> MessageStamp {
+    (stamp.created_at, stamp.updated_at) = ordered(stamp.created_at, stamp.updated_at);
+    stamp
+}
+
+fn fix_user_group(mut group: UserGroup) -> UserGroup {
+    (group.created_at, group.updated_at) = ordered(group.created_at, group.updated_at);
+    group.admins.sort_by(|a, b| a.user_id.cmp(&b.user_id));
+    group.admins.dedup_by(|a, b| a.user_id == b.user_id);
+    group.members.sort_by(|a, b| a.user_id.cmp(&b.user_id));
+    group.members.dedup_by(|a, b| a.user_id == b.user_id);
+    for admin in &mut group.admins {
+        admin.group_id.clone_from(&group.id);
+    }
+    for member in &mut group.members {
+        member.group_id.clone_from(&group.id);
+    }
+    group
+}
+
+fn fix_stamps_updated(
+    mut payload: BotMessageStampsUpdatedPayload,
+) -> BotMessageStampsUpdatedPayload {
+    let key = |s: &MessageStamp| (s.stamp_id.clone(), s.user_id.clone());
+    payload.stamps.sort_by_key(key);
+    payload.stamps.dedup_by(|a, b| key(a) == key(b));
+    payload
+}
+
+#[cfg(feature = "arbitrary")]
+mod arb {
+    use ::arbitrary::{Arbitrary, Result, Unstructured};
+
+    use super::{
+        build_text, format_uuid, name_of, Piece, Uuid, EMBED_TYPES, MAX_CHANNEL_DEPTH,
+        MAX_CHANNEL_NAME_LEN, MAX_LIST_LEN, MAX_MESSAGE_PIECES, MAX_NAME_LEN, NAME_CHARS,
+    };
+
+    pub(super) fn any<'a, T: Arbitrary<'a>>(u: &mut Unstructured<'a>) -> Result<T> {
+        u.arbitrary()
+    }
+
+    pub(super) fn list<'a, T: Arbitrary<'a>>(u: &mut Unstructured<'a>) -> Result<Vec<T>> {
+        let len = u.int_in_range(0..=MAX_LIST_LEN)?;
+        (0..len).map(|_| u.arbitrary()).collect()
+    }
+
+    // 他の生成器とシグネチャを揃える
+    #[allow(clippy::unnecessary_wraps)]
+    pub(super) fn placeholder<T: Default>(_: &mut Unstructured<'_>) -> Result<T> {
+        Ok(T::default())
+    }
+
+    pub(super) fn uuid(u: &mut Unstructured<'_>) -> Result<Uuid> {
+        u.arbitrary().map(format_uuid)
+    }
+
+    fn name_chars(u: &mut Unstructured<'_>, max_len: usize) -> Result<String> {
+        let len = u.int_in_range(1..=max_len)?;
+        let chars = (0..len)
+            .map(|_| u.choose(NAME_CHARS).copied())
+            .collect::<Result<_>>()?;
+        Ok(name_of(chars))
+    }
+
+    pub(super) fn name(u: &mut Unstructured<'_>) -> Result<String> {
+        name_chars(u, MAX_NAME_LEN)
+    }
+
+    pub(super) fn channel_path(u: &mut Unstructured<'_>) -> Result<String> {
+        let depth = u.int_in_range(1..=MAX_CHANNEL_DEPTH)?;
+        let segments = (0..depth)
+            .map(|_| name_chars(u, MAX_CHANNEL_NAME_LEN))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(format!("#{}", segments.join("/")))
+    }
+
+    pub(super) fn embed_type(u: &mut Unstructured<'_>) -> Result<String> {
+        u.choose(EMBED_TYPES).map(|s| (*s).to_string())
+    }
+
+    pub(super) fn count(u: &mut Unstructured<'_>) -> Result<i32> {
+        u.int_in_range(1..=i32::MAX)
+    }
+
+    pub(super) fn message_text(u: &mut Unstructured<'_>) -> Result<String> {
+        let len = u.int_in_range(0..=MAX_MESSAGE_PIECES)?;
+        let pieces = (0..len)
+            .map(|_| {
+                if u.arbitrary()? {
+                    u.arbitrary().map(Piece::Text)
+                } else {
+                    u.arbitrary().map(Piece::Embed)
+                }
+            })
+            .collect::<Result<_>>()?;
+        Ok(build_text(pieces))
+    }
+}
+
+#[cfg(feature = "proptest")]
+mod prop {
+    use std::fmt::Debug;
+
+    use ::proptest::arbitrary::Arbitrary;
+    use ::proptest::collection::vec;
+    use ::proptest::prop_oneof;
+    use ::proptest::sample::select;
+    use ::proptest::strategy::{Just, Strategy};
+
+    use super::{
+        build_text, format_uuid, name_of, EmbeddedInfo, Piece, Uuid, EMBED_TYPES,
+        MAX_CHANNEL_DEPTH, MAX_CHANNEL_NAME_LEN, MAX_LIST_LEN, MAX_MESSAGE_PIECES, MAX_NAME_LEN,
+        NAME_CHARS,
+    };
+
+    pub(super) fn any<T: Arbitrary>() -> impl Strategy<Value = T> {
+        ::proptest::arbitrary::any::<T>()
+    }
+
+    pub(super) fn list<T: Arbitrary>() -> impl Strategy<Value = Vec<T>> {
+        vec(any::<T>(), 0..=MAX_LIST_LEN)
+    }
+
+    pub(super) fn placeholder<T: Default + Clone + Debug>() -> impl Strategy<Value = T> {
+        Just(T::default())
+    }
+
+    pub(super) fn uuid() -> impl Strategy<Value = Uuid> {
+        any::<[u8; 16]>().prop_map(format_uuid)
+    }
+
+    fn name_chars(max_len: usize) -> impl Strategy<Value = String> {
+        vec(select(NAME_CHARS), 1..=max_len).prop_map(name_of)
+    }
+
+    pub(super) fn name() -> impl Strategy<Value = String> {
+        name_chars(MAX_NAME_LEN)
+    }
+
+    pub(super) fn channel_path() -> impl Strategy<Value = String> {
+        vec(name_chars(MAX_CHANNEL_NAME_LEN), 1..=MAX_CHANNEL_DEPTH)
+            .prop_map(|segments| format!("#{}", segments.join("/")))
+    }
+
+    pub(super) fn embed_type() -> impl Strategy<Value = String> {
+        select(EMBED_TYPES).prop_map(str::to_string)
+    }
+
+    pub(super) fn count() -> impl Strategy<Value = i32> {
+        1..=i32::MAX
+    }
+
+    pub(super) fn message_text() -> impl Strategy<Value = String> {
+        let piece = prop_oneof![
+            any::<String>().prop_map(Piece::Text),
+            any::<EmbeddedInfo>().prop_map(Piece::Embed),
+        ];
+        vec(piece, 0..=MAX_MESSAGE_PIECES).prop_map(build_text)
+    }
+}
+
+/// フィールドごとの生成器から`arbitrary`, `proptest`の`Arbitrary`を実装する
+///
+/// 各フィールドの値は`arb`, `prop`モジュールの同名の関数で生成され、最後に`$fix`で整えられる
+macro_rules! generators {
+    ($(
+        $t:ident { $( $field:ident: $gen:ident ),* $(,)? } => $fix:ident;
+    )*) => {$(
+        #[cfg(feature = "arbitrary")]
+        impl<'a> ::arbitrary::Arbitrary<'a> for $t {
+            fn arbitrary(u: &mut ::arbitrary::Unstructured<'a>) -> ::arbitrary::Result<Self> {
+                Ok($fix(Self {
+                    $( $field: arb::$gen(u)?, )*
+                }))
+            }
+        }
+
+        #[cfg(feature = "proptest")]
+        impl ::proptest::arbitrary::Arbitrary for $t {
+            type Parameters = ();
+            type Strategy = ::proptest::strategy::BoxedStrategy<Self>;
+
+            fn arbitrary_with((): Self::Parameters) -> Self::Strategy {
+                use ::proptest::strategy::Strategy;
+
+                ( $( prop::$gen(), )* )
+                    .prop_map(|( $( $field, )* )| $fix(Self { $( $field, )* }))
+                    .boxed()
+            }
+        }
+    )*};
+}
+
+generators! {
+    User {
+        id: uuid,
+        name: name,
+        display_name: any,
+        icon_id: uuid,
+        bot: any,
+    } => identity;
+
+    Channel {
+        id: uuid,
+        name: placeholder,
+        path: channel_path,
+        parent_id: uuid,
+        creator: any,
+        created_at: any,
+        updated_at: any,
+    } => fix_channel;
+
+    EmbeddedInfo {
+        raw: channel_path,
+        r#type: embed_type,
+        id: uuid,
+    } => fix_embedded_info;
+
+    Message {
+        id: uuid,
+        user: any,
+        channel_id: uuid,
+        text: message_text,
+        plain_text: placeholder,
+        embedded: placeholder,
+        created_at: any,
+        updated_at: any,
+    } => fix_message;
+
+    DeletedMessage {
+        id: uuid,
+        channel_id: uuid,
+    } => identity;
+
+    DeletedDirectMessage {
+        id: uuid,
+        user_id: uuid,
+        channel_id: uuid,
+    } => identity;
+
+    MessageStamp {
+        stamp_id: uuid,
+        user_id: uuid,
+        count: count,
+        created_at: any,
+        updated_at: any,
+    } => fix_message_stamp;
+
+    GroupMember {
+        group_id: uuid,
+        user_id: uuid,
+    } => identity;
+
+    UserGroupAdmin {
+        group_id: uuid,
+        user_id: uuid,
+    } => identity;
+
+    UserGroupMember {
+        group_id: uuid,
+        user_id: uuid,
+        role: any,
+    } => identity;
+
+    UserGroup {
+        id: uuid,
+        name: any,
+        description: any,
+        r#type: any,
+        icon: uuid,
+        admins: list,
+        members: list,
+        created_at: any,
+        updated_at: any,
+    } => fix_user_group;
+
+    PingPayload {
+        event_time: any,
+    } => identity;
+
+    JoinedPayload {
+        event_time: any,
+        channel: any,
+    } => identity;
+
+    LeftPayload {
+        event_time: any,
+        channel: any,
+    } => identity;
+
+    MessageCreatedPayload {
+        event_time: any,
+        message: any,
+    } => identity;
+
+    MessageDeletedPayload {
+        event_time: any,
+        message: any,
+    } => identity;
+
+    MessageUpdatedPayload {
+        event_time: any,
+        message: any,
+    } => identity;
+
+    DirectMessageCreatedPayload {
+        event_time: any,
+        message: any,
+    } => identity;
+
+    DirectMessageDeletedPayload {
+        event_time: any,
+        message: any,
+    } => identity;
+
+    DirectMessageUpdatedPayload {
+        event_time: any,
+        message: any,
+    } => identity;
+
+    BotMessageStampsUpdatedPayload {
+        event_time: any,
+        message_id: uuid,
+        stamps: list,
+    } => fix_stamps_updated;
+
+    ChannelCreatedPayload {
+        event_time: any,
+        channel: any,
+    } => identity;
+
+    ChannelTopicChangedPayload {
+        event_time: any,
+        channel: any,
+        topic: any,
+        updater: any,
+    } => identity;
+
+    UserCreatedPayload {
+        event_time: any,
+        user: any,
+    } => identity;
+
+    UserActivatedPayload {
+        event_time: any,
+        user: any,
+    } => identity;
+
+    StampCreatedPayload {
+        event_time: any,
+        id: uuid,
+        name: name,
+        file_id: uuid,
+        creator: any,
+    } => identity;
+
+    TagAddedPayload {
+        event_time: any,
+        tag_id: uuid,
+        tag: any,
+    } => identity;
+
+    TagRemovedPayload {
+        event_time: any,
+        tag_id: uuid,
+        tag: any,
+    } => identity;
+
+    UserGroupCreatedPayload {
+        event_time: any,
+        group: any,
+    } => identity;
+
+    UserGroupUpdatedPayload {
+        event_time: any,
+        group_id: uuid,
+    } => identity;
+
+    UserGroupDeletedPayload {
+        event_time: any,
+        group_id: uuid,
+    } => identity;
+
+    UserGroupMemberAddedPayload {
+        event_time: any,
+        group_member: any,
+    } => identity;
+
+    UserGroupMemberUpdatedPayload {
+        event_time: any,
+        group_member: any,
+    } => identity;
+
+    UserGroupMemberRemovedPayload {
+        event_time: any,
+        group_member: any,
+    } => identity;
+
+    UserGroupAdminAddedPayload {
+        event_time: any,
+        group_member: any,
+    } => identity;
+
+    UserGroupAdminRemovedPayload {
+        event_time: any,
+        group_member: any,
+    } => identity;
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> ::arbitrary::Arbitrary<'a> for Timestamp {
+    fn arbitrary(u: &mut ::arbitrary::Unstructured<'a>) -> ::arbitrary::Result<Self> {
+        let secs = u.int_in_range(Self::SECS_RANGE)?;
+        let nanos = u.int_in_range(Self::NANOS_RANGE)?;
+        Ok(Self::from_unix(secs, nanos).expect("generated timestamp out of range"))
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl ::proptest::arbitrary::Arbitrary for Timestamp {
+    type Parameters = ();
+    type Strategy = ::proptest::strategy::BoxedStrategy<Self>;
+
+    fn arbitrary_with((): Self::Parameters) -> Self::Strategy {
+        use ::proptest::strategy::Strategy;
+
+        (Self::SECS_RANGE, Self::NANOS_RANGE)
+            .prop_map(|(secs, nanos)| {
+                Self::from_unix(secs, nanos).expect("generated timestamp out of range")
+            })
+            .boxed()
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> ::arbitrary::Arbitrary<'a> for ChannelPath {
+    fn arbitrary(u: &mut ::arbitrary::Unstructured<'a>) -> ::arbitrary::Result<Self> {
+        let path = arb::channel_path(u)?;
+        Ok(path.parse().expect("generated invalid channel path"))
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl ::proptest::arbitrary::Arbitrary for ChannelPath {
+    type Parameters = ();
+    type Strategy = ::proptest::strategy::BoxedStrategy<Self>;
+
+    fn arbitrary_with((): Self::Parameters) -> Self::Strategy {
+        use ::proptest::strategy::Strategy;
+
+        prop::channel_path()
+            .prop_map(|path| path.parse().expect("generated invalid channel path"))
+            .boxed()
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> ::arbitrary::Arbitrary<'a> for UserGroupType {
+    fn arbitrary(u: &mut ::arbitrary::Unstructured<'a>) -> ::arbitrary::Result<Self> {
+        Ok(match u.int_in_range(0..=2u8)? {
+            0 => Self::Unspecified,
+            1 => Self::Grade,
+            _ => u.arbitrary::<String>()?.into(),
+        })
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl ::proptest::arbitrary::Arbitrary for UserGroupType {
+    type Parameters = ();
+    type Strategy = ::proptest::strategy::BoxedStrategy<Self>;
+
+    fn arbitrary_with((): Self::Parameters) -> Self::Strategy {
+        use ::proptest::strategy::{Just, Strategy};
+
+        ::proptest::prop_oneof![
+            Just(Self::Unspecified),
+            Just(Self::Grade),
+            prop::any::<String>().prop_map(Self::from),
+        ]
+        .boxed()
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> ::arbitrary::Arbitrary<'a> for UserGroupRole {
+    fn arbitrary(u: &mut ::arbitrary::Unstructured<'a>) -> ::arbitrary::Result<Self> {
+        Ok(if u.arbitrary()? {
+            Self::Unspecified
+        } else {
+            u.arbitrary::<String>()?.into()
+        })
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl ::proptest::arbitrary::Arbitrary for UserGroupRole {
+    type Parameters = ();
+    type Strategy = ::proptest::strategy::BoxedStrategy<Self>;
+
+    fn arbitrary_with((): Self::Parameters) -> Self::Strategy {
+        use ::proptest::strategy::{Just, Strategy};
+
+        ::proptest::prop_oneof![
+            Just(Self::Unspecified),
+            prop::any::<String>().prop_map(Self::from),
+        ]
+        .boxed()
+    }
+}
+
+/// [`EventKind`], [`Event`]に`Arbitrary`を実装する。[`all_events`]と組み合わせること
+///
+/// [`all_events`]: crate::macros::all_events
+macro_rules! event_generators {
+    ($($i:ident),*) => {
+        /// 全ての[`EventKind`]
+        const EVENT_KINDS: &[EventKind] = &[$( EventKind::$i ),*];
+
+        #[cfg(feature = "arbitrary")]
+        impl<'a> ::arbitrary::Arbitrary<'a> for EventKind {
+            fn arbitrary(u: &mut ::arbitrary::Unstructured<'a>) -> ::arbitrary::Result<Self> {
+                u.choose(EVENT_KINDS).copied()
+            }
+        }
+
+        #[cfg(feature = "proptest")]
+        impl ::proptest::arbitrary::Arbitrary for EventKind {
+            type Parameters = ();
+            type Strategy = ::proptest::sample::Select<Self>;
+
+            fn arbitrary_with((): Self::Parameters) -> Self::Strategy {
+                ::proptest::sample::select(EVENT_KINDS)
+            }
+        }
+
+        #[cfg(feature = "arbitrary")]
+        impl<'a> ::arbitrary::Arbitrary<'a> for Event {
+            fn arbitrary(u: &mut ::arbitrary::Unstructured<'a>) -> ::arbitrary::Result<Self> {
+                match u.arbitrary()? {
+                    $( EventKind::$i => u.arbitrary().map(Self::$i), )*
+                }
+            }
+        }
+
+        #[cfg(feature = "proptest")]
+        impl ::proptest::arbitrary::Arbitrary for crate::Event {
+            type Parameters = ();
+            type Strategy = ::proptest::strategy::BoxedStrategy<Self>;
+
+            fn arbitrary_with((): Self::Parameters) -> Self::Strategy {
+                use ::proptest::strategy::{Strategy, Union};
+
+                ::paste::paste! {
+                    Union::new([
+                        $( prop::any::<[<$i Payload>]>().prop_map(Self::$i).boxed(), )*
+                    ])
+                    .boxed()
+                }
+            }
+        }
+    };
+}
+
+crate::macros::all_events! {event_generators}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::macros::all_events;
+    use crate::message;
+    use crate::parser::parse_body;
+    use crate::Event;
+
+    /// ペイロードをJSONにする
+    fn payload_json(event: &Event) -> String {
+        macro_rules! match_event_to_json {
+            ($($i:ident),*) => {
+                match event {
+                    $( Event::$i(payload) => payload.to_string(), )*
+                }
+            };
+        }
+
+        all_events!(match_event_to_json)
+    }
+
+    fn check_uuid(id: &Uuid) {
+        let s = id.to_string();
+        let groups: Vec<_> = s.split('-').map(str::len).collect();
+        assert_eq!(groups, [8, 4, 4, 4, 12], "{s}");
+        assert!(s
+            .chars()
+            .all(|c| c == '-' || c.is_ascii_digit() || ('a'..='f').contains(&c)));
+        assert_eq!(&s[14..15], "4", "{s}");
+    }
+
+    fn check_channel(channel: &Channel) {
+        check_uuid(&channel.id);
+        let path = channel.channel_path().unwrap();
+        assert_eq!(path.name(), channel.name);
+        assert!(channel.created_at <= channel.updated_at);
+    }
+
+    fn check_message(m: &Message) {
+        check_uuid(&m.id);
+        check_uuid(&m.channel_id);
+        assert_eq!(m.plain_text, message::plain_text(&m.text));
+        assert_eq!(m.embedded, message::embedded(&m.text).collect::<Vec<_>>());
+        for info in &m.embedded {
+            let prefix = if info.r#type == "channel" { '#' } else { '@' };
+            assert!(info.raw.starts_with(prefix), "{info:?}");
+        }
+        assert!(m.created_at <= m.updated_at);
+    }
+
+    fn check_user_group(group: &UserGroup) {
+        check_uuid(&group.id);
+        assert!(group.admins.iter().all(|a| a.group_id == group.id));
+        assert!(group.members.iter().all(|m| m.group_id == group.id));
+        let mut members: Vec<_> = group.members.iter().map(|m| &m.user_id).collect();
+        members.dedup();
+        assert_eq!(members.len(), group.members.len());
+    }
+
+    /// 生成された値が妥当で、JSONを経由して元に戻ることを確かめる
+    fn check_event(event: &Event) {
+        let json = payload_json(event);
+        assert_eq!(&parse_body(event.kind(), &json).unwrap(), event, "{json}");
+        match event {
+            Event::Joined(JoinedPayload { channel, .. })
+            | Event::Left(LeftPayload { channel, .. })
+            | Event::ChannelCreated(ChannelCreatedPayload { channel, .. })
+            | Event::ChannelTopicChanged(ChannelTopicChangedPayload { channel, .. }) => {
+                check_channel(channel);
+            }
+            Event::MessageCreated(MessageCreatedPayload { message, .. })
+            | Event::MessageUpdated(MessageUpdatedPayload { message, .. })
+            | Event::DirectMessageCreated(DirectMessageCreatedPayload { message, .. })
+            | Event::DirectMessageUpdated(DirectMessageUpdatedPayload { message, .. }) => {
+                check_message(message);
+            }
+            Event::UserGroupCreated(UserGroupCreatedPayload { group, .. }) => {
+                check_user_group(group);
+            }
+            _ => {}
+        }
+    }
+
+    #[cfg(feature = "arbitrary")]
+    mod arbitrary {
+        use ::arbitrary::{Arbitrary, Unstructured};
+
+        use super::*;
+
+        /// 疑似乱数のバイト列
+        fn bytes(seed: u64, len: usize) -> Vec<u8> {
+            let mut x = seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1;
+            (0..len)
+                .map(|_| {
+                    x ^= x << 13;
+                    x ^= x >> 7;
+                    x ^= x << 17;
+                    x.to_le_bytes()[0]
+                })
+                .collect()
+        }
+
+        #[test]
+        fn event_round_trip() {
+            for seed in 0..512 {
+                let data = bytes(seed, 4096);
+                let event = Event::arbitrary(&mut Unstructured::new(&data)).unwrap();
+                check_event(&event);
+            }
+        }
+
+        #[test]
+        fn message_embeds() {
+            let messages: Vec<_> = (0..256)
+                .map(|seed| {
+                    let data = bytes(seed, 1024);
+                    Message::arbitrary(&mut Unstructured::new(&data)).unwrap()
+                })
+                .collect();
+            for message in &messages {
+                check_message(message);
+            }
+            assert!(messages.iter().any(|m| !m.embedded.is_empty()));
+        }
+
+        #[test]
+        fn every_kind() {
+            for seed in 0..256 {
+                let data = bytes(seed, 64);
+                let kind = EventKind::arbitrary(&mut Unstructured::new(&data)).unwrap();
+                assert!(EVENT_KINDS.contains(&kind));
+            }
+            // 入力が尽きても妥当な値が生成される
+            check_event(&Event::arbitrary(&mut Unstructured::new(&[])).unwrap());
+        }
+
+        #[test]
+        fn timestamp_round_trip() {
+            for seed in 0..512 {
+                let data = bytes(seed, 16);
+                let timestamp = Timestamp::arbitrary(&mut Unstructured::new(&data)).unwrap();
+                assert_eq!(timestamp.to_string().parse(), Ok(timestamp));
+            }
+        }
+    }
+
+    #[cfg(feature = "proptest")]
+    mod proptest {
+        use ::proptest::prelude::*;
+
+        use super::*;
+
+        proptest! {
+            #[test]
+            fn event_round_trip(event in any::<Event>()) {
+                check_event(&event);
+            }
+
+            #[test]
+            fn message_round_trip(message in any::<Message>()) {
+                check_message(&message);
+                prop_assert_eq!(message.to_string().parse::<Message>().unwrap(), message);
+            }
+
+            #[test]
+            fn user_group_round_trip(group in any::<UserGroup>()) {
+                check_user_group(&group);
+                prop_assert_eq!(group.to_string().parse::<UserGroup>().unwrap(), group);
+            }
+
+            #[test]
+            fn timestamp_round_trip(timestamp in any::<Timestamp>()) {
+                prop_assert_eq!(timestamp.to_string().parse(), Ok(timestamp));
+            }
+
+            #[test]
+            fn channel_path_round_trip(path in any::<ChannelPath>()) {
+                prop_assert_eq!(path.to_string().parse(), Ok(path));
+            }
+        }
+    }
+}
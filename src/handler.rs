@@ -9,23 +9,39 @@ use std::marker::PhantomData;
 use std::sync::Arc;
 use std::task::{Context, Poll};
 
+use futures_core::ready;
+use futures_util::future::Either;
 use http::{Request, Response};
 use paste::paste;
+use tower_layer::Layer;
 use tower_service::Service;
 
 use super::Handler;
+use crate::cache::Observer;
 use crate::macros::all_events;
-use crate::{Error, Event, RequestParser};
+use crate::{Error, Event, EventKindSet, RequestParser};
 
+mod background;
 mod future;
+mod shutdown;
 
+#[cfg(feature = "tokio")]
+pub use background::TokioSpawn;
+pub use background::{Background, Spawn};
 #[allow(clippy::module_name_repetitions)]
 pub use future::{HandlerCall, WrapErrorFuture};
+pub use shutdown::{Graceful, GracefulFuture, Shutdown, ShutdownReport, TrackSpawn};
 
 /// handleされなかった[`Event`]の受け皿となる[`Service`]です。
 ///
+/// [`RequestParser::into_handler`]で作成したイベントハンドラの既定の受け皿で、受け取ったイベントを捨てます。
+/// 受け皿を置き換えるには[`Handler::on_event`]または[`Handler::new`]を使用してください。
+///
 /// [`Event`]: crate::Event
 /// [`Service`]: tower::Service
+/// [`RequestParser::into_handler`]: crate::RequestParser::into_handler
+/// [`Handler::on_event`]: crate::Handler::on_event
+/// [`Handler::new`]: crate::Handler::new
 #[must_use]
 #[derive(Debug, Clone, Copy)]
 pub struct Sink {
@@ -34,7 +50,7 @@ pub struct Sink {
 }
 
 impl Sink {
-    fn new() -> Self {
+    pub(crate) fn new() -> Self {
         Self { _priv: PhantomData }
     }
 }
@@ -54,6 +70,258 @@ impl<T> Service<T> for Sink {
     }
 }
 
+/// 他の`Service`にhandleされなかった[`Event`]をhandleする[`Service`]です。
+///
+/// [`Event`]: crate::Event
+/// [`Service`]: tower::Service
+#[must_use]
+#[derive(Debug, Clone)]
+pub struct OnEvent<Service, Req> {
+    _req: PhantomData<Req>,
+    inner: Service,
+}
+
+impl<Srv, Req> OnEvent<Srv, Req> {
+    fn new(inner: Srv) -> Self {
+        Self {
+            _req: PhantomData,
+            inner,
+        }
+    }
+}
+
+impl<Srv> Service<Event> for OnEvent<Srv, Event>
+where
+    Srv: Service<Event, Response = ()>,
+    Srv::Error: Into<Box<dyn std::error::Error + Send + Sync + 'static>>,
+{
+    type Response = ();
+    type Error = Error;
+    type Future = WrapErrorFuture<Srv::Future, Srv::Error>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Error::from_handler)
+    }
+
+    #[inline]
+    fn call(&mut self, request: Event) -> Self::Future {
+        WrapErrorFuture::new(self.inner.call(request))
+    }
+}
+
+impl<State, Srv> Service<(State, Event)> for OnEvent<Srv, Event>
+where
+    Srv: Service<Event, Response = ()>,
+    Srv::Error: Into<Box<dyn std::error::Error + Send + Sync + 'static>>,
+{
+    type Response = ();
+    type Error = Error;
+    type Future = WrapErrorFuture<Srv::Future, Srv::Error>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Error::from_handler)
+    }
+
+    #[inline]
+    fn call(&mut self, (_, request): (State, Event)) -> Self::Future {
+        WrapErrorFuture::new(self.inner.call(request))
+    }
+}
+
+impl<State, Srv> Service<(State, Event)> for OnEvent<Srv, (State, Event)>
+where
+    Srv: Service<(State, Event), Response = ()>,
+    Srv::Error: Into<Box<dyn std::error::Error + Send + Sync + 'static>>,
+{
+    type Response = ();
+    type Error = Error;
+    type Future = WrapErrorFuture<Srv::Future, Srv::Error>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Error::from_handler)
+    }
+
+    #[inline]
+    fn call(&mut self, request: (State, Event)) -> Self::Future {
+        WrapErrorFuture::new(self.inner.call(request))
+    }
+}
+
+/// [`EventKindSet`]に含まれる種類の[`Event`]をhandleする[`Service`]です。
+///
+/// [`Event`]: crate::Event
+/// [`EventKindSet`]: crate::EventKindSet
+/// [`Service`]: tower::Service
+#[must_use]
+#[derive(Debug, Clone)]
+pub struct OnKinds<Service, Fallback, Req> {
+    _req: PhantomData<Req>,
+    kinds: EventKindSet,
+    inner: Service,
+    fallback: Fallback,
+}
+
+impl<Srv, Fallback, Req> OnKinds<Srv, Fallback, Req> {
+    fn poll_ready_both<R1, R2>(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Error>>
+    where
+        Srv: Service<R1>,
+        Srv::Error: Into<Box<dyn std::error::Error + Send + Sync + 'static>>,
+        Fallback: Service<R2, Error = Error>,
+    {
+        if let Err(e) = ready!(self.inner.poll_ready(cx)) {
+            return Poll::Ready(Err(Error::from_handler(e)));
+        }
+        self.fallback.poll_ready(cx)
+    }
+}
+
+impl<Srv, Fallback> Service<Event> for OnKinds<Srv, Fallback, Event>
+where
+    Srv: Service<Event, Response = ()>,
+    Srv::Error: Into<Box<dyn std::error::Error + Send + Sync + 'static>>,
+    Fallback: Service<Event, Response = (), Error = Error>,
+{
+    type Response = ();
+    type Error = Error;
+    type Future = Either<WrapErrorFuture<Srv::Future, Srv::Error>, Fallback::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.poll_ready_both::<Event, Event>(cx)
+    }
+
+    #[inline]
+    fn call(&mut self, request: Event) -> Self::Future {
+        if self.kinds.contains(request.kind()) {
+            Either::Left(WrapErrorFuture::new(self.inner.call(request)))
+        } else {
+            Either::Right(self.fallback.call(request))
+        }
+    }
+}
+
+impl<State, Srv, Fallback> Service<(State, Event)> for OnKinds<Srv, Fallback, Event>
+where
+    Srv: Service<Event, Response = ()>,
+    Srv::Error: Into<Box<dyn std::error::Error + Send + Sync + 'static>>,
+    Fallback: Service<(State, Event), Response = (), Error = Error>,
+{
+    type Response = ();
+    type Error = Error;
+    type Future = Either<WrapErrorFuture<Srv::Future, Srv::Error>, Fallback::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.poll_ready_both::<Event, (State, Event)>(cx)
+    }
+
+    #[inline]
+    fn call(&mut self, (state, request): (State, Event)) -> Self::Future {
+        if self.kinds.contains(request.kind()) {
+            Either::Left(WrapErrorFuture::new(self.inner.call(request)))
+        } else {
+            Either::Right(self.fallback.call((state, request)))
+        }
+    }
+}
+
+impl<State, Srv, Fallback> Service<(State, Event)> for OnKinds<Srv, Fallback, (State, Event)>
+where
+    Srv: Service<(State, Event), Response = ()>,
+    Srv::Error: Into<Box<dyn std::error::Error + Send + Sync + 'static>>,
+    Fallback: Service<(State, Event), Response = (), Error = Error>,
+{
+    type Response = ();
+    type Error = Error;
+    type Future = Either<WrapErrorFuture<Srv::Future, Srv::Error>, Fallback::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.poll_ready_both::<(State, Event), (State, Event)>(cx)
+    }
+
+    #[inline]
+    fn call(&mut self, request: (State, Event)) -> Self::Future {
+        if self.kinds.contains(request.1.kind()) {
+            Either::Left(WrapErrorFuture::new(self.inner.call(request)))
+        } else {
+            Either::Right(self.fallback.call(request))
+        }
+    }
+}
+
+/// 直前に登録した`Service`に[`Layer`]を適用できる[`Service`]です。
+///
+/// [`Handler::layer_last`]で使用されます。
+///
+/// [`Layer`]: tower::Layer
+/// [`Service`]: tower::Service
+/// [`Handler::layer_last`]: crate::Handler::layer_last
+pub trait LayerLast<L> {
+    /// `layer`を適用した後の型
+    type Output;
+
+    /// 直前に登録した`Service`に`layer`を適用します。
+    fn layer_last(self, layer: L) -> Self::Output;
+}
+
+impl<L, Srv, Req> LayerLast<L> for OnEvent<Srv, Req>
+where
+    L: Layer<Srv>,
+{
+    type Output = OnEvent<L::Service, Req>;
+
+    fn layer_last(self, layer: L) -> Self::Output {
+        OnEvent::new(layer.layer(self.inner))
+    }
+}
+
+impl<L, Srv, Fallback, Req> LayerLast<L> for OnKinds<Srv, Fallback, Req>
+where
+    L: Layer<Srv>,
+{
+    type Output = OnKinds<L::Service, Fallback, Req>;
+
+    fn layer_last(self, layer: L) -> Self::Output {
+        OnKinds {
+            _req: PhantomData,
+            kinds: self.kinds,
+            inner: layer.layer(self.inner),
+            fallback: self.fallback,
+        }
+    }
+}
+
+/// [`Layer`]を適用した`Service`のエラーを[`Error`]に変換する[`Service`]です。
+///
+/// エラーが既に[`Error`]であれば、[`ErrorKind`]を保ったまま返します。
+///
+/// [`Layer`]: tower::Layer
+/// [`Error`]: crate::Error
+/// [`ErrorKind`]: crate::ErrorKind
+/// [`Service`]: tower::Service
+#[must_use]
+#[derive(Debug, Clone)]
+pub struct Layered<Service> {
+    inner: Service,
+}
+
+impl<Srv, Req> Service<Req> for Layered<Srv>
+where
+    Srv: Service<Req, Response = ()>,
+    Srv::Error: Into<Box<dyn std::error::Error + Send + Sync + 'static>>,
+{
+    type Response = ();
+    type Error = Error;
+    type Future = WrapErrorFuture<Srv::Future, Srv::Error>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Error::from_handler)
+    }
+
+    #[inline]
+    fn call(&mut self, request: Req) -> Self::Future {
+        WrapErrorFuture::new(self.inner.call(request))
+    }
+}
+
 /// 内部の`Service`に`State`を渡す[`Service`]です。
 ///
 /// `WithState::call`の度に`State`がcloneされるため、`State`は[`Clone`]を実装する必要があります。
@@ -119,6 +387,57 @@ where
     }
 }
 
+/// [`Observer`]にイベントを渡してから内部の`Service`を呼び出す[`Service`]です。
+///
+/// [`Service`]: tower::Service
+/// [`Observer`]: crate::cache::Observer
+#[must_use]
+#[derive(Debug, Clone)]
+pub struct Observe<Observer, Service> {
+    observer: Observer,
+    service: Service,
+}
+
+impl<O, Srv> Service<Event> for Observe<O, Srv>
+where
+    O: Observer,
+    Srv: Service<Event>,
+{
+    type Response = Srv::Response;
+    type Error = Srv::Error;
+    type Future = Srv::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    #[inline]
+    fn call(&mut self, request: Event) -> Self::Future {
+        self.observer.observe(&request);
+        self.service.call(request)
+    }
+}
+
+impl<State, O, Srv> Service<(State, Event)> for Observe<O, Srv>
+where
+    O: Observer,
+    Srv: Service<(State, Event)>,
+{
+    type Response = Srv::Response;
+    type Error = Srv::Error;
+    type Future = Srv::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    #[inline]
+    fn call(&mut self, (state, request): (State, Event)) -> Self::Future {
+        self.observer.observe(&request);
+        self.service.call((state, request))
+    }
+}
+
 macro_rules! all_event_service {
     (
         $( $e:ident ),*
@@ -139,22 +458,169 @@ macro_rules! all_event_service {
 
 all_events! {all_event_service}
 
-impl<Service> Handler<Service> {
-    // TODO: これpubにしたい
-    /// 新しくイベントハンドラを作成します。`service`は以下の条件を満たす必要があります。
+impl<Srv, Req> Handler<OnEvent<Srv, Req>>
+where
+    Srv: Service<Req>,
+{
+    /// `fallback`を受け皿とする新しいイベントハンドラを作成します。
     ///
-    /// - <code>[Service]<[Event]></code>, [`Clone`]を実装している
-    /// - `Service::Response`が`()`と等しい
-    /// - `Service::Error`が<code>Into<Box<dyn [Error] + [Send] + [Sync] + &#39;static>></code>を実装している
+    /// `fallback`は[`Handler::on_event`]と同様に、他の`Service`にhandleされなかった[`Event`]を受け取ります。
+    /// 条件は[`Handler::on_event`]を参照してください。
     ///
-    /// [Service]: tower::Service
-    /// [Event]: crate::Event
+    /// # Example
+    ///
+    /// ```
+    /// use std::convert::Infallible;
+    ///
+    /// use tower::service_fn;
+    /// use traq_bot_http::{Event, Handler, RequestParser};
+    ///
+    /// async fn unhandled(event: Event) -> Result<(), Infallible> {
+    ///     println!("unhandled: {}", event.kind());
+    ///     Ok(())
+    /// }
+    ///
+    /// let parser = RequestParser::new("verification_token");
+    /// let handler = Handler::new(parser, service_fn(unhandled));
+    /// # let _ = handler;
+    /// ```
+    ///
+    /// [`Event`]: crate::Event
+    pub fn new(parser: crate::RequestParser, fallback: Srv) -> Self {
+        Self {
+            service: OnEvent::new(fallback),
+            parser,
+            _res_body: PhantomData,
+        }
+    }
+}
+
+impl<ResBody> Handler<Sink, ResBody> {
+    /// 他の`Service`にhandleされなかった[`Event`]をhandleする[`Service`]を登録します。
+    ///
+    /// 既定の受け皿である[`Sink`]を置き換えるため、`on_*`などの他のメソッドより先に呼び出す必要があります。
+    /// 引数の型`Service2`は`Service<Req>` traitを実装し、さらに以下の条件を満たす必要があります。
+    ///
+    /// - [`Clone`]を実装している
+    /// - `Req`が次のうちいずれかと等しい
+    ///   - [`Event`]
+    ///   - `(State, Event)` (`State`に関しては[`Handler::with_state`]を参照してください)
+    /// - `Service2::Response`が`()`と等しい
+    /// - `Service2::Error`が<code>Into<Box<dyn [Error] + [Send] + [Sync] + &#39;static>></code>を実装している
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::convert::Infallible;
+    ///
+    /// use tower::service_fn;
+    /// use traq_bot_http::{payloads, Event, RequestParser};
+    ///
+    /// async fn on_ping(payload: payloads::PingPayload) -> Result<(), Infallible> {
+    ///     println!("ping: {payload:?}");
+    ///     Ok(())
+    /// }
+    ///
+    /// async fn on_event(event: Event) -> Result<(), Infallible> {
+    ///     // PING以外のイベントを受け取る
+    ///     println!("unhandled: {}", event.kind());
+    ///     Ok(())
+    /// }
+    ///
+    /// let parser = RequestParser::new("verification_token");
+    /// let handler = parser
+    ///     .into_handler()
+    ///     .on_event(service_fn(on_event))
+    ///     .on_ping(service_fn(on_ping));
+    /// # let _ = handler;
+    /// ```
+    ///
+    /// [`Event`]: crate::Event
+    /// [`Service`]: tower::Service
     /// [`Clone`]: std::clone::Clone
     /// [Error]: std::error::Error
     /// [Send]: std::marker::Send
     /// [Sync]: std::marker::Sync
-    fn new(parser: crate::RequestParser, service: Service) -> Self {
-        Self { service, parser }
+    pub fn on_event<Service2, Req>(
+        self,
+        service: Service2,
+    ) -> Handler<OnEvent<Service2, Req>, ResBody>
+    where
+        Service2: Service<Req>,
+    {
+        self.map_service(|_| OnEvent::new(service))
+    }
+}
+
+impl<Service, ResBody> Handler<Service, ResBody> {
+    fn map_service<Service2>(
+        self,
+        f: impl FnOnce(Service) -> Service2,
+    ) -> Handler<Service2, ResBody> {
+        let Self {
+            service, parser, ..
+        } = self;
+        Handler {
+            service: f(service),
+            parser,
+            _res_body: PhantomData,
+        }
+    }
+
+    /// `kinds`に含まれる種類の[`Event`]をhandleする[`Service`]を登録します。
+    ///
+    /// 引数の型`Service2`は`Service<Req>` traitを実装し、さらに以下の条件を満たす必要があります。
+    ///
+    /// - [`Clone`]を実装している
+    /// - `Req`が次のうちいずれかと等しい
+    ///   - [`Event`]
+    ///   - `(State, Event)` (`State`に関しては[`Handler::with_state`]を参照してください)
+    /// - `Service2::Response`が`()`と等しい
+    /// - `Service2::Error`が<code>Into<Box<dyn [Error] + [Send] + [Sync] + &#39;static>></code>を実装している
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::convert::Infallible;
+    ///
+    /// use tower::service_fn;
+    /// use traq_bot_http::{Event, EventKind, EventKindSet, RequestParser};
+    ///
+    /// async fn audit(event: Event) -> Result<(), Infallible> {
+    ///     println!("{}: {event:?}", event.kind());
+    ///     Ok(())
+    /// }
+    ///
+    /// let kinds = EventKindSet::from([
+    ///     EventKind::UserGroupCreated,
+    ///     EventKind::UserGroupUpdated,
+    ///     EventKind::UserGroupDeleted,
+    /// ]);
+    /// let parser = RequestParser::new("verification_token");
+    /// let handler = parser.into_handler().on_kinds(kinds, service_fn(audit));
+    /// # let _ = handler;
+    /// ```
+    ///
+    /// [`Event`]: crate::Event
+    /// [`Service`]: tower::Service
+    /// [`Clone`]: std::clone::Clone
+    /// [Error]: std::error::Error
+    /// [Send]: std::marker::Send
+    /// [Sync]: std::marker::Sync
+    pub fn on_kinds<Service2, Req>(
+        self,
+        kinds: EventKindSet,
+        service: Service2,
+    ) -> Handler<OnKinds<Service2, Service, Req>, ResBody>
+    where
+        Service2: tower_service::Service<Req>,
+    {
+        self.map_service(|fallback| OnKinds {
+            _req: PhantomData,
+            kinds,
+            inner: service,
+            fallback,
+        })
     }
 
     /// イベントハンドラに`State`を追加します。`State`は以下の条件を満たす必要があります。
@@ -186,13 +652,399 @@ impl<Service> Handler<Service> {
     ///
     /// [`Clone`]: std::clone::Clone
     // TODO: State: Clone
-    pub fn with_state<State>(self, state: State) -> Handler<WithState<State, Service>> {
-        let Self { service, parser } = self;
+    pub fn with_state<State>(self, state: State) -> Handler<WithState<State, Service>, ResBody> {
+        self.map_service(|service| WithState::new(state, service))
+    }
+
+    /// 受け取ったイベントを、それまでに登録した`Service`より先に`observer`へ渡します。
+    ///
+    /// [`cache`]のコンポーネントを登録すると、イベントから自動で更新されます。
+    /// `observer`はイベントハンドラと共にcloneされるため、状態を共有するには[`Arc`]で包んでください。
+    ///
+    /// `observe`より後に登録した`on_*`の`Service`が受け取るイベントは`observer`に渡されないため、
+    /// `on_*`などより後に呼び出す必要があります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::convert::Infallible;
+    /// use std::sync::Arc;
+    ///
+    /// use tower::service_fn;
+    /// use traq_bot_http::cache::Directory;
+    /// use traq_bot_http::{payloads, RequestParser};
+    ///
+    /// async fn on_user_created(payload: payloads::UserCreatedPayload) -> Result<(), Infallible> {
+    ///     println!("{}", payload.user.name);
+    ///     Ok(())
+    /// }
+    ///
+    /// let directory = Arc::new(Directory::new());
+    /// let parser = RequestParser::new("verification_token");
+    /// let handler = parser
+    ///     .into_handler()
+    ///     // これはinvalidです; observeはon_*より後に置く必要があります
+    ///     // .observe(Arc::clone(&directory))
+    ///     .on_user_created(service_fn(on_user_created))
+    ///     .observe(Arc::clone(&directory));
+    /// # let _ = handler;
+    /// ```
+    ///
+    /// [`cache`]: crate::cache
+    /// [`Arc`]: std::sync::Arc
+    pub fn observe<O>(self, observer: O) -> Handler<Observe<O, Service>, ResBody>
+    where
+        O: Observer,
+    {
+        self.map_service(|service| Observe { observer, service })
+    }
+
+    /// イベントの処理を`spawner`でバックグラウンドに回し、パースと検証が済み次第`204 No Content`を返すようにします。
+    ///
+    /// バックグラウンドで同時に処理するイベントは最大`max_in_flight`件 (0の場合は1件) です。
+    /// 上限に達している間は[`Service::poll_ready`]が`Pending`を返し、新しいリクエストを待たせます。
+    /// 枠は[`Service::poll_ready`]の時点で確保されるため、リクエストボディのパースやトークンの検証より前に消費されます。
+    /// パースや検証に失敗したリクエストの枠は、そのリクエストの`Future`がdropされた時点で解放されます。
+    /// イベントの処理で発生したエラーはレスポンスに反映されないため、
+    /// [`Handler::on_background_error`]でコールバックを登録してください。
+    ///
+    /// `on_*`, [`Handler::with_state`]などの他のメソッドより後に呼び出す必要があります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::convert::Infallible;
+    /// use std::future::Future;
+    ///
+    /// use tower::service_fn;
+    /// use traq_bot_http::handler::Spawn;
+    /// use traq_bot_http::{payloads, RequestParser};
+    ///
+    /// #[derive(Clone)]
+    /// struct ThreadSpawn;
+    ///
+    /// impl Spawn for ThreadSpawn {
+    ///     fn spawn<F>(&self, future: F)
+    ///     where
+    ///         F: Future<Output = ()> + Send + 'static,
+    ///     {
+    ///         std::thread::spawn(move || futures::executor::block_on(future));
+    ///     }
+    /// }
+    ///
+    /// async fn on_ping(payload: payloads::PingPayload) -> Result<(), Infallible> {
+    ///     println!("{payload:?}");
+    ///     Ok(())
+    /// }
+    ///
+    /// let parser = RequestParser::new("verification_token");
+    /// let handler = parser
+    ///     .into_handler()
+    ///     .on_ping(service_fn(on_ping))
+    ///     .background(ThreadSpawn, 16)
+    ///     .on_background_error(|e| eprintln!("{e}"));
+    /// # let _ = handler;
+    /// ```
+    ///
+    /// [`Service::poll_ready`]: tower::Service::poll_ready
+    pub fn background<Sp>(
+        self,
+        spawner: Sp,
+        max_in_flight: usize,
+    ) -> Handler<Background<Service, Sp>, ResBody>
+    where
+        Sp: Spawn,
+    {
+        self.map_service(|service| Background::new(service, spawner, max_in_flight))
+    }
+
+    /// `shutdown`でシャットダウンできるようにします。
+    ///
+    /// [`Shutdown::shutdown`]が呼ばれると、新しいリクエストには`503 Service Unavailable`を返し、
+    /// 処理中のイベントの完了を待つようになります。
+    /// 全てのリクエストを対象にするため、他のメソッドより後に呼び出す必要があります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::convert::Infallible;
+    /// use std::future::Future;
+    ///
+    /// use tower::service_fn;
+    /// use traq_bot_http::handler::{Shutdown, Spawn};
+    /// use traq_bot_http::{payloads, RequestParser};
+    ///
+    /// #[derive(Clone)]
+    /// struct ThreadSpawn;
+    ///
+    /// impl Spawn for ThreadSpawn {
+    ///     fn spawn<F>(&self, future: F)
+    ///     where
+    ///         F: Future<Output = ()> + Send + 'static,
+    ///     {
+    ///         std::thread::spawn(move || futures::executor::block_on(future));
+    ///     }
+    /// }
+    ///
+    /// async fn on_ping(payload: payloads::PingPayload) -> Result<(), Infallible> {
+    ///     println!("{payload:?}");
+    ///     Ok(())
+    /// }
+    ///
+    /// let shutdown = Shutdown::new();
+    /// let parser = RequestParser::new("verification_token");
+    /// let handler = parser
+    ///     .into_handler()
+    ///     .on_ping(service_fn(on_ping))
+    ///     // バックグラウンドの処理も待機の対象にする
+    ///     .background(shutdown.track_spawn(ThreadSpawn), 16)
+    ///     .graceful(&shutdown);
+    /// # let _ = handler;
+    /// ```
+    pub fn graceful(self, shutdown: &Shutdown) -> Handler<Graceful<Service>, ResBody> {
+        self.map_service(|service| Graceful::new(shutdown.clone(), service))
+    }
+
+    /// それまでに登録した`Service`全体に`layer`を適用します。
+    ///
+    /// `layer`を適用した`Service`は以下の条件を満たす必要があります。
+    ///
+    /// - `Response`が`()`と等しい
+    /// - `Error`が<code>Into<Box<dyn [Error] + [Send] + [Sync] + &#39;static>></code>を実装している
+    ///
+    /// エラーが[`crate::Error`]であればそのまま、そうでなければ[`ErrorKind::Handler`]に変換されます。
+    /// そのため[`Handler::graceful`]の後に呼び出しても、シャットダウン中は`503 Service Unavailable`を返します。
+    ///
+    /// `layer`より後に登録した`on_*`などの`Service`には適用されません。
+    /// 全てのイベントに適用するには`on_*`より後に呼び出してください。
+    /// [`Handler::graceful`]などと併用する場合は、後に呼び出したものほど外側で処理されます。
+    /// 特定の`on_*`で登録した`Service`にのみ適用するには[`Handler::layer_last`]を使用してください。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::convert::Infallible;
+    ///
+    /// use tower::service_fn;
+    /// use tower::util::MapRequestLayer;
+    /// use traq_bot_http::{payloads, Event, RequestParser};
+    ///
+    /// async fn on_ping(payload: payloads::PingPayload) -> Result<(), Infallible> {
+    ///     println!("{payload:?}");
+    ///     Ok(())
+    /// }
+    ///
+    /// let parser = RequestParser::new("verification_token");
+    /// let handler = parser
+    ///     .into_handler()
+    ///     .on_ping(service_fn(on_ping))
+    ///     // on_pingの前に実行される
+    ///     .layer(MapRequestLayer::new(|event: Event| {
+    ///         println!("received: {}", event.kind());
+    ///         event
+    ///     }));
+    /// # let _ = handler;
+    /// ```
+    ///
+    /// [Error]: std::error::Error
+    /// [Send]: std::marker::Send
+    /// [Sync]: std::marker::Sync
+    /// [`ErrorKind::Handler`]: crate::ErrorKind::Handler
+    pub fn layer<L>(self, layer: L) -> Handler<Layered<L::Service>, ResBody>
+    where
+        L: Layer<Service>,
+    {
+        self.map_service(|service| Layered {
+            inner: layer.layer(service),
+        })
+    }
+
+    /// 直前の`on_*`, [`Handler::on_kinds`], [`Handler::on_event`]で登録した`Service`に`layer`を適用します。
+    ///
+    /// `layer`を適用した`Service`は各メソッドの`Service2`と同じ条件を満たす必要があります。
+    /// エラーは[`crate::Error`]に変換されます。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::convert::Infallible;
+    ///
+    /// use tower::service_fn;
+    /// use tower::util::MapRequestLayer;
+    /// use traq_bot_http::{payloads, RequestParser};
+    ///
+    /// async fn on_message_created(
+    ///     payload: payloads::MessageCreatedPayload,
+    /// ) -> Result<(), Infallible> {
+    ///     println!("{}", payload.message.text);
+    ///     Ok(())
+    /// }
+    ///
+    /// let parser = RequestParser::new("verification_token");
+    /// let handler = parser
+    ///     .into_handler()
+    ///     .on_message_created(service_fn(on_message_created))
+    ///     // on_message_createdで登録したServiceにのみ適用される
+    ///     .layer_last(MapRequestLayer::new(
+    ///         |payload: payloads::MessageCreatedPayload| {
+    ///             println!("from: {}", payload.message.user.name);
+    ///             payload
+    ///         },
+    ///     ));
+    /// # let _ = handler;
+    /// ```
+    pub fn layer_last<L>(self, layer: L) -> Handler<Service::Output, ResBody>
+    where
+        Service: LayerLast<L>,
+    {
+        self.map_service(|service| service.layer_last(layer))
+    }
+
+    /// レスポンスボディの型を`B`にします。既定では[`String`]です。
+    ///
+    /// レスポンスボディは常に空で、`B::default()`で作成されます。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use traq_bot_http::RequestParser;
+    ///
+    /// let parser = RequestParser::new("verification_token");
+    /// let handler = parser.into_handler().with_response_body::<Vec<u8>>();
+    /// # let _ = handler;
+    /// ```
+    ///
+    /// [`String`]: std::string::String
+    pub fn with_response_body<B>(self) -> Handler<Service, B>
+    where
+        B: Default,
+    {
+        let Self {
+            service, parser, ..
+        } = self;
         Handler {
-            service: WithState::new(state, service),
+            service,
             parser,
+            _res_body: PhantomData,
         }
     }
+
+    /// 構成した[`Service`]を取り出します。
+    ///
+    /// 取り出した`Service`は<code>[Service]<[Event]></code>を実装し、
+    /// HTTP 以外の経路で受け取ったイベントやテストで作成したイベントをhandleできます。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::convert::Infallible;
+    ///
+    /// use tower::{service_fn, ServiceExt};
+    /// use traq_bot_http::{payloads, Event, RequestParser};
+    ///
+    /// async fn on_ping(payload: payloads::PingPayload) -> Result<(), Infallible> {
+    ///     println!("{payload:?}");
+    ///     Ok(())
+    /// }
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let parser = RequestParser::new("verification_token");
+    /// let service = parser
+    ///     .into_handler()
+    ///     .on_ping(service_fn(on_ping))
+    ///     .into_event_service();
+    /// let payload: payloads::PingPayload =
+    ///     r#"{"eventTime": "2019-05-07T04:50:48.582586882Z"}"#.parse()?;
+    /// futures::executor::block_on(service.oneshot(Event::Ping(payload)))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`Service`]: tower::Service
+    /// [Service]: tower::Service
+    /// [Event]: crate::Event
+    pub fn into_event_service(self) -> Service {
+        self.service
+    }
+
+    /// `event`を構成した[`Service`]でhandleします。
+    ///
+    /// HTTP リクエストのパースと検証を経ずに、[`Service`]の呼び出しだけを行います。
+    ///
+    /// # Errors
+    ///
+    /// 構成した[`Service`]がエラーを返した場合、そのエラーを返します。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::convert::Infallible;
+    ///
+    /// use tower::service_fn;
+    /// use traq_bot_http::{payloads, Event, RequestParser};
+    ///
+    /// async fn on_ping(payload: payloads::PingPayload) -> Result<(), Infallible> {
+    ///     println!("{payload:?}");
+    ///     Ok(())
+    /// }
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let parser = RequestParser::new("verification_token");
+    /// let handler = parser.into_handler().on_ping(service_fn(on_ping));
+    /// let payload: payloads::PingPayload =
+    ///     r#"{"eventTime": "2019-05-07T04:50:48.582586882Z"}"#.parse()?;
+    /// futures::executor::block_on(handler.dispatch(Event::Ping(payload)))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`Service`]: tower::Service
+    pub async fn dispatch(&self, event: Event) -> crate::Result<()>
+    where
+        Service: tower_service::Service<Event, Response = (), Error = Error> + Clone,
+    {
+        let mut service = self.service.clone();
+        std::future::poll_fn(|cx| service.poll_ready(cx)).await?;
+        service.call(event).await
+    }
+}
+
+impl<Service, ResBody> Clone for Handler<Service, ResBody>
+where
+    Service: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            service: self.service.clone(),
+            parser: self.parser.clone(),
+            _res_body: PhantomData,
+        }
+    }
+}
+
+impl<Service, ResBody> std::fmt::Debug for Handler<Service, ResBody>
+where
+    Service: std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Handler")
+            .field("service", &self.service)
+            .field("parser", &self.parser)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<Srv, Sp, ResBody> Handler<Background<Srv, Sp>, ResBody> {
+    /// バックグラウンドでのイベントの処理で発生したエラーを受け取るコールバックを登録します。
+    ///
+    /// 登録しない場合、エラーは捨てられます。
+    pub fn on_background_error<F>(mut self, f: F) -> Self
+    where
+        F: Fn(Error) + Send + Sync + 'static,
+    {
+        self.service.set_on_error(Arc::new(f));
+        self
+    }
 }
 
 impl RequestParser {
@@ -200,7 +1052,11 @@ impl RequestParser {
     ///
     /// **Note**: この関数は`tower`featureが有効になっている時のみ提供されます。
     pub fn into_handler(self) -> Handler<Sink> {
-        Handler::new(self, Sink::new())
+        Handler {
+            service: Sink::new(),
+            parser: self,
+            _res_body: PhantomData,
+        }
     }
 }
 
@@ -235,19 +1091,20 @@ macro_rules! all_handler_on_events {
 
 all_events! {all_handler_on_events}
 
-impl<Srv, Body> Service<Request<Body>> for Handler<Srv>
+impl<Srv, Body, ResBody> Service<Request<Body>> for Handler<Srv, ResBody>
 where
     Srv: Service<Event, Response = (), Error = Error>,
     Srv: Clone,
     Body: http_body::Body,
     Body::Error: Into<Box<dyn std::error::Error + Send + Sync + 'static>>,
+    ResBody: Default,
 {
-    type Response = Response<String>;
+    type Response = Response<ResBody>;
     type Error = Error;
-    type Future = HandlerCall<Body, Srv>;
+    type Future = HandlerCall<Body, Srv, ResBody>;
 
     fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
-        self.service.poll_ready(cx).map_err(Error::handler)
+        self.service.poll_ready(cx)
     }
 
     fn call(&mut self, req: Request<Body>) -> Self::Future {
@@ -258,3 +1115,192 @@ where
         HandlerCall::new(parse_request, s)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::convert::Infallible;
+    use std::sync::Mutex;
+
+    use futures::executor::block_on;
+    use futures::future::{pending, ready, Ready};
+    use http::StatusCode;
+    use tower::util::{MapRequestLayer, ServiceFn};
+    use tower::{service_fn, ServiceExt};
+
+    use crate::cache::Directory;
+    use crate::payloads::{JoinedPayload, LeftPayload, PingPayload, UserCreatedPayload};
+    use crate::test_utils::{make_headers, make_parser, testdata};
+    use crate::{ErrorKind, EventKind};
+
+    const PING: &str = r#"{"eventTime": "2019-05-07T04:50:48.582586882Z"}"#;
+
+    type Log = Arc<Mutex<Vec<(&'static str, EventKind)>>>;
+
+    fn ping() -> Event {
+        PING.parse::<PingPayload>().unwrap().into()
+    }
+
+    fn joined() -> Event {
+        testdata::<JoinedPayload>("system/joined.json").into()
+    }
+
+    fn left() -> Event {
+        testdata::<LeftPayload>("system/left.json").into()
+    }
+
+    /// 受け取ったイベントの種類を`name`と共に`log`へ記録する
+    fn record<T>(
+        log: &Log,
+        name: &'static str,
+    ) -> ServiceFn<impl FnMut(T) -> Ready<Result<(), Infallible>> + Clone>
+    where
+        T: Into<Event>,
+    {
+        let log = Arc::clone(log);
+        service_fn(move |request: T| {
+            log.lock().unwrap().push((name, request.into().kind()));
+            ready(Ok(()))
+        })
+    }
+
+    #[test]
+    fn on_event_receives_unhandled() {
+        let log = Log::default();
+        let handler = make_parser()
+            .into_handler()
+            .on_event(record::<Event>(&log, "event"))
+            .on_ping(record::<PingPayload>(&log, "ping"));
+        for event in [ping(), joined()] {
+            block_on(handler.dispatch(event)).unwrap();
+        }
+        assert_eq!(
+            *log.lock().unwrap(),
+            [("ping", EventKind::Ping), ("event", EventKind::Joined)]
+        );
+    }
+
+    #[test]
+    fn new_with_fallback() {
+        let log = Log::default();
+        let handler = Handler::new(make_parser(), record::<Event>(&log, "fallback"))
+            .on_ping(record::<PingPayload>(&log, "ping"));
+        for event in [ping(), joined()] {
+            block_on(handler.dispatch(event)).unwrap();
+        }
+        assert_eq!(
+            *log.lock().unwrap(),
+            [("ping", EventKind::Ping), ("fallback", EventKind::Joined)]
+        );
+    }
+
+    #[test]
+    fn on_kinds_routes_set() {
+        let log = Log::default();
+        let kinds = EventKindSet::from([EventKind::Joined, EventKind::Left]);
+        let handler = make_parser()
+            .into_handler()
+            .on_event(record::<Event>(&log, "event"))
+            .on_kinds(kinds, record::<Event>(&log, "kinds"));
+        for event in [ping(), joined(), left()] {
+            block_on(handler.dispatch(event)).unwrap();
+        }
+        assert_eq!(
+            *log.lock().unwrap(),
+            [
+                ("event", EventKind::Ping),
+                ("kinds", EventKind::Joined),
+                ("kinds", EventKind::Left)
+            ]
+        );
+    }
+
+    #[test]
+    fn layer_last_wraps_preceding() {
+        let log = Log::default();
+        let layer = MapRequestLayer::new({
+            let log = Arc::clone(&log);
+            move |payload: JoinedPayload| {
+                log.lock().unwrap().push(("layer", EventKind::Joined));
+                payload
+            }
+        });
+        let handler = make_parser()
+            .into_handler()
+            .on_joined(record::<JoinedPayload>(&log, "joined"))
+            .layer_last(layer)
+            .on_ping(record::<PingPayload>(&log, "ping"));
+        for event in [ping(), joined()] {
+            block_on(handler.dispatch(event)).unwrap();
+        }
+        assert_eq!(
+            *log.lock().unwrap(),
+            [
+                ("ping", EventKind::Ping),
+                ("layer", EventKind::Joined),
+                ("joined", EventKind::Joined)
+            ]
+        );
+    }
+
+    #[test]
+    fn observe_after_on_events() {
+        let directory = Arc::new(Directory::new());
+        let handler = make_parser()
+            .into_handler()
+            .on_user_created(service_fn(|_: UserCreatedPayload| async {
+                Ok::<(), Infallible>(())
+            }))
+            .observe(Arc::clone(&directory));
+        let payload: UserCreatedPayload = testdata("user/user_created.json");
+        block_on(handler.dispatch(payload.clone().into())).unwrap();
+        assert_eq!(directory.user(&payload.user.id), Some(payload.user));
+    }
+
+    #[test]
+    fn background_releases_rejected() {
+        #[derive(Clone)]
+        struct Unreachable;
+
+        impl Spawn for Unreachable {
+            fn spawn<F>(&self, _future: F)
+            where
+                F: std::future::Future<Output = ()> + Send + 'static,
+            {
+                unreachable!("rejected requests must not be spawned");
+            }
+        }
+
+        let handler = make_parser().into_handler().background(Unreachable, 1);
+        let mut request = Request::new(PING.to_string());
+        *request.headers_mut() = make_headers("PING");
+        request
+            .headers_mut()
+            .insert("X-TRAQ-BOT-TOKEN", "invalid".parse().unwrap());
+        let error = block_on(handler.clone().oneshot(request)).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::BotTokenMismatch);
+        // 検証に失敗したリクエストの枠は解放される
+        assert_eq!(handler.service.in_flight(), 0);
+    }
+
+    #[test]
+    fn layer_after_graceful() {
+        let shutdown = Shutdown::new();
+        let handler = make_parser()
+            .into_handler()
+            .graceful(&shutdown)
+            .layer(MapRequestLayer::new(|event: Event| event));
+        let report = block_on(shutdown.shutdown(pending::<()>()));
+        assert_eq!(report.abandoned, 0);
+
+        // `Layered`を通ってもエラーの種類は変わらない
+        let error = block_on(handler.dispatch(ping())).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::ShuttingDown);
+
+        let mut request = Request::new(PING.to_string());
+        *request.headers_mut() = make_headers("PING");
+        let response = block_on(handler.oneshot(request)).unwrap();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+}
@@ -0,0 +1,363 @@
+//! イベントをバックグラウンドで処理する[`Background`]
+
+use std::fmt;
+use std::future::Future;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::task::{Context, Poll, Waker};
+
+use futures_core::ready;
+use tower_service::Service;
+
+use crate::{Error, Event};
+
+/// [`Future`]をバックグラウンドで実行するもの
+///
+/// [`Handler::background`]で使用されます。
+/// `tokio` featureでは[`tokio`]の実装として[`TokioSpawn`]が提供されます。
+///
+/// [`Future`]: std::future::Future
+/// [`Handler::background`]: crate::Handler::background
+/// [`tokio`]: https://docs.rs/tokio
+/// [`TokioSpawn`]: crate::handler::TokioSpawn
+pub trait Spawn {
+    /// `future`をバックグラウンドで実行します。
+    fn spawn<F>(&self, future: F)
+    where
+        F: Future<Output = ()> + Send + 'static;
+}
+
+impl<S> Spawn for Arc<S>
+where
+    S: Spawn + ?Sized,
+{
+    fn spawn<F>(&self, future: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        S::spawn(self, future);
+    }
+}
+
+impl<S> Spawn for &S
+where
+    S: Spawn + ?Sized,
+{
+    fn spawn<F>(&self, future: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        S::spawn(self, future);
+    }
+}
+
+/// [`tokio::spawn`]で[`Future`]を実行する[`Spawn`]です。
+///
+/// [`tokio::spawn`]: https://docs.rs/tokio/latest/tokio/task/fn.spawn.html
+/// [`Future`]: std::future::Future
+#[cfg(feature = "tokio")]
+#[must_use]
+#[derive(Debug, Clone, Default)]
+pub struct TokioSpawn {
+    handle: Option<tokio::runtime::Handle>,
+}
+
+#[cfg(feature = "tokio")]
+impl TokioSpawn {
+    /// 実行中のランタイムで[`Future`]を実行する`TokioSpawn`を作成します。
+    ///
+    /// [`Future`]: std::future::Future
+    pub fn new() -> Self {
+        Self { handle: None }
+    }
+
+    /// `handle`のランタイムで[`Future`]を実行する`TokioSpawn`を作成します。
+    ///
+    /// [`Future`]: std::future::Future
+    pub fn with_handle(handle: tokio::runtime::Handle) -> Self {
+        Self {
+            handle: Some(handle),
+        }
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl Spawn for TokioSpawn {
+    fn spawn<F>(&self, future: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        match &self.handle {
+            Some(handle) => drop(handle.spawn(future)),
+            None => drop(tokio::spawn(future)),
+        }
+    }
+}
+
+/// 同時に処理できるイベントの数の上限
+#[derive(Debug)]
+struct Limit {
+    max: usize,
+    state: Mutex<LimitState>,
+}
+
+#[derive(Debug, Default)]
+struct LimitState {
+    in_flight: usize,
+    waiters: Vec<Waker>,
+}
+
+impl Limit {
+    fn new(max: usize) -> Self {
+        Self {
+            max: max.max(1),
+            state: Mutex::default(),
+        }
+    }
+
+    // 処理中にpanicしても数は壊れないため、poisonは無視する
+    fn lock(&self) -> MutexGuard<'_, LimitState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn poll_acquire(self: &Arc<Self>, cx: &mut Context<'_>) -> Poll<Permit> {
+        let mut state = self.lock();
+        if state.in_flight < self.max {
+            state.in_flight += 1;
+            return Poll::Ready(Permit(Arc::clone(self)));
+        }
+        if !state.waiters.iter().any(|w| w.will_wake(cx.waker())) {
+            state.waiters.push(cx.waker().clone());
+        }
+        Poll::Pending
+    }
+
+    fn in_flight(&self) -> usize {
+        self.lock().in_flight
+    }
+}
+
+/// 処理中のイベント1つ分の枠。dropされると枠を空けて待機中のタスクを起こす
+#[derive(Debug)]
+struct Permit(Arc<Limit>);
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        let waiters = {
+            let mut state = self.0.lock();
+            state.in_flight -= 1;
+            std::mem::take(&mut state.waiters)
+        };
+        for waker in waiters {
+            waker.wake();
+        }
+    }
+}
+
+type ErrorCallback = Arc<dyn Fn(Error) + Send + Sync>;
+
+/// 内部の`Service`の処理を[`Spawn`]でバックグラウンドに回し、すぐに完了する[`Service`]です。
+///
+/// 処理中のイベントの数が上限に達している間は`poll_ready`が`Pending`を返します。
+/// 枠は`poll_ready`で確保して`call`で使うため、[`Handler`]ではパースや検証より前に確保されます。
+/// `call`されなかった枠は、この`Service`がdropされた時点で解放されます。
+/// 内部の`Service`のエラーは[`Handler::on_background_error`]で登録したコールバックに渡されます。
+///
+/// [`Service`]: tower::Service
+/// [`Handler`]: crate::Handler
+/// [`Handler::on_background_error`]: crate::Handler::on_background_error
+#[must_use]
+pub struct Background<Service, Spawner> {
+    inner: Service,
+    spawner: Spawner,
+    limit: Arc<Limit>,
+    permit: Option<Permit>,
+    on_error: Option<ErrorCallback>,
+}
+
+impl<Srv, Sp> Background<Srv, Sp> {
+    pub(crate) fn new(inner: Srv, spawner: Sp, max_in_flight: usize) -> Self {
+        Self {
+            inner,
+            spawner,
+            limit: Arc::new(Limit::new(max_in_flight)),
+            permit: None,
+            on_error: None,
+        }
+    }
+
+    pub(crate) fn set_on_error(&mut self, on_error: ErrorCallback) {
+        self.on_error = Some(on_error);
+    }
+
+    /// バックグラウンドで処理中のイベントの数を返します。
+    ///
+    /// 複製された`Background`の間で共有されます。
+    #[must_use]
+    pub fn in_flight(&self) -> usize {
+        self.limit.in_flight()
+    }
+}
+
+impl<Srv, Sp> Clone for Background<Srv, Sp>
+where
+    Srv: Clone,
+    Sp: Clone,
+{
+    fn clone(&self) -> Self {
+        // 確保した枠は複製しない
+        Self {
+            inner: self.inner.clone(),
+            spawner: self.spawner.clone(),
+            limit: Arc::clone(&self.limit),
+            permit: None,
+            on_error: self.on_error.clone(),
+        }
+    }
+}
+
+impl<Srv, Sp> fmt::Debug for Background<Srv, Sp>
+where
+    Srv: fmt::Debug,
+    Sp: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Background")
+            .field("inner", &self.inner)
+            .field("spawner", &self.spawner)
+            .field("max_in_flight", &self.limit.max)
+            .field("ready", &self.permit.is_some())
+            .field("on_error", &self.on_error.as_ref().map(|_| ".."))
+            .finish()
+    }
+}
+
+impl<Srv, Sp> Service<Event> for Background<Srv, Sp>
+where
+    Srv: Service<Event, Response = (), Error = Error>,
+    Srv::Future: Send + 'static,
+    Sp: Spawn,
+{
+    type Response = ();
+    type Error = Error;
+    type Future = futures_util::future::Ready<Result<(), Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        if self.permit.is_none() {
+            self.permit = Some(ready!(self.limit.poll_acquire(cx)));
+        }
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Event) -> Self::Future {
+        let permit = self
+            .permit
+            .take()
+            .expect("`poll_ready` must be called before `call`");
+        let call = self.inner.call(request);
+        let on_error = self.on_error.clone();
+        self.spawner.spawn(async move {
+            let _permit = permit;
+            if let (Err(e), Some(on_error)) = (call.await, on_error) {
+                on_error(e);
+            }
+        });
+        futures_util::future::ready(Ok(()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use futures::executor::block_on;
+    use futures::future::{poll_fn, BoxFuture};
+    use futures::task::noop_waker_ref;
+    use tower::service_fn;
+
+    use crate::payloads::PingPayload;
+
+    #[derive(Clone, Default)]
+    struct Queue(Arc<Mutex<Vec<BoxFuture<'static, ()>>>>);
+
+    impl Queue {
+        fn run_all(&self) {
+            let tasks = std::mem::take(&mut *self.0.lock().unwrap());
+            for task in tasks {
+                block_on(task);
+            }
+        }
+    }
+
+    impl Spawn for Queue {
+        fn spawn<F>(&self, future: F)
+        where
+            F: Future<Output = ()> + Send + 'static,
+        {
+            self.0.lock().unwrap().push(Box::pin(future));
+        }
+    }
+
+    fn ping() -> Event {
+        let payload: PingPayload = r#"{"eventTime": "2019-05-07T04:50:48.582586882Z"}"#
+            .parse()
+            .unwrap();
+        payload.into()
+    }
+
+    fn poll_ready<S: Service<Event>>(service: &mut S) -> Poll<Result<(), S::Error>> {
+        let mut cx = Context::from_waker(noop_waker_ref());
+        service.poll_ready(&mut cx)
+    }
+
+    #[test]
+    fn bounded_in_flight() {
+        let queue = Queue::default();
+        let called = Arc::new(AtomicUsize::new(0));
+        let inner = service_fn({
+            let called = Arc::clone(&called);
+            move |_: Event| {
+                called.fetch_add(1, Ordering::SeqCst);
+                async { Ok::<(), Error>(()) }
+            }
+        });
+        let mut service = Background::new(inner, queue.clone(), 2);
+
+        for _ in 0..2 {
+            assert!(poll_ready(&mut service).is_ready());
+            let res = service.call(ping());
+            assert!(block_on(res).is_ok());
+        }
+        assert_eq!(service.in_flight(), 2);
+        assert!(poll_ready(&mut service).is_pending());
+        // 複製も枠を共有する
+        assert!(poll_ready(&mut service.clone()).is_pending());
+
+        queue.run_all();
+        assert_eq!(service.in_flight(), 0);
+        assert_eq!(called.load(Ordering::SeqCst), 2);
+        assert!(poll_ready(&mut service).is_ready());
+    }
+
+    #[test]
+    fn report_error() {
+        let queue = Queue::default();
+        let inner = service_fn(|_: Event| async {
+            Err::<(), _>(Error::handler(std::io::Error::other("failed")))
+        });
+        let errors = Arc::new(Mutex::new(Vec::new()));
+        let mut service = Background::new(inner, queue.clone(), 1);
+        service.set_on_error({
+            let errors = Arc::clone(&errors);
+            Arc::new(move |e: Error| errors.lock().unwrap().push(e.to_string()))
+        });
+
+        block_on(poll_fn(|cx| service.poll_ready(cx))).unwrap();
+        // 処理の完了を待たずに成功する
+        assert!(block_on(service.call(ping())).is_ok());
+        assert!(errors.lock().unwrap().is_empty());
+        queue.run_all();
+        assert_eq!(errors.lock().unwrap().len(), 1);
+    }
+}
@@ -9,7 +9,7 @@ use http_body::Body;
 use pin_project_lite::pin_project;
 use tower_service::Service;
 
-use crate::error::{Error, Result};
+use crate::error::{Error, ErrorKind, Result};
 use crate::events::Event;
 use crate::parser::ParseRequest;
 
@@ -60,7 +60,7 @@ where
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         let s = self.project();
         let res = ready!(s.inner.poll(cx));
-        Poll::Ready(res.map_err(Error::handler))
+        Poll::Ready(res.map_err(Error::from_handler))
     }
 }
 
@@ -99,26 +99,30 @@ where
 pin_project! {
     #[must_use]
     #[project = HandlerCallServiceCallProject]
-    struct HandlerCallServiceCall<F> {
+    struct HandlerCallServiceCall<F, R> {
+        _body: PhantomData<fn() -> R>,
         #[pin]
         inner: F,
     }
 }
 
-impl<F> Future for HandlerCallServiceCall<F>
+impl<F, R> Future for HandlerCallServiceCall<F, R>
 where
     F: Future<Output = Result<(), Error>>,
+    R: Default,
 {
-    type Output = Result<Response<String>>;
+    type Output = Result<Response<R>>;
 
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         let s = self.project();
-        if let Err(e) = ready!(s.inner.poll(cx)) {
-            return Poll::Ready(Err(e));
-        }
+        let status = match ready!(s.inner.poll(cx)) {
+            Ok(()) => StatusCode::NO_CONTENT,
+            Err(e) if e.kind() == ErrorKind::ShuttingDown => StatusCode::SERVICE_UNAVAILABLE,
+            Err(e) => return Poll::Ready(Err(e)),
+        };
         let res = Response::builder()
-            .status(StatusCode::NO_CONTENT)
-            .body(String::new())
+            .status(status)
+            .body(R::default())
             .map_err(Error::handler);
         Poll::Ready(res)
     }
@@ -128,7 +132,7 @@ pin_project! {
     #[must_use]
     #[project = HandlerCallInnerProject]
     #[project_replace = HandlerCallInnerProjectReplace]
-    enum HandlerCallInner<B, S>
+    enum HandlerCallInner<B, S, R>
     where
         B: Body,
         S: Service<Event>,
@@ -139,18 +143,19 @@ pin_project! {
         },
         ServiceCall {
             #[pin]
-            inner: HandlerCallServiceCall<S::Future>,
+            inner: HandlerCallServiceCall<S::Future, R>,
         },
     }
 }
 
-impl<B, S> Future for HandlerCallInner<B, S>
+impl<B, S, R> Future for HandlerCallInner<B, S, R>
 where
     B: Body,
     B::Error: Into<Box<dyn std::error::Error + Send + Sync + 'static>>,
     S: Service<Event, Response = (), Error = Error>,
+    R: Default,
 {
-    type Output = Result<Response<String>>;
+    type Output = Result<Response<R>>;
 
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         let s = self.as_mut().project();
@@ -163,6 +168,7 @@ where
         };
         self.project_replace(HandlerCallInner::ServiceCall {
             inner: HandlerCallServiceCall {
+                _body: PhantomData,
                 inner: service_call,
             },
         });
@@ -181,17 +187,17 @@ pin_project! {
     /// [Future]: crate::Handler::Future
     #[must_use]
     #[project = HandlerCallProject]
-    pub struct HandlerCall<B, S>
+    pub struct HandlerCall<B, S, R = String>
     where
         B: Body,
         S: Service<Event>,
     {
         #[pin]
-        inner: HandlerCallInner<B, S>,
+        inner: HandlerCallInner<B, S, R>,
     }
 }
 
-impl<B, S> HandlerCall<B, S>
+impl<B, S, R> HandlerCall<B, S, R>
 where
     B: Body,
     S: Service<Event>,
@@ -208,13 +214,14 @@ where
     }
 }
 
-impl<B, S> Future for HandlerCall<B, S>
+impl<B, S, R> Future for HandlerCall<B, S, R>
 where
     B: Body,
     B::Error: Into<Box<dyn std::error::Error + Send + Sync + 'static>>,
     S: Service<Event, Response = (), Error = Error>,
+    R: Default,
 {
-    type Output = Result<Response<String>>;
+    type Output = Result<Response<R>>;
 
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         let s = self.project();
@@ -0,0 +1,428 @@
+//! 処理中のイベントを待ってから止める[`Shutdown`]
+
+use std::future::Future;
+use std::pin::{pin, Pin};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::task::{Context, Poll, Waker};
+
+use futures_core::ready;
+use futures_util::future::Either;
+use pin_project_lite::pin_project;
+use tower_service::Service;
+
+use super::Spawn;
+use crate::{Error, ErrorKind};
+
+#[derive(Debug, Default)]
+struct State {
+    closed: bool,
+    in_flight: usize,
+    completed: usize,
+    dropped: usize,
+    waiters: Vec<Waker>,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    state: Mutex<State>,
+}
+
+impl Inner {
+    // 処理中にpanicしても数は壊れないため、poisonは無視する
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// 処理中のイベント1つ分。dropされると、[`Guard::complete`]を呼んでいれば完了、
+/// そうでなければ破棄として数える
+#[derive(Debug)]
+struct Guard {
+    inner: Arc<Inner>,
+    completed: bool,
+}
+
+impl Guard {
+    fn new(inner: &Arc<Inner>) -> Self {
+        inner.lock().in_flight += 1;
+        Self::entered(inner)
+    }
+
+    /// `in_flight`を数え終えた後に作成する
+    fn entered(inner: &Arc<Inner>) -> Self {
+        Self {
+            inner: Arc::clone(inner),
+            completed: false,
+        }
+    }
+
+    fn complete(&mut self) {
+        self.completed = true;
+    }
+}
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        let waiters = {
+            let mut state = self.inner.lock();
+            state.in_flight -= 1;
+            if self.completed {
+                state.completed += 1;
+            } else {
+                state.dropped += 1;
+            }
+            if state.in_flight > 0 {
+                return;
+            }
+            std::mem::take(&mut state.waiters)
+        };
+        for waker in waiters {
+            waker.wake();
+        }
+    }
+}
+
+/// [`Shutdown::shutdown`]の結果
+#[must_use]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ShutdownReport {
+    /// シャットダウンを始めてから完了したイベントの数
+    pub completed: usize,
+    /// 期限までに完了しなかったイベントの数
+    pub abandoned: usize,
+    /// シャットダウンを始めてから、完了する前に破棄されたイベントの数
+    pub dropped: usize,
+}
+
+/// イベントハンドラのシャットダウンを指示するハンドル
+///
+/// [`Handler::graceful`]で登録したイベントハンドラは、[`Shutdown::shutdown`]が呼ばれると
+/// 新しいリクエストに`503 Service Unavailable`を返すようになります。
+/// [`Handler::background`]と併用する場合は、[`Shutdown::track_spawn`]で包んだ[`Spawn`]を渡すと
+/// バックグラウンドの処理も待機の対象になります。
+///
+/// 複製したハンドルは状態を共有します。
+///
+/// # Example
+///
+/// ```
+/// use std::convert::Infallible;
+///
+/// use tower::service_fn;
+/// use traq_bot_http::handler::Shutdown;
+/// use traq_bot_http::{payloads, RequestParser};
+///
+/// async fn on_ping(payload: payloads::PingPayload) -> Result<(), Infallible> {
+///     println!("{payload:?}");
+///     Ok(())
+/// }
+///
+/// let shutdown = Shutdown::new();
+/// let parser = RequestParser::new("verification_token");
+/// let handler = parser
+///     .into_handler()
+///     .on_ping(service_fn(on_ping))
+///     .graceful(&shutdown);
+/// # let _ = handler;
+///
+/// // 実際には`tokio::time::sleep`などで期限を与える
+/// let deadline = std::future::pending::<()>();
+/// let report = futures::executor::block_on(shutdown.shutdown(deadline));
+/// assert_eq!(report.abandoned, 0);
+/// ```
+///
+/// [`Handler::graceful`]: crate::Handler::graceful
+/// [`Handler::background`]: crate::Handler::background
+#[must_use]
+#[derive(Debug, Clone, Default)]
+pub struct Shutdown {
+    inner: Arc<Inner>,
+}
+
+impl Shutdown {
+    /// 新しいハンドルを作成します。
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// シャットダウンを始めていれば`true`を返します。
+    #[must_use]
+    pub fn is_shutting_down(&self) -> bool {
+        self.inner.lock().closed
+    }
+
+    /// 処理中のイベントの数を返します。
+    #[must_use]
+    pub fn in_flight(&self) -> usize {
+        self.inner.lock().in_flight
+    }
+
+    /// `spawner`で実行する[`Future`]を処理中のイベントとして数える[`Spawn`]を返します。
+    ///
+    /// [`Future`]: std::future::Future
+    pub fn track_spawn<Sp>(&self, spawner: Sp) -> TrackSpawn<Sp>
+    where
+        Sp: Spawn,
+    {
+        TrackSpawn {
+            inner: Arc::clone(&self.inner),
+            spawner,
+        }
+    }
+
+    fn try_enter(&self) -> Option<Guard> {
+        let mut state = self.inner.lock();
+        if state.closed {
+            return None;
+        }
+        state.in_flight += 1;
+        drop(state);
+        Some(Guard::entered(&self.inner))
+    }
+
+    /// シャットダウンを始め、処理中のイベントが全て完了するか`deadline`が完了するまで待ちます。
+    ///
+    /// シャットダウンを始めた後に届いたリクエストには`503 Service Unavailable`を返します。
+    /// 期限を過ぎても完了しなかったイベントは[`ShutdownReport::abandoned`]として数えられますが、
+    /// 処理自体は中断されません。
+    pub async fn shutdown<F>(&self, deadline: F) -> ShutdownReport
+    where
+        F: Future,
+    {
+        let (completed, dropped) = {
+            let mut state = self.inner.lock();
+            state.closed = true;
+            (state.completed, state.dropped)
+        };
+        let mut deadline = pin!(deadline);
+        std::future::poll_fn(|cx| {
+            let mut state = self.inner.lock();
+            let report = ShutdownReport {
+                completed: state.completed - completed,
+                abandoned: state.in_flight,
+                dropped: state.dropped - dropped,
+            };
+            if state.in_flight == 0 {
+                return Poll::Ready(report);
+            }
+            if !state.waiters.iter().any(|w| w.will_wake(cx.waker())) {
+                state.waiters.push(cx.waker().clone());
+            }
+            drop(state);
+            deadline.as_mut().poll(cx).map(|_| report)
+        })
+        .await
+    }
+}
+
+/// 実行する[`Future`]を処理中のイベントとして数える[`Spawn`]です。
+///
+/// [`Shutdown::track_spawn`]で作成します。
+///
+/// [`Future`]: std::future::Future
+#[must_use]
+#[derive(Debug, Clone)]
+pub struct TrackSpawn<Spawner> {
+    inner: Arc<Inner>,
+    spawner: Spawner,
+}
+
+impl<Sp> Spawn for TrackSpawn<Sp>
+where
+    Sp: Spawn,
+{
+    fn spawn<F>(&self, future: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        // 受け付け済みのイベントの処理なので、シャットダウン中でも数える
+        let mut guard = Guard::new(&self.inner);
+        self.spawner.spawn(async move {
+            future.await;
+            guard.complete();
+        });
+    }
+}
+
+pin_project! {
+    /// <code><[Graceful] as [Service]>::[Future]</code>
+    ///
+    /// [Graceful]: crate::handler::Graceful
+    /// [Service]: tower::Service
+    /// [Future]: std::future::Future
+    #[must_use]
+    #[derive(Debug)]
+    pub struct GracefulFuture<F> {
+        #[pin]
+        inner: F,
+        guard: Guard,
+    }
+}
+
+impl<F> Future for GracefulFuture<F>
+where
+    F: Future,
+{
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let s = self.project();
+        let output = ready!(s.inner.poll(cx));
+        s.guard.complete();
+        Poll::Ready(output)
+    }
+}
+
+/// 処理中のイベントを[`Shutdown`]に数えさせ、シャットダウン中は新しいイベントを断る[`Service`]です。
+///
+/// [`Service`]: tower::Service
+#[must_use]
+#[derive(Debug, Clone)]
+pub struct Graceful<Service> {
+    shutdown: Shutdown,
+    service: Service,
+}
+
+impl<Srv> Graceful<Srv> {
+    pub(crate) fn new(shutdown: Shutdown, service: Srv) -> Self {
+        Self { shutdown, service }
+    }
+}
+
+impl<Srv, Req> Service<Req> for Graceful<Srv>
+where
+    Srv: Service<Req, Response = (), Error = Error>,
+{
+    type Response = ();
+    type Error = Error;
+    type Future =
+        Either<futures_util::future::Ready<Result<(), Error>>, GracefulFuture<Srv::Future>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        // シャットダウン中は内部の`Service`を待たずにすぐ断る
+        if self.shutdown.is_shutting_down() {
+            return Poll::Ready(Ok(()));
+        }
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Req) -> Self::Future {
+        let Some(guard) = self.shutdown.try_enter() else {
+            let error = Error::from(ErrorKind::ShuttingDown);
+            return Either::Left(futures_util::future::ready(Err(error)));
+        };
+        Either::Right(GracefulFuture {
+            inner: self.service.call(request),
+            guard,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use futures::channel::oneshot;
+    use futures::executor::block_on;
+    use futures::future::{pending, ready};
+    use tower::{service_fn, ServiceExt};
+
+    #[test]
+    fn drain_in_flight() {
+        let shutdown = Shutdown::new();
+        let (tx, rx) = oneshot::channel::<()>();
+        let rx = futures::FutureExt::shared(rx);
+        let service = service_fn(move |(): ()| {
+            let rx = rx.clone();
+            async move {
+                let _ = rx.await;
+                Ok::<(), Error>(())
+            }
+        });
+        let mut service = Graceful::new(shutdown.clone(), service);
+        let call = block_on(service.ready()).unwrap().call(());
+        assert_eq!(shutdown.in_flight(), 1);
+
+        // 期限までに完了しない
+        let report = block_on(shutdown.shutdown(ready(())));
+        assert_eq!(
+            report,
+            ShutdownReport {
+                completed: 0,
+                abandoned: 1,
+                dropped: 0
+            }
+        );
+        // 新しいイベントは断る
+        let rejected = block_on(block_on(service.ready()).unwrap().call(()));
+        assert_eq!(rejected.unwrap_err().kind(), ErrorKind::ShuttingDown);
+
+        tx.send(()).unwrap();
+        block_on(call).unwrap();
+        let report = block_on(shutdown.shutdown(pending::<()>()));
+        assert_eq!(
+            report,
+            ShutdownReport {
+                completed: 0,
+                abandoned: 0,
+                dropped: 0
+            }
+        );
+        assert_eq!(shutdown.in_flight(), 0);
+    }
+
+    #[test]
+    fn track_spawn() {
+        struct Thread;
+
+        impl Spawn for Thread {
+            fn spawn<F>(&self, future: F)
+            where
+                F: Future<Output = ()> + Send + 'static,
+            {
+                std::thread::spawn(move || block_on(future));
+            }
+        }
+
+        let shutdown = Shutdown::new();
+        let (tx, rx) = oneshot::channel::<()>();
+        shutdown.track_spawn(Thread).spawn(async {
+            let _ = rx.await;
+        });
+        assert_eq!(shutdown.in_flight(), 1);
+        let (report, ()) = block_on(async {
+            // シャットダウンを始めてから完了させる
+            let report = shutdown.shutdown(pending::<()>());
+            futures::join!(report, async { tx.send(()).unwrap() })
+        });
+        assert_eq!(
+            report,
+            ShutdownReport {
+                completed: 1,
+                abandoned: 0,
+                dropped: 0
+            }
+        );
+    }
+
+    #[test]
+    fn drop_unfinished() {
+        let shutdown = Shutdown::new();
+        let service = service_fn(|(): ()| pending::<Result<(), Error>>());
+        let mut service = Graceful::new(shutdown.clone(), service);
+        let call = block_on(service.ready()).unwrap().call(());
+        let (report, ()) = block_on(async {
+            // 完了する前に破棄する
+            let report = shutdown.shutdown(pending::<()>());
+            futures::join!(report, async move { drop(call) })
+        });
+        assert_eq!(
+            report,
+            ShutdownReport {
+                completed: 0,
+                abandoned: 0,
+                dropped: 1
+            }
+        );
+    }
+}
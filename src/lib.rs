@@ -1,4 +1,6 @@
 #![cfg_attr(docsrs, feature(doc_auto_cfg, doc_cfg))]
+// `uuid` featureの有無によって`Uuid`が`Copy`を実装するかが変わる
+#![cfg_attr(feature = "uuid", allow(clippy::clone_on_copy))]
 
 //! [![GitHub](https://img.shields.io/github/license/H1rono/traq-bot-http-rs?style=for-the-badge&logo=github)](https://github.com/H1rono/traq-bot-http-rs/blob/main/LICENSE)
 //! [![Crates.io](https://img.shields.io/crates/l/traq-bot-http?style=for-the-badge&logo=docsdotrs)](https://crates.io/crates/traq-bot-http)
@@ -9,19 +11,29 @@
 //!
 //! [examples](https://github.com/H1rono/traq-bot-http-rs/blob/main/examples)
 
+pub mod cache;
+pub mod command;
 mod error;
 mod events;
+#[cfg(any(feature = "arbitrary", feature = "proptest"))]
+mod generators;
 pub(crate) mod macros;
+pub mod message;
 pub mod parser;
 pub mod payloads;
+#[cfg(feature = "schemars")]
+pub mod schema;
 
 #[cfg(feature = "tower")]
 pub mod handler;
 
+#[cfg(feature = "testing")]
+pub mod testing;
+
 use std::sync::Arc;
 
 pub use error::{Error, ErrorKind, Result};
-pub use events::{Event, EventKind};
+pub use events::{Event, EventKind, EventKindSet};
 
 /// HTTP POSTリクエストのパーサー
 #[must_use]
@@ -62,26 +74,43 @@ pub struct RequestParser {
 /// - [`.on_*<S>(S)`]
 ///     - `*`には [`EventKind`] の variant が `snake_case` で入ります。
 ///     - 例: [`Handler::on_message_created`]
+/// - [`.on_event<S>(S)`]
+///     - handleされなかったイベントを受け取ります。他のメソッドより先に呼び出す必要があります。
+/// - [`.on_kinds<S>(EventKindSet, S)`]
 /// - [`.with_state<S>(S)`]
+/// - [`.observe<O>(O)`]
+/// - [`.background<S>(S, usize)`]
+/// - [`.graceful(&Shutdown)`]
+/// - [`.layer<L>(L)`], [`.layer_last<L>(L)`]
+/// - [`.with_response_body::<B>()`]
 ///
 /// 適切に構成された [`Handler`] は [`Service`] trait を実装します。各メソッドのドキュメントを参照してください。
+/// HTTP を経ずにイベントを handle するには [`Handler::dispatch`] または [`Handler::into_event_service`] を使用してください。
 ///
 /// **[`Handler`] の構成時にはコンパイルエラーが出ない可能性があります** 。
 /// [`Service`] trait を使用するライブラリ (axum 等) の条件も確認してください。
 ///
 /// # Note
 ///
-/// この構造体の型パラメータは **unstable** です。`Handler<T>`における`T`は予告なく変化する可能性があります。
+/// この構造体の型パラメータは **unstable** です。`Handler<T, B>`における`T`は予告なく変化する可能性があります。
 ///
 /// [`handler`]: crate::handler
 /// [`Service`]: tower::Service
 /// [`.on_*<S>(S)`]: crate::Handler::on_ping
+/// [`.on_event<S>(S)`]: crate::Handler::on_event
+/// [`.on_kinds<S>(EventKindSet, S)`]: crate::Handler::on_kinds
 /// [`.with_state<S>(S)`]: crate::Handler::with_state
+/// [`.observe<O>(O)`]: crate::Handler::observe
+/// [`.background<S>(S, usize)`]: crate::Handler::background
+/// [`.graceful(&Shutdown)`]: crate::Handler::graceful
+/// [`.layer<L>(L)`]: crate::Handler::layer
+/// [`.layer_last<L>(L)`]: crate::Handler::layer_last
+/// [`.with_response_body::<B>()`]: crate::Handler::with_response_body
 #[must_use]
-#[derive(Debug, Clone)]
-pub struct Handler<Service> {
+pub struct Handler<Service, ResBody = String> {
     service: Service,
     parser: RequestParser,
+    _res_body: std::marker::PhantomData<fn() -> ResBody>,
 }
 
 #[cfg(test)]
@@ -197,6 +197,7 @@ macro_rules! all_events {
             ChannelCreated,
             ChannelTopicChanged,
             UserCreated,
+            UserActivated,
             StampCreated,
             TagAdded,
             TagRemoved,
@@ -396,7 +397,7 @@ macro_rules! event_service_poll_ready {
             if let ::std::result::Result::Err(e) = ::futures_core::ready!(self.inner.poll_ready(cx))
             {
                 return ::std::task::Poll::Ready(::std::result::Result::Err(
-                    $crate::Error::handler(e),
+                    $crate::Error::from_handler(e),
                 ));
             }
             if let ::std::result::Result::Err(e) =
@@ -521,6 +522,22 @@ macro_rules! event_service {
                 [< $e:camel >] (e) => (state, e)
             }
         }
+
+        impl<L, Service, Fallback, Req> $crate::handler::LayerLast<L>
+        for [< On $e:camel >] <Service, Fallback, Req>
+        where
+            L: ::tower_layer::Layer<Service>,
+        {
+            type Output = [< On $e:camel >] <L::Service, Fallback, Req>;
+
+            fn layer_last(self, layer: L) -> Self::Output {
+                [< On $e:camel >] {
+                    _req: ::std::marker::PhantomData,
+                    inner: layer.layer(self.inner),
+                    fallback: self.fallback,
+                }
+            }
+        }
     }};
 }
 
@@ -530,26 +547,19 @@ macro_rules! handler_on_events {
         $( #[$m:meta] )*
         $v:vis $e:ident;
     )+) => { ::paste::paste! {
-        impl<Service1> $crate::Handler<Service1> {
+        impl<Service1, ResBody> $crate::Handler<Service1, ResBody> {
             $(
                 $( #[$m] )*
                 $v fn [< on_ $e:snake:lower >] <Service2, Req> (self, service: Service2)
-                -> $crate::Handler<$crate::handler::[< On $e:camel >] <Service2, Service1, Req>>
+                -> $crate::Handler<$crate::handler::[< On $e:camel >] <Service2, Service1, Req>, ResBody>
                 where
                     Service2: ::tower_service::Service<Req>,
                 {
-                    let Self {
-                        service: fallback,
-                        parser,
-                    } = self;
-                    $crate::Handler {
-                        service: $crate::handler::[< On $e:camel >] {
-                            _req: ::std::marker::PhantomData,
-                            inner: service,
-                            fallback,
-                        },
-                        parser,
-                    }
+                    self.map_service(|fallback| $crate::handler::[< On $e:camel >] {
+                        _req: ::std::marker::PhantomData,
+                        inner: service,
+                        fallback,
+                    })
                 }
             )+
         }
@@ -711,7 +721,8 @@ macro_rules! all_error_kinds {
             BotEventMismatch,
             ReadBodyFailed,
             ParseBodyFailed,
-            Handler
+            Handler,
+            ShuttingDown
         }
     };
 }
@@ -0,0 +1,808 @@
+//! メッセージ本文の組み立てと解析
+//!
+//! traQのメッセージ本文では、メンションやチャンネルリンクが
+//! `!{"type":"user","raw":"@takashi_trap","id":"..."}` のような埋め込み形式で表現されます。
+//! このモジュールはその形式の本文を組み立てる [`MessageBuilder`] と、
+//! 本文を埋め込みとそれ以外に分割する [`segments`] を提供します。
+//! また、本文中のメッセージやファイルへのリンクを [`links`] で取り出せます。
+//! 本文の変更は [`text_diff`] で行単位の差分として得られます。
+
+use std::fmt;
+use std::ops::Range;
+
+use serde::Serialize;
+
+use crate::payloads::types::{Channel, ChannelPath, EmbeddedInfo, Message, User, UserGroup, Uuid};
+
+/// 埋め込み形式でのシリアライズ用。traQの出力と同じく`type`, `raw`, `id`の順に並べる
+#[derive(Serialize)]
+struct EmbeddedRef<'a> {
+    r#type: &'a str,
+    raw: &'a str,
+    id: &'a Uuid,
+}
+
+/// 埋め込みを本文中の表現に変換します。
+fn write_embedded(buf: &mut String, info: &EmbeddedInfo) {
+    let embedded = EmbeddedRef {
+        r#type: &info.r#type,
+        raw: &info.raw,
+        id: &info.id,
+    };
+    let json = serde_json::to_string(&embedded).expect("failed to serialize EmbeddedInfo");
+    buf.push('!');
+    buf.push_str(&json);
+}
+
+/// traQに投稿するメッセージ本文を組み立てるビルダーです。
+///
+/// メンションやチャンネルリンクは埋め込み形式で、JSONとしてエスケープされた上で出力されます。
+///
+/// ## Example
+/// ```
+/// # fn main() -> Result<(), serde_json::Error> {
+/// use traq_bot_http::message::{self, MessageBuilder};
+/// use traq_bot_http::payloads::types::User;
+///
+/// let user: User = r#"{
+///     "id": "dfdff0c9-5de0-46ee-9721-2525e8bb3d45",
+///     "name": "takashi_trap",
+///     "displayName": "寺田 健二",
+///     "iconId": "2bc06cda-bdb9-4a68-8000-62f907f36a92",
+///     "bot": false
+/// }"#.parse()?;
+/// let builder = MessageBuilder::new()
+///     .mention_user(&user)
+///     .text(" こんにちは")
+///     .stamp("pro");
+/// let embedded = builder.embedded().to_vec();
+/// let text = builder.build();
+/// assert_eq!(
+///     text,
+///     r#"!{"type":"user","raw":"@takashi_trap","id":"dfdff0c9-5de0-46ee-9721-2525e8bb3d45"} こんにちは:pro:"#
+/// );
+/// assert_eq!(message::embedded(&text).collect::<Vec<_>>(), embedded);
+/// # Ok(())
+/// # }
+/// ```
+#[must_use]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MessageBuilder {
+    text: String,
+    embedded: Vec<EmbeddedInfo>,
+    // コードブロックの直後では次の内容の前に改行が必要
+    after_block: bool,
+}
+
+impl MessageBuilder {
+    /// 空の`MessageBuilder`を作成します。
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn buf(&mut self) -> &mut String {
+        if std::mem::take(&mut self.after_block) {
+            self.text.push('\n');
+        }
+        &mut self.text
+    }
+
+    /// テキストをそのまま追加します。
+    ///
+    /// `text`中の文字列は加工されないため、埋め込み形式の文字列を含む場合はそのまま埋め込みとして解釈されます。
+    pub fn text(mut self, text: &str) -> Self {
+        self.buf().push_str(text);
+        self
+    }
+
+    /// 埋め込みを追加します。
+    pub fn embed(mut self, info: EmbeddedInfo) -> Self {
+        write_embedded(self.buf(), &info);
+        self.embedded.push(info);
+        self
+    }
+
+    /// ユーザーへのメンションを追加します。
+    pub fn mention_user(self, user: &User) -> Self {
+        self.embed(EmbeddedInfo {
+            raw: format!("@{}", user.name),
+            r#type: "user".to_string(),
+            id: user.id.clone(),
+        })
+    }
+
+    /// ユーザーグループへのメンションを追加します。
+    pub fn mention_group(self, group: &UserGroup) -> Self {
+        self.embed(EmbeddedInfo {
+            raw: format!("@{}", group.name),
+            r#type: "group".to_string(),
+            id: group.id.clone(),
+        })
+    }
+
+    /// チャンネルへのリンクを追加します。
+    pub fn channel(self, channel: &Channel) -> Self {
+        self.embed(EmbeddedInfo {
+            raw: channel.path.clone(),
+            r#type: "channel".to_string(),
+            id: channel.id.clone(),
+        })
+    }
+
+    /// スタンプ`:name:`を追加します。
+    pub fn stamp(mut self, name: &str) -> Self {
+        let buf = self.buf();
+        buf.push(':');
+        buf.push_str(name);
+        buf.push(':');
+        self
+    }
+
+    /// コードブロックを追加します。
+    ///
+    /// フェンスの長さは`code`中に含まれるバッククォートの連続よりも長くなるよう調整されます。
+    /// コードブロックは常に独立した行に置かれます。
+    pub fn code_block(mut self, lang: &str, code: &str) -> Self {
+        let longest_run = code
+            .split(|c| c != '`')
+            .map(str::len)
+            .max()
+            .unwrap_or_default();
+        let fence = "`".repeat(longest_run.max(2) + 1);
+        let buf = self.buf();
+        if !buf.is_empty() && !buf.ends_with('\n') {
+            buf.push('\n');
+        }
+        buf.push_str(&fence);
+        buf.push_str(lang);
+        buf.push('\n');
+        buf.push_str(code);
+        if !code.ends_with('\n') {
+            buf.push('\n');
+        }
+        buf.push_str(&fence);
+        self.after_block = true;
+        self
+    }
+
+    /// これまでに追加された埋め込みを返します。
+    #[must_use]
+    pub fn embedded(&self) -> &[EmbeddedInfo] {
+        &self.embedded
+    }
+
+    /// メッセージ本文を返します。
+    #[must_use]
+    pub fn build(self) -> String {
+        self.text
+    }
+}
+
+impl fmt::Display for MessageBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+/// メッセージ本文の断片です。[`segments`]で得られます。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Segment<'a> {
+    /// 埋め込み以外のテキスト
+    Text {
+        /// 本文中のバイト範囲
+        range: Range<usize>,
+        text: &'a str,
+    },
+    /// 埋め込み
+    Embedded {
+        /// 本文中のバイト範囲
+        range: Range<usize>,
+        info: EmbeddedInfo,
+    },
+}
+
+impl Segment<'_> {
+    /// 本文中のバイト範囲を返します。
+    #[must_use]
+    pub fn range(&self) -> Range<usize> {
+        match self {
+            Self::Text { range, .. } | Self::Embedded { range, .. } => range.clone(),
+        }
+    }
+}
+
+/// [`segments`]の返り値です。
+#[must_use]
+#[derive(Debug, Clone)]
+pub struct Segments<'a> {
+    text: &'a str,
+    pos: usize,
+    // 次に返す埋め込み
+    pending: Option<(Range<usize>, EmbeddedInfo)>,
+}
+
+/// `start`から始まる埋め込みをパースし、埋め込みの終端のバイト位置と共に返します。
+fn parse_embedded_at(text: &str, start: usize) -> Option<(usize, EmbeddedInfo)> {
+    let rest = text[start..].strip_prefix("!{")?;
+    let json_start = text.len() - rest.len() - 1;
+    let mut stream =
+        serde_json::Deserializer::from_str(&text[json_start..]).into_iter::<EmbeddedInfo>();
+    let info = stream.next()?.ok()?;
+    Some((json_start + stream.byte_offset(), info))
+}
+
+impl<'a> Iterator for Segments<'a> {
+    type Item = Segment<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some((range, info)) = self.pending.take() {
+            self.pos = range.end;
+            return Some(Segment::Embedded { range, info });
+        }
+        if self.pos >= self.text.len() {
+            return None;
+        }
+        let start = self.pos;
+        let mut cursor = start;
+        while let Some(offset) = self.text[cursor..].find("!{") {
+            let found = cursor + offset;
+            if let Some((end, info)) = parse_embedded_at(self.text, found) {
+                if found == start {
+                    self.pos = end;
+                    return Some(Segment::Embedded {
+                        range: start..end,
+                        info,
+                    });
+                }
+                self.pending = Some((found..end, info));
+                self.pos = found;
+                return Some(Segment::Text {
+                    range: start..found,
+                    text: &self.text[start..found],
+                });
+            }
+            cursor = found + 1;
+        }
+        self.pos = self.text.len();
+        Some(Segment::Text {
+            range: start..self.text.len(),
+            text: &self.text[start..],
+        })
+    }
+}
+
+/// メッセージ本文を埋め込みとそれ以外のテキストに分割します。
+///
+/// ## Example
+/// ```
+/// use traq_bot_http::message::{segments, Segment};
+///
+/// let text = r#"!{"type":"user","raw":"@takashi_trap","id":"dfdff0c9-5de0-46ee-9721-2525e8bb3d45"} こんにちは"#;
+/// let segments: Vec<_> = segments(text).collect();
+/// assert_eq!(segments.len(), 2);
+/// assert!(matches!(&segments[0], Segment::Embedded { info, .. } if info.raw == "@takashi_trap"));
+/// assert!(matches!(&segments[1], Segment::Text { text: " こんにちは", .. }));
+/// ```
+pub fn segments(text: &str) -> Segments<'_> {
+    Segments {
+        text,
+        pos: 0,
+        pending: None,
+    }
+}
+
+/// メッセージ本文に含まれる埋め込みを順に返します。
+pub fn embedded(text: &str) -> impl Iterator<Item = EmbeddedInfo> + '_ {
+    segments(text).filter_map(|s| match s {
+        Segment::Embedded { info, .. } => Some(info),
+        Segment::Text { .. } => None,
+    })
+}
+
+/// 埋め込みを`raw`の値で置き換えた本文を返します。
+///
+/// traQのメッセージにおける`plainText`に相当します。
+#[must_use]
+pub fn plain_text(text: &str) -> String {
+    segments(text).fold(String::with_capacity(text.len()), |mut buf, s| {
+        match s {
+            Segment::Text { text, .. } => buf.push_str(text),
+            Segment::Embedded { info, .. } => buf.push_str(&info.raw),
+        }
+        buf
+    })
+}
+
+/// メッセージのID
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MessageId(pub Uuid);
+
+/// ファイルのID
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FileId(pub Uuid);
+
+impl From<Uuid> for MessageId {
+    fn from(id: Uuid) -> Self {
+        Self(id)
+    }
+}
+
+impl From<Uuid> for FileId {
+    fn from(id: Uuid) -> Self {
+        Self(id)
+    }
+}
+
+impl fmt::Display for MessageId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl fmt::Display for FileId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// 本文中のリンクが指すものです。
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Reference {
+    /// メッセージの引用 (`{origin}/messages/{id}`)
+    Message(MessageId),
+    /// ファイル (`{origin}/files/{id}`)
+    File(FileId),
+    /// チャンネル (`{origin}/channels/{path}`)
+    Channel(ChannelPath),
+}
+
+/// 本文中のリンクです。[`links`]で得られます。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Link {
+    /// 本文中のバイト範囲
+    pub range: Range<usize>,
+    pub reference: Reference,
+}
+
+/// [`links`]の返り値です。
+#[must_use]
+#[derive(Debug, Clone)]
+pub struct Links<'a> {
+    text: &'a str,
+    origin: &'a str,
+    segments: Segments<'a>,
+    // 探索中の埋め込み以外のテキストの範囲
+    current: Option<Range<usize>>,
+}
+
+/// `8-4-4-4-12`形式のUUIDであるか
+fn is_uuid(s: &str) -> bool {
+    const GROUPS: [usize; 5] = [8, 4, 4, 4, 12];
+    s.len() == 36
+        && s.split('-').map(str::len).eq(GROUPS)
+        && s.bytes().all(|b| b == b'-' || b.is_ascii_hexdigit())
+}
+
+/// リンクの直後に続いてよい文字か
+fn is_link_boundary(rest: &str) -> bool {
+    rest.chars()
+        .next()
+        .is_none_or(|c| !(c.is_ascii_alphanumeric() || c == '-' || c == '_'))
+}
+
+/// `{prefix}{uuid}`の形をパースし、UUIDとその終端のバイト位置を返します。
+fn parse_id_link(rest: &str, prefix: &str) -> Option<(usize, Uuid)> {
+    let id = rest.strip_prefix(prefix)?.get(..36)?;
+    let end = prefix.len() + id.len();
+    if !is_uuid(id) || !is_link_boundary(&rest[end..]) {
+        return None;
+    }
+    Some((end, id.parse().ok()?))
+}
+
+/// `/channels/{path}`の形をパースし、パスとその終端のバイト位置を返します。
+fn parse_channel_link(rest: &str) -> Option<(usize, ChannelPath)> {
+    const PREFIX: &str = "/channels/";
+    let path = rest.strip_prefix(PREFIX)?;
+    let len = path
+        .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '/')))
+        .unwrap_or(path.len());
+    let path = path[..len].trim_end_matches('/');
+    let channel_path = ChannelPath::new(&format!("#{path}")).ok()?;
+    Some((PREFIX.len() + path.len(), channel_path))
+}
+
+/// originの直後`start`から始まるリンクをパースします。
+fn parse_link_at(text: &str, start: usize) -> Option<(usize, Reference)> {
+    let rest = &text[start..];
+    let (len, reference) = if let Some((len, id)) = parse_id_link(rest, "/messages/") {
+        (len, Reference::Message(MessageId(id)))
+    } else if let Some((len, id)) = parse_id_link(rest, "/files/") {
+        (len, Reference::File(FileId(id)))
+    } else {
+        let (len, path) = parse_channel_link(rest)?;
+        (len, Reference::Channel(path))
+    };
+    Some((start + len, reference))
+}
+
+impl Iterator for Links<'_> {
+    type Item = Link;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let Some(range) = self.current.as_mut() else {
+                self.current = loop {
+                    match self.segments.next()? {
+                        Segment::Text { range, .. } => break Some(range),
+                        Segment::Embedded { .. } => {}
+                    }
+                };
+                continue;
+            };
+            if range.start >= range.end {
+                self.current = None;
+                continue;
+            }
+            let text = &self.text[..range.end];
+            let Some(offset) = text[range.start..].find(self.origin) else {
+                self.current = None;
+                continue;
+            };
+            let found = range.start + offset;
+            let after = found + self.origin.len();
+            if let Some((end, reference)) = parse_link_at(text, after) {
+                range.start = end;
+                return Some(Link {
+                    range: found..end,
+                    reference,
+                });
+            }
+            // originが空の場合にも進むよう、少なくとも1文字進める
+            let next_char = text[found..].chars().next().map_or(1, char::len_utf8);
+            range.start = found + self.origin.len().max(next_char);
+        }
+    }
+}
+
+/// メッセージ本文に含まれる、`origin`のtraQへのリンクを順に返します。
+///
+/// `origin`は`https://q.trap.jp`のようなtraQのオリジンです。末尾の`/`は無視されます。
+/// 埋め込みの中のリンクは含まれません。
+///
+/// ## Example
+/// ```
+/// use traq_bot_http::message::{links, Reference};
+///
+/// let text = "見て https://q.trap.jp/messages/dfdff0c9-5de0-46ee-9721-2525e8bb3d45";
+/// let links: Vec<_> = links(text, "https://q.trap.jp/").collect();
+/// assert_eq!(links.len(), 1);
+/// assert_eq!(links[0].range, "見て ".len()..text.len());
+/// assert!(matches!(&links[0].reference, Reference::Message(id)
+///     if id.to_string() == "dfdff0c9-5de0-46ee-9721-2525e8bb3d45"));
+/// ```
+pub fn links<'a>(text: &'a str, origin: &'a str) -> Links<'a> {
+    Links {
+        text,
+        origin: origin.trim_end_matches('/'),
+        segments: segments(text),
+        current: None,
+    }
+}
+
+impl Message {
+    /// 本文に含まれる、`origin`のtraQへのリンクを順に返します。[`links`]を参照してください。
+    pub fn links<'a>(&'a self, origin: &'a str) -> Links<'a> {
+        links(&self.text, origin)
+    }
+}
+
+/// 行単位の差分の1行です。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiffLine {
+    /// 変更されていない行
+    Unchanged(String),
+    /// 削除された行
+    Removed(String),
+    /// 追加された行
+    Added(String),
+}
+
+/// 本文の行単位の差分です。[`text_diff`]で得られます。
+///
+/// [`Display`]では各行の先頭に`  `, `- `, `+ `を付けて出力します。
+///
+/// [`Display`]: std::fmt::Display
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TextDiff {
+    pub lines: Vec<DiffLine>,
+}
+
+impl TextDiff {
+    /// 変更が無い場合に`true`を返します。
+    #[must_use]
+    pub fn is_unchanged(&self) -> bool {
+        self.lines
+            .iter()
+            .all(|l| matches!(l, DiffLine::Unchanged(_)))
+    }
+}
+
+impl fmt::Display for TextDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for line in &self.lines {
+            match line {
+                DiffLine::Unchanged(l) => writeln!(f, "  {l}")?,
+                DiffLine::Removed(l) => writeln!(f, "- {l}")?,
+                DiffLine::Added(l) => writeln!(f, "+ {l}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `old`から`new`への行単位の差分を計算します。
+///
+/// ## Example
+/// ```
+/// use traq_bot_http::message::{text_diff, DiffLine};
+///
+/// let diff = text_diff("こんにちは\nよろしく", "こんばんは\nよろしく");
+/// assert_eq!(
+///     diff.lines,
+///     [
+///         DiffLine::Removed("こんにちは".to_string()),
+///         DiffLine::Added("こんばんは".to_string()),
+///         DiffLine::Unchanged("よろしく".to_string()),
+///     ]
+/// );
+/// assert_eq!(diff.to_string(), "- こんにちは\n+ こんばんは\n  よろしく\n");
+/// ```
+#[must_use]
+pub fn text_diff(old: &str, new: &str) -> TextDiff {
+    let old: Vec<_> = old.lines().collect();
+    let new: Vec<_> = new.lines().collect();
+    // lcs[i][j]: old[i..]とnew[j..]の最長共通部分列の長さ
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+    let (mut i, mut j) = (0, 0);
+    let mut lines = Vec::with_capacity(old.len().max(new.len()));
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            lines.push(DiffLine::Unchanged(old[i].to_string()));
+            (i, j) = (i + 1, j + 1);
+        } else if j == new.len() || (i < old.len() && lcs[i + 1][j] >= lcs[i][j + 1]) {
+            lines.push(DiffLine::Removed(old[i].to_string()));
+            i += 1;
+        } else {
+            lines.push(DiffLine::Added(new[j].to_string()));
+            j += 1;
+        }
+    }
+    TextDiff { lines }
+}
+
+#[cfg(any(feature = "testing", feature = "arbitrary", feature = "proptest"))]
+impl Message {
+    /// 本文を`text`に置き換え、`plain_text`と`embedded`を本文から計算し直す
+    pub(crate) fn with_text(mut self, text: String) -> Self {
+        self.plain_text = plain_text(&text);
+        self.embedded = embedded(&text).collect();
+        self.text = text;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{channel_a_po, embedded_takashi_trap, takashi_trap, uuid};
+
+    use std::fs::read_to_string;
+
+    use crate::payloads::MessageCreatedPayload;
+
+    #[test]
+    fn build_mentions() {
+        let user = takashi_trap();
+        let channel = channel_a_po();
+        let builder = MessageBuilder::new()
+            .mention_user(&user)
+            .text(" ")
+            .channel(&channel);
+        assert_eq!(
+            builder.embedded(),
+            &[
+                embedded_takashi_trap(),
+                EmbeddedInfo {
+                    raw: "#a/po".to_string(),
+                    r#type: "channel".to_string(),
+                    id: uuid("f86c925c-3002-4ba5-939a-c92344e534f9"),
+                },
+            ]
+        );
+        assert_eq!(
+            builder.build(),
+            concat!(
+                r#"!{"type":"user","raw":"@takashi_trap","id":"dfdff0c9-5de0-46ee-9721-2525e8bb3d45"} "#,
+                r##"!{"type":"channel","raw":"#a/po","id":"f86c925c-3002-4ba5-939a-c92344e534f9"}"##,
+            )
+        );
+    }
+
+    #[test]
+    fn embedded_round_trip() {
+        let info = EmbeddedInfo {
+            raw: r#"@"quoted"\name}"#.to_string(),
+            r#type: "user".to_string(),
+            id: uuid("dfdff0c9-5de0-46ee-9721-2525e8bb3d45"),
+        };
+        let builder = MessageBuilder::new()
+            .text("!{ not embedded ")
+            .embed(info.clone())
+            .text("!{}");
+        let expected = builder.embedded().to_vec();
+        let text = builder.build();
+        assert_eq!(embedded(&text).collect::<Vec<_>>(), expected);
+        assert_eq!(
+            plain_text(&text),
+            r#"!{ not embedded @"quoted"\name}!{}"#.to_string()
+        );
+    }
+
+    #[test]
+    fn segments_ranges() {
+        let text = MessageBuilder::new()
+            .text("a")
+            .embed(embedded_takashi_trap())
+            .embed(embedded_takashi_trap())
+            .text("b")
+            .build();
+        let ranges: Vec<_> = segments(&text).map(|s| s.range()).collect();
+        let first = text.find("!{").unwrap();
+        let second = text.rfind("!{").unwrap();
+        let last = text.len() - 1;
+        assert_eq!(
+            ranges,
+            vec![0..first, first..second, second..last, last..text.len()]
+        );
+    }
+
+    #[test]
+    fn segments_of_message() {
+        let data = read_to_string("testdata/message/message_created.json").unwrap();
+        let payload: MessageCreatedPayload = data.parse().unwrap();
+        let message = payload.message;
+        assert_eq!(
+            embedded(&message.text).collect::<Vec<_>>(),
+            message.embedded
+        );
+        assert_eq!(plain_text(&message.text), message.plain_text);
+    }
+
+    #[test]
+    fn code_block_fence() {
+        let text = MessageBuilder::new()
+            .text("code:")
+            .code_block("rust", "let s = \"```\";")
+            .text("done")
+            .build();
+        assert_eq!(text, "code:\n````rust\nlet s = \"```\";\n````\ndone");
+        let text = MessageBuilder::new().code_block("", "a\n").build();
+        assert_eq!(text, "```\na\n```");
+    }
+
+    #[test]
+    fn stamp() {
+        let text = MessageBuilder::new().text("nice").stamp("pro").build();
+        assert_eq!(text, "nice:pro:");
+    }
+
+    #[test]
+    fn links_kinds() {
+        let origin = "https://q.trap.jp";
+        let text = concat!(
+            "引用 https://q.trap.jp/messages/dfdff0c9-5de0-46ee-9721-2525e8bb3d45\n",
+            "[画像](https://q.trap.jp/files/2bc06cda-bdb9-4a68-8000-62f907f36a92)\n",
+            "https://q.trap.jp/channels/a/po/ を見て",
+        );
+        let links: Vec<_> = links(text, origin).collect();
+        let ranges: Vec<_> = links.iter().map(|l| &text[l.range.clone()]).collect();
+        assert_eq!(
+            ranges,
+            [
+                "https://q.trap.jp/messages/dfdff0c9-5de0-46ee-9721-2525e8bb3d45",
+                "https://q.trap.jp/files/2bc06cda-bdb9-4a68-8000-62f907f36a92",
+                "https://q.trap.jp/channels/a/po",
+            ]
+        );
+        assert_eq!(
+            links[0].reference,
+            Reference::Message(MessageId(uuid("dfdff0c9-5de0-46ee-9721-2525e8bb3d45")))
+        );
+        assert_eq!(
+            links[1].reference,
+            Reference::File(FileId(uuid("2bc06cda-bdb9-4a68-8000-62f907f36a92")))
+        );
+        assert_eq!(
+            links[2].reference,
+            Reference::Channel("#a/po".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn links_skip_invalid() {
+        let text = concat!(
+            "https://q.trap.jp/messages/dfdff0c9-5de0-46ee-9721-2525e8bb3d4 ",
+            "https://q.trap.jp/messages/dfdff0c9-5de0-46ee-9721-2525e8bb3d45a ",
+            "https://q.trap.jp/messages/dfdff0c9x5de0-46ee-9721-2525e8bb3d45 ",
+            "https://example.com/files/2bc06cda-bdb9-4a68-8000-62f907f36a92 ",
+            "https://q.trap.jp/channels/ ",
+            "https://q.trap.jp/users/takashi_trap",
+        );
+        assert_eq!(links(text, "https://q.trap.jp/").count(), 0);
+    }
+
+    #[test]
+    fn links_outside_embedded() {
+        let link = "https://q.trap.jp/channels/a/po";
+        let channel = Channel {
+            path: link.to_string(),
+            ..channel_a_po()
+        };
+        let text = MessageBuilder::new().channel(&channel).text(link).build();
+        let links: Vec<_> = links(&text, "https://q.trap.jp").collect();
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].range, text.len() - link.len()..text.len());
+    }
+
+    #[test]
+    fn links_relative() {
+        let text = "![](/files/2bc06cda-bdb9-4a68-8000-62f907f36a92)";
+        let links: Vec<_> = links(text, "").collect();
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].range, 4..text.len() - 1);
+    }
+
+    #[test]
+    fn links_of_message() {
+        let data = read_to_string("testdata/message/message_created.json").unwrap();
+        let payload: MessageCreatedPayload = data.parse().unwrap();
+        let mut message = payload.message;
+        assert_eq!(message.links("https://q.trap.jp").count(), 0);
+        message.text = format!("{} https://q.trap.jp/messages/{}", message.text, message.id);
+        let links: Vec<_> = message.links("https://q.trap.jp").collect();
+        assert_eq!(
+            links[0].reference,
+            Reference::Message(message.id.clone().into())
+        );
+    }
+
+    #[test]
+    fn text_diff_lines() {
+        let diff = text_diff("a\nb\nc\nd", "a\nc\nx\nd\ne");
+        assert_eq!(
+            diff.lines,
+            [
+                DiffLine::Unchanged("a".to_string()),
+                DiffLine::Removed("b".to_string()),
+                DiffLine::Unchanged("c".to_string()),
+                DiffLine::Added("x".to_string()),
+                DiffLine::Unchanged("d".to_string()),
+                DiffLine::Added("e".to_string()),
+            ]
+        );
+        assert!(!diff.is_unchanged());
+        assert!(text_diff("a\nb", "a\nb").is_unchanged());
+        assert_eq!(text_diff("", "a").lines, [DiffLine::Added("a".to_string())]);
+    }
+}
@@ -315,6 +315,8 @@ mod tests {
 
     test_parse_payload! {"user", UserCreated}
 
+    test_parse_payload! {"user", UserActivated}
+
     test_parse_payload! {"stamp", StampCreated}
 
     test_parse_payload! {"tag", TagAdded}
@@ -6,7 +6,6 @@ use crate::macros::{all_events, payloads_impl_for_kinds};
 
 mod channel;
 mod message;
-pub(crate) mod serde;
 mod stamp;
 mod system;
 mod tag;
@@ -23,7 +22,7 @@ pub use message::{
 pub use stamp::StampCreatedPayload;
 pub use system::{JoinedPayload, LeftPayload, PingPayload};
 pub use tag::{TagAddedPayload, TagRemovedPayload};
-pub use user::UserCreatedPayload;
+pub use user::{UserActivatedPayload, UserCreatedPayload};
 pub use user_group::{
     UserGroupAdminAddedPayload, UserGroupAdminRemovedPayload, UserGroupCreatedPayload,
     UserGroupDeletedPayload, UserGroupMemberAddedPayload, UserGroupMemberRemovedPayload,
@@ -102,6 +101,22 @@ mod tests {
         assert_eq!(payload, data.parse().unwrap());
     }
 
+    #[test]
+    fn user_activated_convert() {
+        let data = read_to_string("testdata/user/user_activated.json").unwrap();
+        let payload: UserActivatedPayload = data.parse().unwrap();
+        let payload: UserCreatedPayload = payload.into();
+        assert_eq!(payload, data.parse().unwrap());
+    }
+
+    #[test]
+    fn user_created_convert() {
+        let data = read_to_string("testdata/user/user_created.json").unwrap();
+        let payload: UserCreatedPayload = data.parse().unwrap();
+        let payload: UserActivatedPayload = payload.into();
+        assert_eq!(payload, data.parse().unwrap());
+    }
+
     #[test]
     fn user_group_updated_convert() {
         let data = read_to_string("testdata/user-group/user_group_updated.json").unwrap();
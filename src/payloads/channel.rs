@@ -5,7 +5,7 @@
 
 use serde::{Deserialize, Serialize};
 
-use super::types::{Channel, TimeStamp, User};
+use super::types::{Channel, Timestamp, User};
 
 /// `CHANNEL_CREATED`ペイロード
 /// - [traQの型定義](https://github.com/traPtitech/traQ/blob/d2bc98f1e0e68f4acc371eb78e6a49a167446761/service/bot/event/payload/ev_channel_created.go#L9-L13)
@@ -39,10 +39,11 @@ use super::types::{Channel, TimeStamp, User};
 /// # }
 /// ```
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "schemars", schemars(transform = crate::schema::trim_description))]
 #[serde(rename_all = "camelCase")]
 pub struct ChannelCreatedPayload {
-    #[serde(with = "crate::payloads::serde::timestamp")]
-    pub event_time: TimeStamp,
+    pub event_time: Timestamp,
     pub channel: Channel,
 }
 
@@ -86,10 +87,11 @@ pub struct ChannelCreatedPayload {
 /// # }
 /// ```
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "schemars", schemars(transform = crate::schema::trim_description))]
 #[serde(rename_all = "camelCase")]
 pub struct ChannelTopicChangedPayload {
-    #[serde(with = "crate::payloads::serde::timestamp")]
-    pub event_time: TimeStamp,
+    pub event_time: Timestamp,
     pub channel: Channel,
     pub topic: String,
     pub updater: User,
@@ -8,9 +8,13 @@
 //! - [`DirectMessageUpdatedPayload`](DirectMessageUpdatedPayload)
 //! - [`BotMessageStampsUpdatedPayload`](BotMessageStampsUpdatedPayload)
 
+use std::collections::{BTreeMap, BTreeSet};
+
 use serde::{Deserialize, Serialize};
 
-use super::types::{DeletedDirectMessage, DeletedMessage, Message, MessageStamp, TimeStamp, Uuid};
+use super::types::{
+    DeletedDirectMessage, DeletedMessage, Message, MessageStamp, StampsDiff, Timestamp, Uuid,
+};
 
 /// `MESSAGE_CREATED`ペイロード
 /// - [traQの型定義](https://github.com/traPtitech/traQ/blob/d2bc98f1e0e68f4acc371eb78e6a49a167446761/service/bot/event/payload/ev_message_created.go#L10-L14)
@@ -51,10 +55,11 @@ use super::types::{DeletedDirectMessage, DeletedMessage, Message, MessageStamp,
 /// # }
 /// ```
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "schemars", schemars(transform = crate::schema::trim_description))]
 #[serde(rename_all = "camelCase")]
 pub struct MessageCreatedPayload {
-    #[serde(with = "crate::payloads::serde::timestamp")]
-    pub event_time: TimeStamp,
+    pub event_time: Timestamp,
     pub message: Message,
 }
 
@@ -92,10 +97,11 @@ impl From<DirectMessageCreatedPayload> for MessageCreatedPayload {
 /// # }
 /// ```
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "schemars", schemars(transform = crate::schema::trim_description))]
 #[serde(rename_all = "camelCase")]
 pub struct MessageDeletedPayload {
-    #[serde(with = "crate::payloads::serde::timestamp")]
-    pub event_time: TimeStamp,
+    pub event_time: Timestamp,
     pub message: DeletedMessage,
 }
 
@@ -138,10 +144,11 @@ pub struct MessageDeletedPayload {
 /// # }
 /// ```
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "schemars", schemars(transform = crate::schema::trim_description))]
 #[serde(rename_all = "camelCase")]
 pub struct MessageUpdatedPayload {
-    #[serde(with = "crate::payloads::serde::timestamp")]
-    pub event_time: TimeStamp,
+    pub event_time: Timestamp,
     pub message: Message,
 }
 
@@ -197,10 +204,11 @@ impl From<DirectMessageUpdatedPayload> for MessageUpdatedPayload {
 /// # }
 /// ```
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "schemars", schemars(transform = crate::schema::trim_description))]
 #[serde(rename_all = "camelCase")]
 pub struct DirectMessageCreatedPayload {
-    #[serde(with = "crate::payloads::serde::timestamp")]
-    pub event_time: TimeStamp,
+    pub event_time: Timestamp,
     pub message: Message,
 }
 
@@ -239,10 +247,11 @@ impl From<MessageCreatedPayload> for DirectMessageCreatedPayload {
 /// # }
 /// ```
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "schemars", schemars(transform = crate::schema::trim_description))]
 #[serde(rename_all = "camelCase")]
 pub struct DirectMessageDeletedPayload {
-    #[serde(with = "crate::payloads::serde::timestamp")]
-    pub event_time: TimeStamp,
+    pub event_time: Timestamp,
     pub message: DeletedDirectMessage,
 }
 
@@ -285,10 +294,11 @@ pub struct DirectMessageDeletedPayload {
 /// # }
 /// ```
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "schemars", schemars(transform = crate::schema::trim_description))]
 #[serde(rename_all = "camelCase")]
 pub struct DirectMessageUpdatedPayload {
-    #[serde(with = "crate::payloads::serde::timestamp")]
-    pub event_time: TimeStamp,
+    pub event_time: Timestamp,
     pub message: Message,
 }
 
@@ -346,17 +356,85 @@ impl From<MessageUpdatedPayload> for DirectMessageUpdatedPayload {
 /// # }
 /// ```
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "schemars", schemars(transform = crate::schema::trim_description))]
 #[serde(rename_all = "camelCase")]
 pub struct BotMessageStampsUpdatedPayload {
-    #[serde(with = "crate::payloads::serde::timestamp")]
-    pub event_time: TimeStamp,
+    pub event_time: Timestamp,
+    #[cfg_attr(feature = "schemars", schemars(with = "crate::schema::UuidSchema"))]
     pub message_id: Uuid,
     pub stamps: Vec<MessageStamp>,
 }
 
+impl BotMessageStampsUpdatedPayload {
+    /// スタンプごとに、押された回数の合計を返します。
+    #[must_use]
+    pub fn stamp_totals(&self) -> BTreeMap<&Uuid, i64> {
+        self.stamps.iter().fold(BTreeMap::new(), |mut totals, s| {
+            *totals.entry(&s.stamp_id).or_default() += i64::from(s.count);
+            totals
+        })
+    }
+
+    /// ユーザーごとに、押したスタンプを返します。
+    #[must_use]
+    pub fn stamps_by_user(&self) -> BTreeMap<&Uuid, Vec<&MessageStamp>> {
+        self.stamps.iter().fold(BTreeMap::new(), |mut stamps, s| {
+            stamps.entry(&s.user_id).or_default().push(s);
+            stamps
+        })
+    }
+
+    /// スタンプを押したユーザーを重複無く返します。
+    #[must_use]
+    pub fn reactors(&self) -> BTreeSet<&Uuid> {
+        self.stamps.iter().map(|s| &s.user_id).collect()
+    }
+
+    /// 同じメッセージに対する`old`から`new`への差分を計算します。
+    ///
+    /// `message_id`が異なる場合は`None`を返します。
+    ///
+    /// ## Example
+    /// ```
+    /// # fn main() -> Result<(), serde_json::Error> {
+    /// use traq_bot_http::payloads::BotMessageStampsUpdatedPayload;
+    /// let old = r##"{
+    ///     "eventTime": "2020-10-17T03:35:17.9Z",
+    ///     "messageId": "200b6600-b2cd-4c1e-b366-9c40308cc087",
+    ///     "stamps": []
+    /// }"##;
+    /// let new = r##"{
+    ///     "eventTime": "2020-10-17T03:35:34.5326265Z",
+    ///     "messageId": "200b6600-b2cd-4c1e-b366-9c40308cc087",
+    ///     "stamps": [
+    ///         {
+    ///             "stampId": "1cd58034-8998-4b1c-afe4-fcd591354a97",
+    ///             "userId": "b80551a5-2768-4d29-ad78-8e0e92330c8d",
+    ///             "count": 1,
+    ///             "createdAt": "2020-10-17T03:35:17.89545Z",
+    ///             "updatedAt": "2020-10-17T03:35:34Z"
+    ///         }
+    ///     ]
+    /// }"##;
+    /// let old: BotMessageStampsUpdatedPayload = old.parse()?;
+    /// let new: BotMessageStampsUpdatedPayload = new.parse()?;
+    /// let diff = BotMessageStampsUpdatedPayload::diff(&old, &new).unwrap();
+    /// assert_eq!(diff.added, new.stamps);
+    /// assert!(diff.removed.is_empty());
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn diff(old: &Self, new: &Self) -> Option<StampsDiff> {
+        (old.message_id == new.message_id).then(|| StampsDiff::new(&old.stamps, &new.stamps))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::payloads::types::{Change, StampCountChange};
     use crate::test_utils::{embedded_takashi_trap, takashi_trap, timestamp, uuid};
 
     use std::fs::read_to_string;
@@ -546,4 +624,63 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn bot_message_stamps_aggregation() {
+        let stamp = |stamp_id, user_id, count| MessageStamp {
+            stamp_id: uuid(stamp_id),
+            user_id: uuid(user_id),
+            count,
+            created_at: timestamp("2020-10-17T03:35:17.89545Z"),
+            updated_at: timestamp("2020-10-17T03:35:34Z"),
+        };
+        let (s1, s2) = (
+            "1cd58034-8998-4b1c-afe4-fcd591354a97",
+            "6fc62b49-dea0-45b8-8c0c-38035082b111",
+        );
+        let (u1, u2, u3) = (
+            "b80551a5-2768-4d29-ad78-8e0e92330c8d",
+            "dfdff0c9-5de0-46ee-9721-2525e8bb3d45",
+            "8e6a088f-9274-42c0-bb20-cee7913d144b",
+        );
+        let old = BotMessageStampsUpdatedPayload {
+            event_time: timestamp("2020-10-17T03:35:34.5326265Z"),
+            message_id: uuid("200b6600-b2cd-4c1e-b366-9c40308cc087"),
+            stamps: vec![stamp(s1, u1, 2), stamp(s1, u2, 1), stamp(s2, u1, 3)],
+        };
+        assert_eq!(
+            old.stamp_totals(),
+            BTreeMap::from([(&uuid(s1), 3), (&uuid(s2), 3)])
+        );
+        let by_user = old.stamps_by_user();
+        assert_eq!(by_user[&uuid(u1)], [&old.stamps[0], &old.stamps[2]]);
+        assert_eq!(by_user[&uuid(u2)], [&old.stamps[1]]);
+        assert_eq!(old.reactors(), BTreeSet::from([&uuid(u1), &uuid(u2)]));
+
+        let new = BotMessageStampsUpdatedPayload {
+            stamps: vec![stamp(s1, u1, 5), stamp(s2, u1, 3), stamp(s2, u3, 1)],
+            ..old.clone()
+        };
+        let diff = BotMessageStampsUpdatedPayload::diff(&old, &new).unwrap();
+        assert_eq!(
+            diff,
+            StampsDiff {
+                added: vec![stamp(s2, u3, 1)],
+                removed: vec![stamp(s1, u2, 1)],
+                count_changed: vec![StampCountChange {
+                    stamp_id: uuid(s1),
+                    user_id: uuid(u1),
+                    count: Change { old: 2, new: 5 },
+                }],
+            }
+        );
+        assert!(BotMessageStampsUpdatedPayload::diff(&old, &old)
+            .unwrap()
+            .is_empty());
+        let other = BotMessageStampsUpdatedPayload {
+            message_id: uuid("bc9106b3-f9b2-4eca-9ba1-72b39b40954e"),
+            ..new
+        };
+        assert_eq!(BotMessageStampsUpdatedPayload::diff(&old, &other), None);
+    }
 }
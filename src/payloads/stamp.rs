@@ -4,7 +4,7 @@
 
 use serde::{Deserialize, Serialize};
 
-use super::types::{TimeStamp, User, Uuid};
+use super::types::{Timestamp, User, Uuid};
 
 /// `STAMP_CREATED`ペイロード
 /// - [traQの型定義](https://github.com/traPtitech/traQ/blob/d2bc98f1e0e68f4acc371eb78e6a49a167446761/service/bot/event/payload/ev_stamp_created.go#L11-L18)
@@ -33,12 +33,15 @@ use super::types::{TimeStamp, User, Uuid};
 /// # }
 /// ```
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "schemars", schemars(transform = crate::schema::trim_description))]
 #[serde(rename_all = "camelCase")]
 pub struct StampCreatedPayload {
-    #[serde(with = "crate::payloads::serde::timestamp")]
-    pub event_time: TimeStamp,
+    pub event_time: Timestamp,
+    #[cfg_attr(feature = "schemars", schemars(with = "crate::schema::UuidSchema"))]
     pub id: Uuid,
     pub name: String,
+    #[cfg_attr(feature = "schemars", schemars(with = "crate::schema::UuidSchema"))]
     pub file_id: Uuid,
     pub creator: User,
 }
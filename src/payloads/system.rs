@@ -6,7 +6,7 @@
 
 use serde::{Deserialize, Serialize};
 
-use super::types::{Channel, TimeStamp};
+use super::types::{Channel, Timestamp};
 
 /// `PING`ペイロード
 /// - [traQの型定義](https://github.com/traPtitech/traQ/blob/d2bc98f1e0e68f4acc371eb78e6a49a167446761/service/bot/event/payload/ev_ping.go#L5-L8)
@@ -25,10 +25,11 @@ use super::types::{Channel, TimeStamp};
 /// # }
 /// ```
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "schemars", schemars(transform = crate::schema::trim_description))]
 #[serde(rename_all = "camelCase")]
 pub struct PingPayload {
-    #[serde(with = "crate::payloads::serde::timestamp")]
-    pub event_time: TimeStamp,
+    pub event_time: Timestamp,
 }
 
 /// `JOINED`ペイロード
@@ -63,10 +64,11 @@ pub struct PingPayload {
 /// # }
 /// ```
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "schemars", schemars(transform = crate::schema::trim_description))]
 #[serde(rename_all = "camelCase")]
 pub struct JoinedPayload {
-    #[serde(with = "crate::payloads::serde::timestamp")]
-    pub event_time: TimeStamp,
+    pub event_time: Timestamp,
     pub channel: Channel,
 }
 
@@ -115,10 +117,11 @@ impl From<LeftPayload> for JoinedPayload {
 /// # }
 /// ```
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "schemars", schemars(transform = crate::schema::trim_description))]
 #[serde(rename_all = "camelCase")]
 pub struct LeftPayload {
-    #[serde(with = "crate::payloads::serde::timestamp")]
-    pub event_time: TimeStamp,
+    pub event_time: Timestamp,
     pub channel: Channel,
 }
 
@@ -5,7 +5,7 @@
 
 use serde::{Deserialize, Serialize};
 
-use super::types::{TimeStamp, Uuid};
+use super::types::{Timestamp, Uuid};
 
 /// `TAG_ADDED`ペイロード
 /// - [traQの型定義](https://github.com/traPtitech/traQ/blob/d2bc98f1e0e68f4acc371eb78e6a49a167446761/service/bot/event/payload/ev_tag_added.go#L11-L16)
@@ -26,10 +26,12 @@ use super::types::{TimeStamp, Uuid};
 /// # }
 /// ```
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "schemars", schemars(transform = crate::schema::trim_description))]
 #[serde(rename_all = "camelCase")]
 pub struct TagAddedPayload {
-    #[serde(with = "crate::payloads::serde::timestamp")]
-    pub event_time: TimeStamp,
+    pub event_time: Timestamp,
+    #[cfg_attr(feature = "schemars", schemars(with = "crate::schema::UuidSchema"))]
     pub tag_id: Uuid,
     pub tag: String,
 }
@@ -68,10 +70,12 @@ impl From<TagRemovedPayload> for TagAddedPayload {
 /// # }
 /// ```
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "schemars", schemars(transform = crate::schema::trim_description))]
 #[serde(rename_all = "camelCase")]
 pub struct TagRemovedPayload {
-    #[serde(with = "crate::payloads::serde::timestamp")]
-    pub event_time: TimeStamp,
+    pub event_time: Timestamp,
+    #[cfg_attr(feature = "schemars", schemars(with = "crate::schema::UuidSchema"))]
     pub tag_id: Uuid,
     pub tag: String,
 }
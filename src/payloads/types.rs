@@ -2,38 +2,75 @@
 
 use serde::{Deserialize, Serialize};
 
-#[cfg(feature = "chrono")]
-use chrono::{DateTime, Utc};
+use crate::macros::payload_impl;
 
-#[cfg(not(feature = "chrono"))]
-#[cfg(feature = "time")]
-use time::OffsetDateTime;
+mod channel_path;
+mod message_stamp;
+mod timestamp;
+mod user_group;
 
-use crate::macros::payload_impl;
+pub use channel_path::{ChannelPath, ChannelPathError};
+pub use message_stamp::{StampCountChange, StampsDiff};
+pub use timestamp::{Timestamp, TimestampError};
+pub use user_group::{RoleChange, UserGroupDiff, UserGroupRole, UserGroupType};
+
+// タイムスタンプ型のfeatureは`chrono`, `time`, `jiff`の順に優先される
 
 #[cfg(feature = "chrono")]
 /// タイムスタンプ([RFC3339 format](https://tools.ietf.org/html/rfc3339#section-5.6))を表現する型
 ///
 /// デフォルトでは[`String`](https://doc.rust-lang.org/stable/alloc/string/struct.String.html)型、
-/// `time` featureで[`time::OffsetDateTime`](https://docs.rs/time/latest/time/struct.OffsetDateTime.html)型になる
-pub type TimeStamp = DateTime<Utc>;
+/// `chrono` featureで[`chrono::DateTime<chrono::Utc>`](https://docs.rs/chrono/latest/chrono/struct.DateTime.html)型、
+/// `time` featureで[`time::OffsetDateTime`](https://docs.rs/time/latest/time/struct.OffsetDateTime.html)型、
+/// `jiff` featureで[`jiff::Timestamp`](https://docs.rs/jiff/latest/jiff/struct.Timestamp.html)型になる
+///
+/// *`chrono`, `time`, `jiff`の順に優先
+///
+/// ペイロードではfeatureに依存しない[`Timestamp`]が使われます。`Timestamp`からは[`Into`]で変換できます。
+pub type TimeStamp = chrono::DateTime<chrono::Utc>;
 
 #[cfg(not(feature = "chrono"))]
 #[cfg(feature = "time")]
 /// タイムスタンプ([RFC3339 format](https://tools.ietf.org/html/rfc3339#section-5.6))を表現する型
 ///
 /// デフォルトでは[`String`](https://doc.rust-lang.org/stable/alloc/string/struct.String.html)型、
-/// `chrono` featureで[`chrono::DateTime<chrono::Utc>`](https://docs.rs/chrono/latest/chrono/struct.DateTime.html)型になる
-pub type TimeStamp = OffsetDateTime;
+/// `chrono` featureで[`chrono::DateTime<chrono::Utc>`](https://docs.rs/chrono/latest/chrono/struct.DateTime.html)型、
+/// `time` featureで[`time::OffsetDateTime`](https://docs.rs/time/latest/time/struct.OffsetDateTime.html)型、
+/// `jiff` featureで[`jiff::Timestamp`](https://docs.rs/jiff/latest/jiff/struct.Timestamp.html)型になる
+///
+/// *`chrono`, `time`, `jiff`の順に優先
+///
+/// ペイロードではfeatureに依存しない[`Timestamp`]が使われます。`Timestamp`からは[`Into`]で変換できます。
+pub type TimeStamp = time::OffsetDateTime;
+
+#[cfg(not(feature = "chrono"))]
+#[cfg(not(feature = "time"))]
+#[cfg(feature = "jiff")]
+/// タイムスタンプ([RFC3339 format](https://tools.ietf.org/html/rfc3339#section-5.6))を表現する型
+///
+/// デフォルトでは[`String`](https://doc.rust-lang.org/stable/alloc/string/struct.String.html)型、
+/// `chrono` featureで[`chrono::DateTime<chrono::Utc>`](https://docs.rs/chrono/latest/chrono/struct.DateTime.html)型、
+/// `time` featureで[`time::OffsetDateTime`](https://docs.rs/time/latest/time/struct.OffsetDateTime.html)型、
+/// `jiff` featureで[`jiff::Timestamp`](https://docs.rs/jiff/latest/jiff/struct.Timestamp.html)型になる
+///
+/// *`chrono`, `time`, `jiff`の順に優先
+///
+/// ペイロードではfeatureに依存しない[`Timestamp`]が使われます。`Timestamp`からは[`Into`]で変換できます。
+pub type TimeStamp = jiff::Timestamp;
 
 #[cfg(not(feature = "chrono"))]
 #[cfg(not(feature = "time"))]
+#[cfg(not(feature = "jiff"))]
 /// タイムスタンプ([RFC3339 format](https://tools.ietf.org/html/rfc3339#section-5.6))を表現する型
 ///
+/// デフォルトでは[`String`](https://doc.rust-lang.org/stable/alloc/string/struct.String.html)型、
+/// `chrono` featureで[`chrono::DateTime<chrono::Utc>`](https://docs.rs/chrono/latest/chrono/struct.DateTime.html)型、
 /// `time` featureで[`time::OffsetDateTime`](https://docs.rs/time/latest/time/struct.OffsetDateTime.html)型、
-/// `chrono` featureで[`chrono::DateTime<chrono::Utc>`](https://docs.rs/chrono/latest/chrono/struct.DateTime.html)型になる
+/// `jiff` featureで[`jiff::Timestamp`](https://docs.rs/jiff/latest/jiff/struct.Timestamp.html)型になる
 ///
-/// *`chrono` featureが優先
+/// *`chrono`, `time`, `jiff`の順に優先
+///
+/// ペイロードではfeatureに依存しない[`Timestamp`]が使われます。`Timestamp`からは[`Into`]で変換できます。
 pub type TimeStamp = String;
 
 #[cfg(feature = "uuid")]
@@ -48,6 +85,26 @@ pub type Uuid = uuid::Uuid;
 /// `uuid` featureで[`uuid::Uuid`](https://docs.rs/uuid/latest/uuid/struct.Uuid.html)型になる
 pub type Uuid = String;
 
+/// 値の変更前後の組
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Change<T> {
+    pub old: T,
+    pub new: T,
+}
+
+impl<T: PartialEq> Change<T> {
+    /// 値が異なる場合にのみ`Some`を返します。
+    pub(crate) fn of(old: &T, new: &T) -> Option<Self>
+    where
+        T: Clone,
+    {
+        (old != new).then(|| Self {
+            old: old.clone(),
+            new: new.clone(),
+        })
+    }
+}
+
 /// - [traQの型定義](https://github.com/traPtitech/traQ/blob/d2bc98f1e0e68f4acc371eb78e6a49a167446761/service/bot/event/payload/common.go#L69-L75)
 ///
 /// ## Example
@@ -67,11 +124,15 @@ pub type Uuid = String;
 /// # }
 /// ```
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "schemars", schemars(transform = crate::schema::trim_description))]
 #[serde(rename_all = "camelCase")]
 pub struct User {
+    #[cfg_attr(feature = "schemars", schemars(with = "crate::schema::UuidSchema"))]
     pub id: Uuid,
     pub name: String,
     pub display_name: String,
+    #[cfg_attr(feature = "schemars", schemars(with = "crate::schema::UuidSchema"))]
     pub icon_id: Uuid,
     pub bot: bool,
 }
@@ -105,17 +166,19 @@ payload_impl! {User}
 /// # }
 /// ```
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "schemars", schemars(transform = crate::schema::trim_description))]
 #[serde(rename_all = "camelCase")]
 pub struct Channel {
+    #[cfg_attr(feature = "schemars", schemars(with = "crate::schema::UuidSchema"))]
     pub id: Uuid,
     pub name: String,
     pub path: String,
+    #[cfg_attr(feature = "schemars", schemars(with = "crate::schema::UuidSchema"))]
     pub parent_id: Uuid,
     pub creator: User,
-    #[serde(with = "crate::payloads::serde::timestamp")]
-    pub created_at: TimeStamp,
-    #[serde(with = "crate::payloads::serde::timestamp")]
-    pub updated_at: TimeStamp,
+    pub created_at: Timestamp,
+    pub updated_at: Timestamp,
 }
 
 payload_impl! {Channel}
@@ -137,10 +200,13 @@ payload_impl! {Channel}
 /// # }
 /// ```
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "schemars", schemars(transform = crate::schema::trim_description))]
 #[serde(rename_all = "camelCase")]
 pub struct EmbeddedInfo {
     pub raw: String,
     pub r#type: String,
+    #[cfg_attr(feature = "schemars", schemars(with = "crate::schema::UuidSchema"))]
     pub id: Uuid,
 }
 
@@ -180,18 +246,20 @@ payload_impl! {EmbeddedInfo}
 /// # }
 /// ```
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "schemars", schemars(transform = crate::schema::trim_description))]
 #[serde(rename_all = "camelCase")]
 pub struct Message {
+    #[cfg_attr(feature = "schemars", schemars(with = "crate::schema::UuidSchema"))]
     pub id: Uuid,
     pub user: User,
+    #[cfg_attr(feature = "schemars", schemars(with = "crate::schema::UuidSchema"))]
     pub channel_id: Uuid,
     pub text: String,
     pub plain_text: String,
     pub embedded: Vec<EmbeddedInfo>,
-    #[serde(with = "crate::payloads::serde::timestamp")]
-    pub created_at: TimeStamp,
-    #[serde(with = "crate::payloads::serde::timestamp")]
-    pub updated_at: TimeStamp,
+    pub created_at: Timestamp,
+    pub updated_at: Timestamp,
 }
 
 payload_impl! {Message}
@@ -212,9 +280,13 @@ payload_impl! {Message}
 /// # }
 /// ```
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "schemars", schemars(transform = crate::schema::trim_description))]
 #[serde(rename_all = "camelCase")]
 pub struct DeletedMessage {
+    #[cfg_attr(feature = "schemars", schemars(with = "crate::schema::UuidSchema"))]
     pub id: Uuid,
+    #[cfg_attr(feature = "schemars", schemars(with = "crate::schema::UuidSchema"))]
     pub channel_id: Uuid,
 }
 
@@ -237,10 +309,15 @@ payload_impl! {DeletedMessage}
 /// # }
 /// ```
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "schemars", schemars(transform = crate::schema::trim_description))]
 #[serde(rename_all = "camelCase")]
 pub struct DeletedDirectMessage {
+    #[cfg_attr(feature = "schemars", schemars(with = "crate::schema::UuidSchema"))]
     pub id: Uuid,
+    #[cfg_attr(feature = "schemars", schemars(with = "crate::schema::UuidSchema"))]
     pub user_id: Uuid,
+    #[cfg_attr(feature = "schemars", schemars(with = "crate::schema::UuidSchema"))]
     pub channel_id: Uuid,
 }
 
@@ -265,24 +342,30 @@ payload_impl! {DeletedDirectMessage}
 /// # }
 /// ```
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "schemars", schemars(transform = crate::schema::trim_description))]
 #[serde(rename_all = "camelCase")]
 pub struct MessageStamp {
+    #[cfg_attr(feature = "schemars", schemars(with = "crate::schema::UuidSchema"))]
     pub stamp_id: Uuid,
+    #[cfg_attr(feature = "schemars", schemars(with = "crate::schema::UuidSchema"))]
     pub user_id: Uuid,
     pub count: i32,
-    #[serde(with = "crate::payloads::serde::timestamp")]
-    pub created_at: TimeStamp,
-    #[serde(with = "crate::payloads::serde::timestamp")]
-    pub updated_at: TimeStamp,
+    pub created_at: Timestamp,
+    pub updated_at: Timestamp,
 }
 
 payload_impl! {MessageStamp}
 
 /// [traQの定義](https://github.com/traPtitech/traQ/blob/a1aaf12d089a9033461d0f1fcabb69a92873a3b1/service/bot/event/payload/common.go#L92-L95)
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "schemars", schemars(transform = crate::schema::trim_description))]
 #[serde(rename_all = "camelCase")]
 pub struct GroupMember {
+    #[cfg_attr(feature = "schemars", schemars(with = "crate::schema::UuidSchema"))]
     pub group_id: Uuid,
+    #[cfg_attr(feature = "schemars", schemars(with = "crate::schema::UuidSchema"))]
     pub user_id: Uuid,
 }
 
@@ -304,9 +387,13 @@ payload_impl! {GroupMember}
 /// # }
 /// ```
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "schemars", schemars(transform = crate::schema::trim_description))]
 #[serde(rename_all = "camelCase")]
 pub struct UserGroupAdmin {
+    #[cfg_attr(feature = "schemars", schemars(with = "crate::schema::UuidSchema"))]
     pub group_id: Uuid,
+    #[cfg_attr(feature = "schemars", schemars(with = "crate::schema::UuidSchema"))]
     pub user_id: Uuid,
 }
 
@@ -347,11 +434,15 @@ impl From<GroupMember> for UserGroupAdmin {
 /// # }
 /// ```
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "schemars", schemars(transform = crate::schema::trim_description))]
 #[serde(rename_all = "camelCase")]
 pub struct UserGroupMember {
+    #[cfg_attr(feature = "schemars", schemars(with = "crate::schema::UuidSchema"))]
     pub group_id: Uuid,
+    #[cfg_attr(feature = "schemars", schemars(with = "crate::schema::UuidSchema"))]
     pub user_id: Uuid,
-    pub role: String,
+    pub role: UserGroupRole,
 }
 
 payload_impl! {UserGroupMember}
@@ -390,19 +481,72 @@ payload_impl! {UserGroupMember}
 /// # }
 /// ```
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "schemars", schemars(transform = crate::schema::trim_description))]
 #[serde(rename_all = "camelCase")]
 pub struct UserGroup {
+    #[cfg_attr(feature = "schemars", schemars(with = "crate::schema::UuidSchema"))]
     pub id: Uuid,
     pub name: String,
     pub description: String,
-    pub r#type: String,
+    pub r#type: UserGroupType,
+    #[cfg_attr(feature = "schemars", schemars(with = "crate::schema::UuidSchema"))]
     pub icon: Uuid,
     pub admins: Vec<UserGroupAdmin>,
     pub members: Vec<UserGroupMember>,
-    #[serde(with = "crate::payloads::serde::timestamp")]
-    pub created_at: TimeStamp,
-    #[serde(with = "crate::payloads::serde::timestamp")]
-    pub updated_at: TimeStamp,
+    pub created_at: Timestamp,
+    pub updated_at: Timestamp,
 }
 
 payload_impl! {UserGroup}
+
+#[cfg(test)]
+mod tests {
+    use std::any::TypeId;
+
+    use super::{TimeStamp, Timestamp};
+
+    // `TimeStamp`の型は`chrono`, `time`, `jiff`の順に優先される
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn timestamp_chrono() {
+        let expected = TypeId::of::<chrono::DateTime<chrono::Utc>>();
+        assert_eq!(TypeId::of::<TimeStamp>(), expected);
+    }
+
+    #[cfg(not(feature = "chrono"))]
+    #[cfg(feature = "time")]
+    #[test]
+    fn timestamp_time() {
+        assert_eq!(
+            TypeId::of::<TimeStamp>(),
+            TypeId::of::<time::OffsetDateTime>()
+        );
+    }
+
+    #[cfg(not(feature = "chrono"))]
+    #[cfg(not(feature = "time"))]
+    #[cfg(feature = "jiff")]
+    #[test]
+    fn timestamp_jiff() {
+        assert_eq!(TypeId::of::<TimeStamp>(), TypeId::of::<jiff::Timestamp>());
+    }
+
+    #[cfg(not(feature = "chrono"))]
+    #[cfg(not(feature = "time"))]
+    #[cfg(not(feature = "jiff"))]
+    #[test]
+    fn timestamp_string() {
+        assert_eq!(TypeId::of::<TimeStamp>(), TypeId::of::<String>());
+    }
+
+    // `jiff::Timestamp`への変換のみ失敗しうる
+    #[allow(clippy::unnecessary_fallible_conversions)]
+    #[test]
+    fn timestamp_nanoseconds() {
+        let timestamp: Timestamp = "2019-05-08T13:33:51.632149265Z".parse().unwrap();
+        let converted = TimeStamp::try_from(timestamp).unwrap();
+        assert_eq!(Timestamp::try_from(converted), Ok(timestamp));
+    }
+}
@@ -0,0 +1,243 @@
+//! チャンネルのパスを表す型
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use super::Channel;
+
+/// チャンネル名の最大文字数
+const MAX_NAME_LEN: usize = 20;
+
+/// [`ChannelPath`]の解釈に失敗した理由です。
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChannelPathError {
+    /// 先頭が`#`でない
+    MissingHash,
+    /// 空のチャンネル名を含む
+    EmptySegment,
+    /// チャンネル名に使用できない文字を含むか、長すぎる
+    InvalidSegment(String),
+}
+
+impl fmt::Display for ChannelPathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingHash => f.write_str("channel path must start with '#'"),
+            Self::EmptySegment => f.write_str("channel path contains an empty channel name"),
+            Self::InvalidSegment(name) => write!(f, "invalid channel name {name:?}"),
+        }
+    }
+}
+
+impl std::error::Error for ChannelPathError {}
+
+/// `#a/po`のようなチャンネルのパス
+///
+/// 各チャンネル名は1文字以上20文字以下の英数字, `-`, `_`からなります。
+///
+/// ## Example
+/// ```
+/// use traq_bot_http::payloads::types::ChannelPath;
+///
+/// let path: ChannelPath = "#a/po".parse().unwrap();
+/// assert_eq!(path.segments().collect::<Vec<_>>(), ["a", "po"]);
+/// assert_eq!(path.depth(), 2);
+/// assert_eq!(path.name(), "po");
+/// let parent = path.parent().unwrap();
+/// assert_eq!(parent.to_string(), "#a");
+/// assert!(path.starts_with(&parent));
+/// assert!(parent.parent().is_none());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct ChannelPath {
+    /// `#`を含むパス全体
+    path: String,
+}
+
+impl ChannelPath {
+    /// パスを解釈します。
+    ///
+    /// ## Errors
+    /// パスの形式が正しくない場合、[`ChannelPathError`]を返します。
+    pub fn new(path: &str) -> Result<Self, ChannelPathError> {
+        path.to_string().try_into()
+    }
+
+    fn validate(path: &str) -> Result<(), ChannelPathError> {
+        let segments = path
+            .strip_prefix('#')
+            .ok_or(ChannelPathError::MissingHash)?;
+        for segment in segments.split('/') {
+            if segment.is_empty() {
+                return Err(ChannelPathError::EmptySegment);
+            }
+            let valid = segment.len() <= MAX_NAME_LEN
+                && segment
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+            if !valid {
+                return Err(ChannelPathError::InvalidSegment(segment.to_string()));
+            }
+        }
+        Ok(())
+    }
+
+    /// `#`を含むパス全体を返します。
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.path
+    }
+
+    /// 根から順にチャンネル名を列挙します。
+    #[must_use]
+    pub fn segments(&self) -> impl DoubleEndedIterator<Item = &str> + '_ {
+        self.path[1..].split('/')
+    }
+
+    /// チャンネル名を返します。
+    #[must_use]
+    pub fn name(&self) -> &str {
+        self.segments().next_back().unwrap_or_default()
+    }
+
+    /// 階層の深さを返します。`#a`は1、`#a/po`は2です。
+    #[must_use]
+    pub fn depth(&self) -> usize {
+        self.segments().count()
+    }
+
+    /// 親チャンネルのパスを返します。最上位のチャンネルでは`None`を返します。
+    #[must_use]
+    pub fn parent(&self) -> Option<Self> {
+        let (parent, _) = self.path.rsplit_once('/')?;
+        Some(Self {
+            path: parent.to_string(),
+        })
+    }
+
+    /// `self`が`base`自身、または`base`の子孫のチャンネルであれば`true`を返します。
+    #[must_use]
+    pub fn starts_with(&self, base: &Self) -> bool {
+        let mut segments = self.segments();
+        base.segments().all(|s| segments.next() == Some(s))
+    }
+
+    /// 子チャンネル`name`のパスを返します。
+    ///
+    /// ## Errors
+    /// `name`がチャンネル名として正しくない場合、[`ChannelPathError`]を返します。
+    pub fn join(&self, name: &str) -> Result<Self, ChannelPathError> {
+        if name.contains('/') {
+            return Err(ChannelPathError::InvalidSegment(name.to_string()));
+        }
+        format!("{}/{name}", self.path).try_into()
+    }
+}
+
+impl TryFrom<String> for ChannelPath {
+    type Error = ChannelPathError;
+
+    fn try_from(path: String) -> Result<Self, Self::Error> {
+        Self::validate(&path)?;
+        Ok(Self { path })
+    }
+}
+
+impl From<ChannelPath> for String {
+    fn from(path: ChannelPath) -> Self {
+        path.path
+    }
+}
+
+impl FromStr for ChannelPath {
+    type Err = ChannelPathError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(s)
+    }
+}
+
+impl AsRef<str> for ChannelPath {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl fmt::Display for ChannelPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.path)
+    }
+}
+
+impl Channel {
+    /// [`Channel::path`]を[`ChannelPath`]として返します。
+    ///
+    /// ## Errors
+    /// パスの形式が正しくない場合、[`ChannelPathError`]を返します。
+    pub fn channel_path(&self) -> Result<ChannelPath, ChannelPathError> {
+        self.path.parse()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::channel_a_po;
+
+    fn path(s: &str) -> ChannelPath {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn parse_error() {
+        assert_eq!(ChannelPath::new("a/po"), Err(ChannelPathError::MissingHash));
+        assert_eq!(ChannelPath::new("#"), Err(ChannelPathError::EmptySegment));
+        assert_eq!(
+            ChannelPath::new("#a//po"),
+            Err(ChannelPathError::EmptySegment)
+        );
+        assert_eq!(ChannelPath::new("#a/"), Err(ChannelPathError::EmptySegment));
+        assert_eq!(
+            ChannelPath::new("#a/p o"),
+            Err(ChannelPathError::InvalidSegment("p o".to_string()))
+        );
+        assert_eq!(
+            ChannelPath::new("#aaaaaaaaaaaaaaaaaaaaa"),
+            Err(ChannelPathError::InvalidSegment("a".repeat(21)))
+        );
+    }
+
+    #[test]
+    fn hierarchy() {
+        let po = path("#a/po");
+        assert_eq!(po.depth(), 2);
+        assert_eq!(po.name(), "po");
+        assert_eq!(path("#a").name(), "a");
+        assert_eq!(po.parent(), Some(path("#a")));
+        assert!(po.starts_with(&po));
+        assert!(po.starts_with(&path("#a")));
+        assert!(!path("#a").starts_with(&po));
+        assert!(!path("#ab/po").starts_with(&path("#a")));
+        assert!(path("#a/po/x").starts_with(&po));
+        assert_eq!(po.join("x"), Ok(path("#a/po/x")));
+        assert!(po.join("x/y").is_err());
+    }
+
+    #[test]
+    fn serde() {
+        let po: ChannelPath = serde_json::from_str(r##""#a/po""##).unwrap();
+        assert_eq!(po, path("#a/po"));
+        assert_eq!(serde_json::to_string(&po).unwrap(), r##""#a/po""##);
+        assert!(serde_json::from_str::<ChannelPath>(r#""a/po""#).is_err());
+    }
+
+    #[test]
+    fn channel_accessor() {
+        let channel = channel_a_po();
+        assert_eq!(channel.channel_path(), Ok(path("#a/po")));
+    }
+}
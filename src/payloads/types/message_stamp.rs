@@ -0,0 +1,73 @@
+//! メッセージのスタンプに関する型
+
+use super::{Change, MessageStamp, Uuid};
+
+/// ユーザーが押したスタンプの回数の変更
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct StampCountChange {
+    pub stamp_id: Uuid,
+    pub user_id: Uuid,
+    pub count: Change<i32>,
+}
+
+/// 同じメッセージに対する2つのスタンプの状態の差分
+///
+/// [`BotMessageStampsUpdatedPayload::diff`]で得られます。
+/// 各要素は`(stamp_id, user_id)`の組で対応付けられ、
+/// 追加されたものは新しい方、削除・変更されたものは古い方での順序に従います。
+///
+/// [`BotMessageStampsUpdatedPayload::diff`]: crate::payloads::BotMessageStampsUpdatedPayload::diff
+#[must_use]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StampsDiff {
+    /// 新たに押されたスタンプ
+    pub added: Vec<MessageStamp>,
+    /// 全て取り消されたスタンプ
+    pub removed: Vec<MessageStamp>,
+    /// 押された回数が変わったスタンプ
+    pub count_changed: Vec<StampCountChange>,
+}
+
+impl StampsDiff {
+    /// 差分が無い場合に`true`を返します。
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.count_changed.is_empty()
+    }
+
+    /// `old`から`new`への差分を計算します。
+    pub(crate) fn new(old: &[MessageStamp], new: &[MessageStamp]) -> Self {
+        let find = |stamps: &'_ [MessageStamp], s: &MessageStamp| {
+            stamps
+                .iter()
+                .find(|t| t.stamp_id == s.stamp_id && t.user_id == s.user_id)
+                .map(|t| t.count)
+        };
+        let added = new
+            .iter()
+            .filter(|s| find(old, s).is_none())
+            .cloned()
+            .collect();
+        let removed = old
+            .iter()
+            .filter(|s| find(new, s).is_none())
+            .cloned()
+            .collect();
+        let count_changed = old
+            .iter()
+            .filter_map(|s| {
+                let count = Change::of(&s.count, &find(new, s)?)?;
+                Some(StampCountChange {
+                    stamp_id: s.stamp_id.clone(),
+                    user_id: s.user_id.clone(),
+                    count,
+                })
+            })
+            .collect();
+        Self {
+            added,
+            removed,
+            count_changed,
+        }
+    }
+}
@@ -0,0 +1,589 @@
+//! featureに依存しないタイムスタンプ型
+
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+const NANOS_PER_SEC: u32 = 1_000_000_000;
+const SECS_PER_DAY: i64 = 86_400;
+/// `0000-01-01T00:00:00Z`のUNIX時間
+const MIN_SECS: i64 = -62_167_219_200;
+/// `9999-12-31T23:59:59Z`のUNIX時間
+const MAX_SECS: i64 = 253_402_300_799;
+
+/// [`Timestamp`]の解釈・変換に失敗した理由です。
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimestampError {
+    /// [RFC3339 format](https://tools.ietf.org/html/rfc3339#section-5.6)として正しくない
+    InvalidFormat,
+    /// 表現できる範囲(`0000-01-01T00:00:00Z`から`9999-12-31T23:59:59.999999999Z`)の外にある
+    OutOfRange,
+}
+
+impl fmt::Display for TimestampError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidFormat => f.write_str("invalid RFC3339 timestamp"),
+            Self::OutOfRange => f.write_str("timestamp out of range"),
+        }
+    }
+}
+
+impl std::error::Error for TimestampError {}
+
+/// タイムスタンプ([RFC3339 format](https://tools.ietf.org/html/rfc3339#section-5.6))を表現する型
+///
+/// [`TimeStamp`]と異なり、featureによって型が変わりません。
+/// 時点のみを保持し、オフセットの情報は保持しません。
+/// 文字列にする際はUTC(`Z`)で、秒の小数部は必要な桁数だけ出力されます。
+///
+/// `time`, `chrono`, `jiff` featureでは、それぞれの型との相互変換が可能です。
+/// ただし[`SystemTime`] (環境による) と`jiff::Timestamp`は表現できる範囲が狭いため、これらへの変換は失敗することがあります。
+///
+/// ## Example
+/// ```
+/// use traq_bot_http::payloads::types::Timestamp;
+///
+/// let timestamp: Timestamp = "2019-05-08T22:33:51.6321+09:00".parse().unwrap();
+/// assert_eq!(timestamp.to_string(), "2019-05-08T13:33:51.6321Z");
+/// assert_eq!(timestamp.unix_seconds(), 1_557_322_431);
+/// assert_eq!(timestamp.subsec_nanos(), 632_100_000);
+/// ```
+///
+/// [`TimeStamp`]: crate::payloads::types::TimeStamp
+/// [`SystemTime`]: std::time::SystemTime
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Timestamp {
+    secs: i64,
+    nanos: u32,
+}
+
+impl Timestamp {
+    /// UNIX時間から作成します。
+    ///
+    /// ## Errors
+    /// `nanos`が10億以上の場合、または範囲外の場合は[`TimestampError::OutOfRange`]を返します。
+    pub fn from_unix(secs: i64, nanos: u32) -> Result<Self, TimestampError> {
+        if nanos >= NANOS_PER_SEC || !(MIN_SECS..=MAX_SECS).contains(&secs) {
+            return Err(TimestampError::OutOfRange);
+        }
+        Ok(Self { secs, nanos })
+    }
+
+    /// UNIX時間の秒の部分を返します。
+    #[must_use]
+    pub fn unix_seconds(&self) -> i64 {
+        self.secs
+    }
+
+    /// 秒未満の部分をナノ秒で返します。
+    #[must_use]
+    pub fn subsec_nanos(&self) -> u32 {
+        self.nanos
+    }
+
+    /// [RFC3339 format](https://tools.ietf.org/html/rfc3339#section-5.6)の文字列を解釈します。
+    ///
+    /// ## Errors
+    /// 形式が正しくない場合は[`TimestampError::InvalidFormat`]、
+    /// 範囲外の場合は[`TimestampError::OutOfRange`]を返します。
+    pub fn parse(s: &str) -> Result<Self, TimestampError> {
+        Parser(s.as_bytes()).timestamp()
+    }
+}
+
+#[cfg(any(feature = "arbitrary", feature = "proptest"))]
+impl Timestamp {
+    /// 生成するUNIX時間の秒の範囲
+    ///
+    /// どのfeatureの型にも変換できるよう、上限は`jiff::Timestamp::MAX`と同じ`9999-12-30T22:00:00Z`にする
+    pub(crate) const SECS_RANGE: std::ops::RangeInclusive<i64> = MIN_SECS..=253_402_207_200;
+    /// 秒の小数部として表現できるナノ秒の範囲
+    pub(crate) const NANOS_RANGE: std::ops::RangeInclusive<u32> = 0..=999_999_999;
+}
+
+/// RFC3339の`date-time`のパーサー
+struct Parser<'a>(&'a [u8]);
+
+impl Parser<'_> {
+    fn byte(&mut self) -> Result<u8, TimestampError> {
+        let (&b, rest) = self.0.split_first().ok_or(TimestampError::InvalidFormat)?;
+        self.0 = rest;
+        Ok(b)
+    }
+
+    fn expect(&mut self, candidates: &[u8]) -> Result<(), TimestampError> {
+        if candidates.contains(&self.byte()?) {
+            Ok(())
+        } else {
+            Err(TimestampError::InvalidFormat)
+        }
+    }
+
+    /// `digits`桁の10進数を読み、`max`以下であることを確認する
+    fn number(&mut self, digits: usize, max: u32) -> Result<u32, TimestampError> {
+        let mut n = 0;
+        for _ in 0..digits {
+            let b = self.byte()?;
+            if !b.is_ascii_digit() {
+                return Err(TimestampError::InvalidFormat);
+            }
+            n = n * 10 + u32::from(b - b'0');
+        }
+        if n > max {
+            return Err(TimestampError::InvalidFormat);
+        }
+        Ok(n)
+    }
+
+    /// `.`の後の小数部をナノ秒として読む。10桁目以降は切り捨てる
+    fn fraction(&mut self) -> Result<u32, TimestampError> {
+        let digits = self.0.iter().take_while(|b| b.is_ascii_digit()).count();
+        if digits == 0 {
+            return Err(TimestampError::InvalidFormat);
+        }
+        let (fraction, rest) = self.0.split_at(digits);
+        self.0 = rest;
+        let nanos = fraction
+            .iter()
+            .chain(std::iter::repeat(&b'0'))
+            .take(9)
+            .fold(0, |n, b| n * 10 + u32::from(b - b'0'));
+        Ok(nanos)
+    }
+
+    /// オフセットを秒で返す
+    fn offset(&mut self) -> Result<i64, TimestampError> {
+        let sign = match self.byte()? {
+            b'Z' | b'z' => return Ok(0),
+            b'+' => 1,
+            b'-' => -1,
+            _ => return Err(TimestampError::InvalidFormat),
+        };
+        let hour = self.number(2, 23)?;
+        self.expect(b":")?;
+        let minute = self.number(2, 59)?;
+        Ok(sign * i64::from(hour * 3600 + minute * 60))
+    }
+
+    fn timestamp(mut self) -> Result<Timestamp, TimestampError> {
+        let year = self.number(4, 9999)?;
+        self.expect(b"-")?;
+        let month = self.number(2, 12)?;
+        self.expect(b"-")?;
+        let day = self.number(2, 31)?;
+        self.expect(b"Tt ")?;
+        let hour = self.number(2, 23)?;
+        self.expect(b":")?;
+        let minute = self.number(2, 59)?;
+        self.expect(b":")?;
+        // うるう秒は次の秒の始まりとして扱う
+        let second = self.number(2, 60)?;
+        let nanos = match self.0.first() {
+            Some(b'.') => {
+                self.0 = &self.0[1..];
+                self.fraction()?
+            }
+            _ => 0,
+        };
+        let offset = self.offset()?;
+        if !self.0.is_empty() || month == 0 || day == 0 || day > days_in_month(year, month) {
+            return Err(TimestampError::InvalidFormat);
+        }
+        let days = days_from_civil(i64::from(year), month, day);
+        let secs = days * SECS_PER_DAY + i64::from(hour * 3600 + minute * 60 + second) - offset;
+        Timestamp::from_unix(secs, nanos)
+    }
+}
+
+fn is_leap_year(year: u32) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+fn days_in_month(year: u32, month: u32) -> u32 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// 1970-01-01からの日数を返す
+///
+/// <http://howardhinnant.github.io/date_algorithms.html#days_from_civil>
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let shifted_month = i64::from((month + 9) % 12);
+    let day_of_year = (153 * shifted_month + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// 1970-01-01からの日数を`(year, month, day)`にする
+///
+/// <http://howardhinnant.github.io/date_algorithms.html#civil_from_days>
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (year, month, day) = civil_from_days(self.secs.div_euclid(SECS_PER_DAY));
+        let secs = self.secs.rem_euclid(SECS_PER_DAY);
+        let (hour, minute, second) = (secs / 3600, secs / 60 % 60, secs % 60);
+        write!(
+            f,
+            "{year:04}-{month:02}-{day:02}T{hour:02}:{minute:02}:{second:02}"
+        )?;
+        if self.nanos != 0 {
+            let fraction = format!("{:09}", self.nanos);
+            write!(f, ".{}", fraction.trim_end_matches('0'))?;
+        }
+        f.write_str("Z")
+    }
+}
+
+impl fmt::Debug for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Timestamp")
+            .field(&format_args!("{self}"))
+            .finish()
+    }
+}
+
+impl FromStr for Timestamp {
+    type Err = TimestampError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl TryFrom<&str> for Timestamp {
+    type Error = TimestampError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        Self::parse(s)
+    }
+}
+
+impl TryFrom<String> for Timestamp {
+    type Error = TimestampError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        Self::parse(&s)
+    }
+}
+
+impl From<Timestamp> for String {
+    fn from(timestamp: Timestamp) -> Self {
+        timestamp.to_string()
+    }
+}
+
+impl Serialize for Timestamp {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Timestamp {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+
+        let s = String::deserialize(deserializer)?;
+        Self::parse(&s).map_err(D::Error::custom)
+    }
+}
+
+impl TryFrom<Timestamp> for SystemTime {
+    type Error = TimestampError;
+
+    fn try_from(timestamp: Timestamp) -> Result<Self, Self::Error> {
+        let Timestamp { secs, nanos } = timestamp;
+        let since_epoch = Duration::new(secs.unsigned_abs(), 0);
+        let time = if secs >= 0 {
+            UNIX_EPOCH.checked_add(since_epoch)
+        } else {
+            UNIX_EPOCH.checked_sub(since_epoch)
+        };
+        time.and_then(|t| t.checked_add(Duration::from_nanos(nanos.into())))
+            .ok_or(TimestampError::OutOfRange)
+    }
+}
+
+impl TryFrom<SystemTime> for Timestamp {
+    type Error = TimestampError;
+
+    fn try_from(time: SystemTime) -> Result<Self, Self::Error> {
+        let (secs, nanos) = match time.duration_since(UNIX_EPOCH) {
+            Ok(d) => (i64::try_from(d.as_secs()), d.subsec_nanos()),
+            Err(e) => {
+                let d = e.duration();
+                let secs = i64::try_from(d.as_secs()).map(|s| -s);
+                match d.subsec_nanos() {
+                    0 => (secs, 0),
+                    n => (secs.map(|s| s - 1), NANOS_PER_SEC - n),
+                }
+            }
+        };
+        Self::from_unix(secs.map_err(|_| TimestampError::OutOfRange)?, nanos)
+    }
+}
+
+#[cfg(feature = "time")]
+impl From<Timestamp> for time::OffsetDateTime {
+    fn from(timestamp: Timestamp) -> Self {
+        let nanos =
+            i128::from(timestamp.secs) * i128::from(NANOS_PER_SEC) + i128::from(timestamp.nanos);
+        Self::from_unix_timestamp_nanos(nanos).expect("Timestamp is always in range")
+    }
+}
+
+#[cfg(feature = "time")]
+impl TryFrom<time::OffsetDateTime> for Timestamp {
+    type Error = TimestampError;
+
+    fn try_from(time: time::OffsetDateTime) -> Result<Self, Self::Error> {
+        Self::from_unix(time.unix_timestamp(), time.nanosecond())
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl From<Timestamp> for chrono::DateTime<chrono::Utc> {
+    fn from(timestamp: Timestamp) -> Self {
+        Self::from_timestamp(timestamp.secs, timestamp.nanos).expect("Timestamp is always in range")
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl TryFrom<chrono::DateTime<chrono::Utc>> for Timestamp {
+    type Error = TimestampError;
+
+    fn try_from(time: chrono::DateTime<chrono::Utc>) -> Result<Self, Self::Error> {
+        Self::from_unix(time.timestamp(), time.timestamp_subsec_nanos())
+    }
+}
+
+// `jiff::Timestamp::MAX`は`9999-12-30T22:00:00.999999999Z`のため、最後の約26時間は変換できない
+#[cfg(feature = "jiff")]
+impl TryFrom<Timestamp> for jiff::Timestamp {
+    type Error = TimestampError;
+
+    fn try_from(timestamp: Timestamp) -> Result<Self, Self::Error> {
+        let nanos = i32::try_from(timestamp.nanos).expect("nanos is less than 1_000_000_000");
+        Self::new(timestamp.secs, nanos).map_err(|_| TimestampError::OutOfRange)
+    }
+}
+
+#[cfg(feature = "jiff")]
+impl TryFrom<jiff::Timestamp> for Timestamp {
+    type Error = TimestampError;
+
+    fn try_from(time: jiff::Timestamp) -> Result<Self, Self::Error> {
+        // 負の時刻では`subsec_nanosecond`も負になる
+        let (secs, nanos) = match time.subsec_nanosecond() {
+            n if n < 0 => (time.as_second() - 1, n + 1_000_000_000),
+            n => (time.as_second(), n),
+        };
+        let nanos = u32::try_from(nanos).expect("nanos is non-negative");
+        Self::from_unix(secs, nanos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ts(s: &str) -> Timestamp {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn parse_and_display() {
+        let cases = [
+            ("1970-01-01T00:00:00Z", "1970-01-01T00:00:00Z", 0, 0),
+            (
+                "2019-05-08T13:33:51.632149265Z",
+                "2019-05-08T13:33:51.632149265Z",
+                1_557_322_431,
+                632_149_265,
+            ),
+            (
+                "2020-10-17T12:35:34.5326265+09:00",
+                "2020-10-17T03:35:34.5326265Z",
+                1_602_905_734,
+                532_626_500,
+            ),
+            (
+                "1969-12-31t23:59:59.0000000001z",
+                "1969-12-31T23:59:59Z",
+                -1,
+                0,
+            ),
+            (
+                "2024-02-29 00:00:00-00:30",
+                "2024-02-29T00:30:00Z",
+                1_709_166_600,
+                0,
+            ),
+            ("0000-01-01T00:00:00Z", "0000-01-01T00:00:00Z", MIN_SECS, 0),
+        ];
+        for (input, output, secs, nanos) in cases {
+            let timestamp = ts(input);
+            assert_eq!(timestamp.to_string(), output, "{input}");
+            assert_eq!(timestamp.unix_seconds(), secs, "{input}");
+            assert_eq!(timestamp.subsec_nanos(), nanos, "{input}");
+        }
+    }
+
+    #[test]
+    fn parse_error() {
+        let invalid = [
+            "",
+            "2019-05-08",
+            "2019-05-08T13:33:51",
+            "2019-05-08T13:33:51.Z",
+            "2019-05-08T13:33:51ZZ",
+            "2019-13-08T13:33:51Z",
+            "2019-02-29T13:33:51Z",
+            "2019-05-08T24:33:51Z",
+            "2019-05-08T13:33:51+0900",
+            "２０１９-05-08T13:33:51Z",
+        ];
+        for s in invalid {
+            assert_eq!(
+                Timestamp::parse(s),
+                Err(TimestampError::InvalidFormat),
+                "{s}"
+            );
+        }
+        assert_eq!(
+            Timestamp::parse("9999-12-31T23:59:59-01:00"),
+            Err(TimestampError::OutOfRange)
+        );
+    }
+
+    #[test]
+    fn serde() {
+        let timestamp: Timestamp =
+            serde_json::from_str(r#""2019-05-08T13:33:51.632149265Z""#).unwrap();
+        assert_eq!(timestamp, ts("2019-05-08T13:33:51.632149265Z"));
+        assert_eq!(
+            serde_json::to_string(&timestamp).unwrap(),
+            r#""2019-05-08T13:33:51.632149265Z""#
+        );
+        assert!(serde_json::from_str::<Timestamp>(r#""2019-05-08""#).is_err());
+    }
+
+    #[test]
+    fn system_time() {
+        for s in ["2019-05-08T13:33:51.632149265Z", "1969-12-31T23:59:58.5Z"] {
+            let time = SystemTime::try_from(ts(s)).unwrap();
+            assert_eq!(Timestamp::try_from(time), Ok(ts(s)));
+        }
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn time() {
+        use time::macros::datetime;
+
+        let time = datetime!(2019-05-08 13:33:51.632149265 UTC);
+        assert_eq!(
+            time::OffsetDateTime::from(ts("2019-05-08T13:33:51.632149265Z")),
+            time
+        );
+        assert_eq!(
+            Timestamp::try_from(datetime!(1969-12-31 23:59:58.5 UTC)),
+            Ok(ts("1969-12-31T23:59:58.5Z"))
+        );
+
+        let max = Timestamp::from_unix(MAX_SECS, NANOS_PER_SEC - 1).unwrap();
+        assert_eq!(
+            Timestamp::try_from(time::OffsetDateTime::from(max)),
+            Ok(max)
+        );
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn chrono() {
+        let time: chrono::DateTime<chrono::Utc> = "2019-05-08T13:33:51.632149265Z".parse().unwrap();
+        assert_eq!(
+            chrono::DateTime::<chrono::Utc>::from(ts("2019-05-08T13:33:51.632149265Z")),
+            time
+        );
+        let time: chrono::DateTime<chrono::Utc> = "1969-12-31T23:59:58.5Z".parse().unwrap();
+        assert_eq!(Timestamp::try_from(time), Ok(ts("1969-12-31T23:59:58.5Z")));
+
+        let max = Timestamp::from_unix(MAX_SECS, NANOS_PER_SEC - 1).unwrap();
+        assert_eq!(
+            Timestamp::try_from(chrono::DateTime::<chrono::Utc>::from(max)),
+            Ok(max)
+        );
+    }
+
+    #[cfg(all(feature = "jiff", any(feature = "arbitrary", feature = "proptest")))]
+    #[test]
+    fn generated_range() {
+        let (secs, nanos) = (Timestamp::SECS_RANGE, Timestamp::NANOS_RANGE);
+        for (secs, nanos) in [(*secs.start(), *nanos.start()), (*secs.end(), *nanos.end())] {
+            let timestamp = Timestamp::from_unix(secs, nanos).unwrap();
+            assert!(jiff::Timestamp::try_from(timestamp).is_ok());
+        }
+        assert_eq!(
+            *Timestamp::SECS_RANGE.end(),
+            jiff::Timestamp::MAX.as_second()
+        );
+    }
+
+    #[cfg(feature = "jiff")]
+    #[test]
+    fn jiff() {
+        let time: jiff::Timestamp = "2019-05-08T13:33:51.632149265Z".parse().unwrap();
+        assert_eq!(
+            jiff::Timestamp::try_from(ts("2019-05-08T13:33:51.632149265Z")),
+            Ok(time)
+        );
+        let time: jiff::Timestamp = "1969-12-31T23:59:58.5Z".parse().unwrap();
+        assert_eq!(Timestamp::try_from(time), Ok(ts("1969-12-31T23:59:58.5Z")));
+
+        // `jiff::Timestamp`の範囲の外
+        let max = Timestamp::from_unix(MAX_SECS, NANOS_PER_SEC - 1).unwrap();
+        assert_eq!(
+            jiff::Timestamp::try_from(max),
+            Err(TimestampError::OutOfRange)
+        );
+        let jiff_max = Timestamp::try_from(jiff::Timestamp::MAX).unwrap();
+        assert_eq!(
+            jiff::Timestamp::try_from(jiff_max),
+            Ok(jiff::Timestamp::MAX)
+        );
+        let min = Timestamp::from_unix(MIN_SECS, 0).unwrap();
+        assert_eq!(
+            jiff::Timestamp::try_from(min).map(Timestamp::try_from),
+            Ok(Ok(min))
+        );
+    }
+}
@@ -0,0 +1,487 @@
+//! ユーザーグループに関する型
+
+use std::convert::Infallible;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use super::{Change, UserGroup, UserGroupAdmin, UserGroupMember, Uuid};
+
+/// 既知の値を持つ文字列を表すenumを定義する
+///
+/// 既知の値以外は`Other(String)`となり、文字列との相互変換で元の値が保たれる
+macro_rules! string_enum {
+    (
+        $( #[$m:meta] )*
+        $v:vis enum $name:ident {
+            $(
+                $( #[$vm:meta] )*
+                $variant:ident = $value:literal,
+            )*
+        }
+    ) => {
+        $( #[$m] )*
+        #[derive(Debug, Clone, Default, Deserialize, Serialize)]
+        #[serde(from = "String", into = "String")]
+        $v enum $name {
+            $(
+                $( #[$vm] )*
+                $variant,
+            )*
+            /// 上記以外の値
+            Other(String),
+        }
+
+        impl $name {
+            /// 文字列としての値を返します。
+            #[must_use]
+            pub fn as_str(&self) -> &str {
+                match self {
+                    $( Self::$variant => $value, )*
+                    Self::Other(value) => value,
+                }
+            }
+        }
+
+        impl From<String> for $name {
+            fn from(value: String) -> Self {
+                match value.as_str() {
+                    $( $value => Self::$variant, )*
+                    _ => Self::Other(value),
+                }
+            }
+        }
+
+        impl From<&str> for $name {
+            fn from(value: &str) -> Self {
+                value.to_string().into()
+            }
+        }
+
+        impl From<$name> for String {
+            fn from(value: $name) -> Self {
+                match value {
+                    $name::Other(value) => value,
+                    value => value.as_str().to_string(),
+                }
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = Infallible;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                Ok(s.into())
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        // `Other`に既知の値が入っていても等しくなるよう、文字列としての値で比較する
+        impl PartialEq for $name {
+            fn eq(&self, other: &Self) -> bool {
+                self.as_str() == other.as_str()
+            }
+        }
+
+        impl Eq for $name {}
+
+        impl Hash for $name {
+            fn hash<H: Hasher>(&self, state: &mut H) {
+                self.as_str().hash(state);
+            }
+        }
+
+        impl PartialEq<str> for $name {
+            fn eq(&self, other: &str) -> bool {
+                self.as_str() == other
+            }
+        }
+
+        impl PartialEq<&str> for $name {
+            fn eq(&self, other: &&str) -> bool {
+                self.as_str() == *other
+            }
+        }
+    };
+}
+
+string_enum! {
+    /// ユーザーグループの種類
+    ///
+    /// - [traQの定義](https://github.com/traPtitech/traQ/blob/a1aaf12d089a9033461d0f1fcabb69a92873a3b1/model/user_group.go)
+    ///
+    /// ## Example
+    /// ```
+    /// use traq_bot_http::payloads::types::UserGroupType;
+    ///
+    /// assert_eq!(UserGroupType::from("grade"), UserGroupType::Grade);
+    /// assert_eq!(UserGroupType::from("ふがふが"), UserGroupType::Other("ふがふが".to_string()));
+    /// assert_eq!(String::from(UserGroupType::Grade), "grade");
+    /// ```
+    pub enum UserGroupType {
+        /// 種類が設定されていない (`""`)
+        #[default]
+        Unspecified = "",
+        /// 学年グループ (`"grade"`)
+        Grade = "grade",
+    }
+}
+
+string_enum! {
+    /// ユーザーグループのメンバーの役割
+    ///
+    /// - [traQの定義](https://github.com/traPtitech/traQ/blob/a1aaf12d089a9033461d0f1fcabb69a92873a3b1/model/user_group.go)
+    ///
+    /// ## Example
+    /// ```
+    /// use traq_bot_http::payloads::types::UserGroupRole;
+    ///
+    /// assert_eq!(UserGroupRole::from(""), UserGroupRole::Unspecified);
+    /// assert_eq!(UserGroupRole::from("leader"), UserGroupRole::Other("leader".to_string()));
+    /// ```
+    pub enum UserGroupRole {
+        /// 役割が設定されていない (`""`)
+        #[default]
+        Unspecified = "",
+    }
+}
+
+impl UserGroup {
+    /// ユーザー`user_id`がこのグループの管理者であれば`true`を返します。
+    #[must_use]
+    pub fn is_admin(&self, user_id: &Uuid) -> bool {
+        self.admins.iter().any(|a| &a.user_id == user_id)
+    }
+
+    /// ユーザー`user_id`がこのグループのメンバーであれば`true`を返します。
+    #[must_use]
+    pub fn is_member(&self, user_id: &Uuid) -> bool {
+        self.member(user_id).is_some()
+    }
+
+    /// ユーザー`user_id`のメンバー情報を返します。
+    #[must_use]
+    pub fn member(&self, user_id: &Uuid) -> Option<&UserGroupMember> {
+        self.members.iter().find(|m| &m.user_id == user_id)
+    }
+
+    /// ユーザー`user_id`のこのグループでの役割を返します。メンバーでない場合は`None`を返します。
+    #[must_use]
+    pub fn member_role(&self, user_id: &Uuid) -> Option<&UserGroupRole> {
+        self.member(user_id).map(|m| &m.role)
+    }
+
+    /// 役割が`role`であるメンバーを列挙します。
+    ///
+    /// ## Example
+    /// ```
+    /// # fn main() -> Result<(), serde_json::Error> {
+    /// use traq_bot_http::payloads::types::{UserGroup, UserGroupRole};
+    /// let group = r##"{
+    ///     "id": "f265bde2-04cc-4856-9008-3db1d953a539",
+    ///     "name": "fugafuga",
+    ///     "description": "FUGA_FUGA",
+    ///     "type": "",
+    ///     "icon": "81f6da0d-eaab-4c42-84ac-74f5111e1eaa",
+    ///     "admins": [],
+    ///     "members": [
+    ///         {
+    ///             "groupId": "f265bde2-04cc-4856-9008-3db1d953a539",
+    ///             "userId": "8e6a088f-9274-42c0-bb20-cee7913d144b",
+    ///             "role": "leader"
+    ///         },
+    ///         {
+    ///             "groupId": "f265bde2-04cc-4856-9008-3db1d953a539",
+    ///             "userId": "dfdff0c9-5de0-46ee-9721-2525e8bb3d45",
+    ///             "role": ""
+    ///         }
+    ///     ],
+    ///     "createdAt": "2023-08-25T04:04:32.912312Z",
+    ///     "updatedAt": "2023-08-25T04:04:32.912312Z"
+    /// }"##;
+    /// let group: UserGroup = group.parse()?;
+    /// let leaders: Vec<_> = group
+    ///     .members_with_role(&UserGroupRole::from("leader"))
+    ///     .map(|m| m.user_id.to_string())
+    ///     .collect();
+    /// assert_eq!(leaders, ["8e6a088f-9274-42c0-bb20-cee7913d144b"]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn members_with_role<'a>(
+        &'a self,
+        role: &'a UserGroupRole,
+    ) -> impl Iterator<Item = &'a UserGroupMember> + 'a {
+        self.members.iter().filter(move |m| &m.role == role)
+    }
+}
+
+/// メンバーの役割の変更
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RoleChange {
+    pub user_id: Uuid,
+    pub role: Change<UserGroupRole>,
+}
+
+/// [`UserGroup::diff`]で得られる、2つの[`UserGroup`]の差分
+///
+/// メンバーと管理者の並びは、追加されたものは新しい方、
+/// 削除・変更されたものは古い方での順序に従います。
+#[must_use]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UserGroupDiff {
+    pub name: Option<Change<String>>,
+    pub description: Option<Change<String>>,
+    pub r#type: Option<Change<UserGroupType>>,
+    pub icon: Option<Change<Uuid>>,
+    pub members_added: Vec<UserGroupMember>,
+    pub members_removed: Vec<UserGroupMember>,
+    pub members_role_changed: Vec<RoleChange>,
+    pub admins_added: Vec<UserGroupAdmin>,
+    pub admins_removed: Vec<UserGroupAdmin>,
+}
+
+impl UserGroupDiff {
+    /// 差分が無い場合に`true`を返します。
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+}
+
+impl UserGroup {
+    /// `old`から`new`への変更を計算します。
+    ///
+    /// `id`, `created_at`, `updated_at`は比較されません。
+    ///
+    /// ## Example
+    /// ```
+    /// # fn main() -> Result<(), serde_json::Error> {
+    /// use traq_bot_http::payloads::types::UserGroup;
+    /// let old = r##"{
+    ///     "id": "f265bde2-04cc-4856-9008-3db1d953a539",
+    ///     "name": "fugafuga",
+    ///     "description": "FUGA_FUGA",
+    ///     "type": "",
+    ///     "icon": "81f6da0d-eaab-4c42-84ac-74f5111e1eaa",
+    ///     "admins": [],
+    ///     "members": [],
+    ///     "createdAt": "2023-08-25T04:04:32.912312Z",
+    ///     "updatedAt": "2023-08-25T04:04:32.912312Z"
+    /// }"##;
+    /// let old: UserGroup = old.parse()?;
+    /// let mut new = old.clone();
+    /// new.name = "hogehoge".to_string();
+    /// let diff = UserGroup::diff(&old, &new);
+    /// let name = diff.name.unwrap();
+    /// assert_eq!((name.old.as_str(), name.new.as_str()), ("fugafuga", "hogehoge"));
+    /// assert!(diff.members_added.is_empty());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn diff(old: &Self, new: &Self) -> UserGroupDiff {
+        let members_added = new
+            .members
+            .iter()
+            .filter(|m| !old.is_member(&m.user_id))
+            .cloned()
+            .collect();
+        let members_removed = old
+            .members
+            .iter()
+            .filter(|m| !new.is_member(&m.user_id))
+            .cloned()
+            .collect();
+        let members_role_changed = old
+            .members
+            .iter()
+            .filter_map(|m| {
+                let role = Change::of(&m.role, new.member_role(&m.user_id)?)?;
+                Some(RoleChange {
+                    user_id: m.user_id.clone(),
+                    role,
+                })
+            })
+            .collect();
+        let admins_added = new
+            .admins
+            .iter()
+            .filter(|a| !old.is_admin(&a.user_id))
+            .cloned()
+            .collect();
+        let admins_removed = old
+            .admins
+            .iter()
+            .filter(|a| !new.is_admin(&a.user_id))
+            .cloned()
+            .collect();
+        UserGroupDiff {
+            name: Change::of(&old.name, &new.name),
+            description: Change::of(&old.description, &new.description),
+            r#type: Change::of(&old.r#type, &new.r#type),
+            icon: Change::of(&old.icon, &new.icon),
+            members_added,
+            members_removed,
+            members_role_changed,
+            admins_added,
+            admins_removed,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::payloads::types::UserGroupAdmin;
+    use crate::test_utils::{timestamp, uuid};
+
+    #[test]
+    fn string_round_trip() {
+        for value in ["", "grade", "ふがふが"] {
+            let ty = UserGroupType::from(value);
+            assert_eq!(ty, value);
+            assert_eq!(ty.to_string(), value);
+            assert_eq!(String::from(ty), value);
+        }
+        assert_eq!(
+            UserGroupType::from("Grade"),
+            UserGroupType::Other("Grade".to_string())
+        );
+        let json = serde_json::to_string(&UserGroupRole::from("leader")).unwrap();
+        assert_eq!(json, r#""leader""#);
+        let role: UserGroupRole = serde_json::from_str(r#""""#).unwrap();
+        assert_eq!(role, UserGroupRole::Unspecified);
+    }
+
+    #[test]
+    fn other_with_known_value() {
+        use std::collections::HashSet;
+
+        let other = UserGroupType::Other("grade".to_string());
+        assert_eq!(other, UserGroupType::Grade);
+        assert_ne!(other, UserGroupType::Unspecified);
+        let types = HashSet::from([UserGroupType::Grade]);
+        assert!(types.contains(&other));
+        assert_eq!(
+            UserGroupRole::Other(String::new()),
+            UserGroupRole::Unspecified
+        );
+    }
+
+    #[test]
+    fn member_helpers() {
+        const GROUP: &str = "f265bde2-04cc-4856-9008-3db1d953a539";
+        const LEADER: &str = "8e6a088f-9274-42c0-bb20-cee7913d144b";
+        const MEMBER: &str = "dfdff0c9-5de0-46ee-9721-2525e8bb3d45";
+        const STRANGER: &str = "b80551a5-2768-4d29-ad78-8e0e92330c8d";
+        let member = |user_id, role| UserGroupMember {
+            group_id: uuid(GROUP),
+            user_id: uuid(user_id),
+            role,
+        };
+        let group = UserGroup {
+            id: uuid(GROUP),
+            name: "fugafuga".to_string(),
+            description: "FUGA_FUGA".to_string(),
+            r#type: UserGroupType::Grade,
+            icon: uuid("81f6da0d-eaab-4c42-84ac-74f5111e1eaa"),
+            admins: vec![UserGroupAdmin {
+                group_id: uuid(GROUP),
+                user_id: uuid(LEADER),
+            }],
+            members: vec![
+                member(LEADER, "leader".into()),
+                member(MEMBER, UserGroupRole::Unspecified),
+            ],
+            created_at: timestamp("2023-08-25T04:04:32.912312Z"),
+            updated_at: timestamp("2023-08-25T04:04:32.912312Z"),
+        };
+        assert!(group.is_admin(&uuid(LEADER)));
+        assert!(!group.is_admin(&uuid(MEMBER)));
+        assert!(group.is_member(&uuid(MEMBER)));
+        assert!(!group.is_member(&uuid(STRANGER)));
+        assert_eq!(group.member_role(&uuid(LEADER)), Some(&"leader".into()));
+        assert_eq!(
+            group.member_role(&uuid(MEMBER)),
+            Some(&UserGroupRole::Unspecified)
+        );
+        assert_eq!(group.member_role(&uuid(STRANGER)), None);
+        let unspecified: Vec<_> = group
+            .members_with_role(&UserGroupRole::Unspecified)
+            .map(|m| &m.user_id)
+            .collect();
+        assert_eq!(unspecified, [&uuid(MEMBER)]);
+    }
+
+    #[test]
+    fn diff() {
+        const GROUP: &str = "f265bde2-04cc-4856-9008-3db1d953a539";
+        const A: &str = "8e6a088f-9274-42c0-bb20-cee7913d144b";
+        const B: &str = "dfdff0c9-5de0-46ee-9721-2525e8bb3d45";
+        const C: &str = "b80551a5-2768-4d29-ad78-8e0e92330c8d";
+        let member = |user_id, role: &str| UserGroupMember {
+            group_id: uuid(GROUP),
+            user_id: uuid(user_id),
+            role: role.into(),
+        };
+        let admin = |user_id| UserGroupAdmin {
+            group_id: uuid(GROUP),
+            user_id: uuid(user_id),
+        };
+        let old = UserGroup {
+            id: uuid(GROUP),
+            name: "fugafuga".to_string(),
+            description: "FUGA_FUGA".to_string(),
+            r#type: UserGroupType::Unspecified,
+            icon: uuid("81f6da0d-eaab-4c42-84ac-74f5111e1eaa"),
+            admins: vec![admin(A)],
+            members: vec![member(A, ""), member(B, "")],
+            created_at: timestamp("2023-08-25T04:04:32.912312Z"),
+            updated_at: timestamp("2023-08-25T04:04:32.912312Z"),
+        };
+        assert!(UserGroup::diff(&old, &old).is_empty());
+        let new = UserGroup {
+            description: "HOGE_HOGE".to_string(),
+            r#type: UserGroupType::Grade,
+            admins: vec![admin(C)],
+            members: vec![member(A, "leader"), member(C, "")],
+            updated_at: timestamp("2023-08-26T04:04:32.912312Z"),
+            ..old.clone()
+        };
+        let diff = UserGroup::diff(&old, &new);
+        assert_eq!(
+            diff,
+            UserGroupDiff {
+                name: None,
+                description: Some(Change {
+                    old: "FUGA_FUGA".to_string(),
+                    new: "HOGE_HOGE".to_string(),
+                }),
+                r#type: Some(Change {
+                    old: UserGroupType::Unspecified,
+                    new: UserGroupType::Grade,
+                }),
+                icon: None,
+                members_added: vec![member(C, "")],
+                members_removed: vec![member(B, "")],
+                members_role_changed: vec![RoleChange {
+                    user_id: uuid(A),
+                    role: Change {
+                        old: UserGroupRole::Unspecified,
+                        new: "leader".into(),
+                    },
+                }],
+                admins_added: vec![admin(C)],
+                admins_removed: vec![admin(A)],
+            }
+        );
+        assert!(!diff.is_empty());
+    }
+}
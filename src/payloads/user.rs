@@ -1,10 +1,11 @@
 //! ユーザー関連のイベントペイロード
 //! ## types
 //! - [`UserCreatedPayload`](UserCreatedPayload)
+//! - [`UserActivatedPayload`](UserActivatedPayload)
 
 use serde::{Deserialize, Serialize};
 
-use super::types::{TimeStamp, User};
+use super::types::{Timestamp, User};
 
 /// `USER_CREATED`ペイロード
 /// - [traQの型定義](https://github.com/traPtitech/traQ/blob/d2bc98f1e0e68f4acc371eb78e6a49a167446761/service/bot/event/payload/ev_user_created.go#L9-L13)
@@ -30,13 +31,60 @@ use super::types::{TimeStamp, User};
 /// # }
 /// ```
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "schemars", schemars(transform = crate::schema::trim_description))]
 #[serde(rename_all = "camelCase")]
 pub struct UserCreatedPayload {
-    #[serde(with = "crate::payloads::serde::timestamp")]
-    pub event_time: TimeStamp,
+    pub event_time: Timestamp,
     pub user: User,
 }
 
+impl From<UserActivatedPayload> for UserCreatedPayload {
+    fn from(payload: UserActivatedPayload) -> Self {
+        let UserActivatedPayload { event_time, user } = payload;
+        Self { event_time, user }
+    }
+}
+
+/// `USER_ACTIVATED`ペイロード
+/// - [traQの型定義](https://github.com/traPtitech/traQ/blob/a1aaf12d089a9033461d0f1fcabb69a92873a3b1/service/bot/event/payload/ev_user_activated.go)
+/// - [traQ-bot-consoleのリファレンス](https://github.com/traPtitech/traQ-bot-console/blob/dev/src/docs/bot/events/user.md#user_activated)
+///
+/// ## Example
+/// ```
+/// # fn main() -> Result<(), serde_json::Error> {
+/// use traq_bot_http::payloads::UserActivatedPayload;
+/// let payload = r##"{
+///     "eventTime": "2019-05-08T08:31:06.566228282Z",
+///     "user": {
+///         "id": "dfdff0c9-5de0-46ee-9721-2525e8bb3d45",
+///         "name": "takashi_trap",
+///         "displayName": "",
+///         "iconId": "2bc06cda-bdb9-4a68-8000-62f907f36a92",
+///         "bot": false
+///     }
+/// }"##;
+/// let payload: UserActivatedPayload = payload.parse()?;
+/// println!("{payload}");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "schemars", schemars(transform = crate::schema::trim_description))]
+#[serde(rename_all = "camelCase")]
+pub struct UserActivatedPayload {
+    pub event_time: Timestamp,
+    pub user: User,
+}
+
+impl From<UserCreatedPayload> for UserActivatedPayload {
+    fn from(payload: UserCreatedPayload) -> Self {
+        let UserCreatedPayload { event_time, user } = payload;
+        Self { event_time, user }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -65,4 +113,26 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn user_activated_test() {
+        let data = read_to_string("testdata/user/user_activated.json").unwrap();
+        let payload: UserActivatedPayload = data.parse().unwrap();
+        let pretty_payload = serde_json::to_string_pretty(&payload).unwrap();
+        println!("{pretty_payload}");
+        println!("{payload}");
+        assert_eq!(
+            payload,
+            UserActivatedPayload {
+                event_time: timestamp("2019-05-08T08:31:06.566228282Z"),
+                user: User {
+                    id: uuid("dfdff0c9-5de0-46ee-9721-2525e8bb3d45"),
+                    name: "takashi_trap".to_string(),
+                    display_name: String::new(),
+                    icon_id: uuid("2bc06cda-bdb9-4a68-8000-62f907f36a92"),
+                    bot: false,
+                }
+            }
+        );
+    }
 }
@@ -11,7 +11,7 @@
 
 use serde::{Deserialize, Serialize};
 
-use super::types::{GroupMember, TimeStamp, UserGroup, Uuid};
+use super::types::{GroupMember, Timestamp, UserGroup, Uuid};
 
 /// `USER_GROUP_CREATED`ペイロード
 /// - [traQの型定義](https://github.com/traPtitech/traQ/blob/a1aaf12d089a9033461d0f1fcabb69a92873a3b1/service/bot/event/payload/ev_user_group_created.go#L9-L13)
@@ -52,10 +52,11 @@ use super::types::{GroupMember, TimeStamp, UserGroup, Uuid};
 /// # }
 /// ```
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "schemars", schemars(transform = crate::schema::trim_description))]
 #[serde(rename_all = "camelCase")]
 pub struct UserGroupCreatedPayload {
-    #[serde(with = "crate::payloads::serde::timestamp")]
-    pub event_time: TimeStamp,
+    pub event_time: Timestamp,
     pub group: UserGroup,
 }
 
@@ -77,10 +78,12 @@ pub struct UserGroupCreatedPayload {
 /// # }
 /// ```
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "schemars", schemars(transform = crate::schema::trim_description))]
 #[serde(rename_all = "camelCase")]
 pub struct UserGroupUpdatedPayload {
-    #[serde(with = "crate::payloads::serde::timestamp")]
-    pub event_time: TimeStamp,
+    pub event_time: Timestamp,
+    #[cfg_attr(feature = "schemars", schemars(with = "crate::schema::UuidSchema"))]
     pub group_id: Uuid,
 }
 
@@ -111,10 +114,12 @@ impl From<UserGroupDeletedPayload> for UserGroupUpdatedPayload {
 /// # }
 /// ```
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "schemars", schemars(transform = crate::schema::trim_description))]
 #[serde(rename_all = "camelCase")]
 pub struct UserGroupDeletedPayload {
-    #[serde(with = "crate::payloads::serde::timestamp")]
-    pub event_time: TimeStamp,
+    pub event_time: Timestamp,
+    #[cfg_attr(feature = "schemars", schemars(with = "crate::schema::UuidSchema"))]
     pub group_id: Uuid,
 }
 
@@ -148,10 +153,11 @@ impl From<UserGroupUpdatedPayload> for UserGroupDeletedPayload {
 /// # }
 /// ```
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "schemars", schemars(transform = crate::schema::trim_description))]
 #[serde(rename_all = "camelCase")]
 pub struct UserGroupMemberAddedPayload {
-    #[serde(with = "crate::payloads::serde::timestamp")]
-    pub event_time: TimeStamp,
+    pub event_time: Timestamp,
     pub group_member: GroupMember,
 }
 
@@ -203,10 +209,11 @@ impl From<UserGroupAdminAddedPayload> for UserGroupMemberAddedPayload {
 /// # }
 /// ```
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "schemars", schemars(transform = crate::schema::trim_description))]
 #[serde(rename_all = "camelCase")]
 pub struct UserGroupMemberUpdatedPayload {
-    #[serde(with = "crate::payloads::serde::timestamp")]
-    pub event_time: TimeStamp,
+    pub event_time: Timestamp,
     pub group_member: GroupMember,
 }
 
@@ -249,10 +256,11 @@ impl From<UserGroupMemberRemovedPayload> for UserGroupMemberUpdatedPayload {
 /// # }
 /// ```
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "schemars", schemars(transform = crate::schema::trim_description))]
 #[serde(rename_all = "camelCase")]
 pub struct UserGroupMemberRemovedPayload {
-    #[serde(with = "crate::payloads::serde::timestamp")]
-    pub event_time: TimeStamp,
+    pub event_time: Timestamp,
     pub group_member: GroupMember,
 }
 
@@ -304,10 +312,11 @@ impl From<UserGroupAdminRemovedPayload> for UserGroupMemberRemovedPayload {
 /// # }
 /// ```
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "schemars", schemars(transform = crate::schema::trim_description))]
 #[serde(rename_all = "camelCase")]
 pub struct UserGroupAdminAddedPayload {
-    #[serde(with = "crate::payloads::serde::timestamp")]
-    pub event_time: TimeStamp,
+    pub event_time: Timestamp,
     pub group_member: GroupMember,
 }
 
@@ -350,10 +359,11 @@ impl From<UserGroupMemberAddedPayload> for UserGroupAdminAddedPayload {
 /// # }
 /// ```
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "schemars", schemars(transform = crate::schema::trim_description))]
 #[serde(rename_all = "camelCase")]
 pub struct UserGroupAdminRemovedPayload {
-    #[serde(with = "crate::payloads::serde::timestamp")]
-    pub event_time: TimeStamp,
+    pub event_time: Timestamp,
     pub group_member: GroupMember,
 }
 
@@ -378,7 +388,7 @@ impl From<UserGroupMemberRemovedPayload> for UserGroupAdminRemovedPayload {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::payloads::types::{UserGroupAdmin, UserGroupMember};
+    use crate::payloads::types::{UserGroupAdmin, UserGroupMember, UserGroupRole, UserGroupType};
     use crate::test_utils::{timestamp, uuid};
 
     use std::{fs::read_to_string, vec};
@@ -395,7 +405,7 @@ mod tests {
                     id: uuid("f265bde2-04cc-4856-9008-3db1d953a539"),
                     name: "fugafuga".to_string(),
                     description: "FUGA_FUGA".to_string(),
-                    r#type: "ふがふが".to_string(),
+                    r#type: UserGroupType::Other("ふがふが".to_string()),
                     icon: uuid("81f6da0d-eaab-4c42-84ac-74f5111e1eaa"),
                     admins: vec![UserGroupAdmin {
                         group_id: uuid("f265bde2-04cc-4856-9008-3db1d953a539"),
@@ -404,7 +414,7 @@ mod tests {
                     members: vec![UserGroupMember {
                         group_id: uuid("f265bde2-04cc-4856-9008-3db1d953a539"),
                         user_id: uuid("8e6a088f-9274-42c0-bb20-cee7913d144b"),
-                        role: String::new(),
+                        role: UserGroupRole::Unspecified,
                     }],
                     created_at: timestamp("2023-08-25T04:04:32.912312Z"),
                     updated_at: timestamp("2023-08-25T04:04:32.912312Z")
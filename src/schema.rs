@@ -0,0 +1,328 @@
+//! ペイロードの[JSON Schema](https://json-schema.org/)
+//!
+//! `schemars` featureでは、[`Event`], 各ペイロード, [`crate::payloads::types`]の型が
+//! [`JsonSchema`]を実装します。
+//! UUIDは`"format": "uuid"`、タイムスタンプは`"format": "date-time"`の文字列として表現されます。
+//!
+//! ## Example
+//! ```
+//! use traq_bot_http::{schema, EventKind};
+//!
+//! let bundle = schema::bundle();
+//! let message_created = bundle.pointer("/$defs/MESSAGE_CREATED/$ref").unwrap();
+//! assert_eq!(message_created, "#/$defs/MessageCreatedPayload");
+//!
+//! let schema = schema::event_schema(EventKind::Ping);
+//! assert_eq!(schema.get("title").unwrap(), "PingPayload");
+//! ```
+
+use std::borrow::Cow;
+
+use schemars::generate::SchemaSettings;
+use schemars::{json_schema, JsonSchema, Schema, SchemaGenerator};
+use serde_json::{Map, Value};
+
+use crate::macros::all_events;
+use crate::payloads::types::{ChannelPath, Timestamp, UserGroupRole, UserGroupType};
+use crate::payloads::{
+    BotMessageStampsUpdatedPayload, ChannelCreatedPayload, ChannelTopicChangedPayload,
+    DirectMessageCreatedPayload, DirectMessageDeletedPayload, DirectMessageUpdatedPayload,
+    JoinedPayload, LeftPayload, MessageCreatedPayload, MessageDeletedPayload,
+    MessageUpdatedPayload, PingPayload, StampCreatedPayload, TagAddedPayload, TagRemovedPayload,
+    UserActivatedPayload, UserCreatedPayload, UserGroupAdminAddedPayload,
+    UserGroupAdminRemovedPayload, UserGroupCreatedPayload, UserGroupDeletedPayload,
+    UserGroupMemberAddedPayload, UserGroupMemberRemovedPayload, UserGroupMemberUpdatedPayload,
+    UserGroupUpdatedPayload,
+};
+use crate::{Event, EventKind};
+
+/// 全てのイベントのペイロードのスキーマをまとめたものを返します。
+///
+/// `$defs`には各ペイロードと、それらが使用する型のスキーマが含まれます。
+/// さらに[`EventKind`]の名前(`MESSAGE_CREATED`など)をキーとして、
+/// 対応するペイロードのスキーマへの`$ref`が含まれます。
+#[must_use]
+pub fn bundle() -> Schema {
+    let mut generator = new_generator();
+
+    macro_rules! kind_refs {
+        ($($i:ident),*) => {
+            ::paste::paste! {
+                [$(
+                    (
+                        EventKind::$i.to_string(),
+                        generator.subschema_for::<[<$i Payload>]>().to_value(),
+                    ),
+                )*]
+            }
+        };
+    }
+
+    let refs = all_events!(kind_refs);
+    let mut defs: Map<String, Value> = refs.into_iter().collect();
+    defs.extend(generator.take_definitions(true));
+    let meta_schema = generator.settings().meta_schema.clone();
+    json_schema!({
+        "$schema": meta_schema,
+        "title": "traQ BOT event payloads",
+        "$defs": defs,
+    })
+}
+
+/// `kind`のイベントのペイロードのスキーマを返します。
+#[must_use]
+pub fn event_schema(kind: EventKind) -> Schema {
+    macro_rules! match_kind_to_schema {
+        ($($i:ident),*) => {
+            ::paste::paste! {
+                match kind {
+                    $( EventKind::$i => new_generator().into_root_schema_for::<[<$i Payload>]>(), )*
+                }
+            }
+        };
+    }
+
+    all_events!(match_kind_to_schema)
+}
+
+fn new_generator() -> SchemaGenerator {
+    SchemaSettings::draft2020_12().into_generator()
+}
+
+/// UUIDのスキーマ。[`Uuid`]のフィールドに`#[schemars(with = ...)]`で指定する
+///
+/// [`Uuid`]: crate::payloads::types::Uuid
+pub(crate) struct UuidSchema;
+
+impl JsonSchema for UuidSchema {
+    fn inline_schema() -> bool {
+        true
+    }
+
+    fn schema_name() -> Cow<'static, str> {
+        "Uuid".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "type": "string",
+            "format": "uuid",
+        })
+    }
+}
+
+/// ドキュメントコメント由来の`description`から、Rust向けの`## Example`以降を取り除く
+pub(crate) fn trim_description(schema: &mut Schema) {
+    let Some(Value::String(description)) = schema.get_mut("description") else {
+        return;
+    };
+    if let Some(i) = description.find("## Example") {
+        description.truncate(i);
+        let len = description.trim_end_matches(['#', ' ', '\n']).len();
+        description.truncate(len);
+    }
+}
+
+impl JsonSchema for Timestamp {
+    fn inline_schema() -> bool {
+        true
+    }
+
+    fn schema_name() -> Cow<'static, str> {
+        "Timestamp".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "type": "string",
+            "format": "date-time",
+        })
+    }
+}
+
+impl JsonSchema for ChannelPath {
+    fn inline_schema() -> bool {
+        true
+    }
+
+    fn schema_name() -> Cow<'static, str> {
+        "ChannelPath".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "type": "string",
+            "pattern": "^#[0-9A-Za-z_-]{1,20}(/[0-9A-Za-z_-]{1,20})*$",
+        })
+    }
+}
+
+impl JsonSchema for UserGroupType {
+    fn inline_schema() -> bool {
+        true
+    }
+
+    fn schema_name() -> Cow<'static, str> {
+        "UserGroupType".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "type": "string",
+            "examples": ["", "grade"],
+        })
+    }
+}
+
+impl JsonSchema for UserGroupRole {
+    fn inline_schema() -> bool {
+        true
+    }
+
+    fn schema_name() -> Cow<'static, str> {
+        "UserGroupRole".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "type": "string",
+            "examples": [""],
+        })
+    }
+}
+
+impl JsonSchema for EventKind {
+    fn schema_name() -> Cow<'static, str> {
+        "EventKind".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        macro_rules! kind_names {
+            ($($i:ident),*) => {
+                [$( EventKind::$i.to_string() ),*]
+            };
+        }
+
+        json_schema!({
+            "type": "string",
+            "enum": all_events!(kind_names),
+        })
+    }
+}
+
+/// いずれかのイベントのペイロード
+impl JsonSchema for Event {
+    fn schema_name() -> Cow<'static, str> {
+        "Event".into()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        macro_rules! payload_refs {
+            ($($i:ident),*) => {
+                ::paste::paste! {
+                    [$( generator.subschema_for::<[<$i Payload>]>() ),*]
+                }
+            };
+        }
+
+        // 同じ形のペイロードがあるため`oneOf`ではなく`anyOf`
+        json_schema!({
+            "anyOf": all_events!(payload_refs),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::macros::all_events;
+
+    fn pointer<'a>(schema: &'a Schema, pointer: &str) -> &'a Value {
+        schema
+            .pointer(pointer)
+            .unwrap_or_else(|| panic!("{pointer} not found"))
+    }
+
+    #[test]
+    fn bundle_refs() {
+        macro_rules! check_refs {
+            ($($i:ident),*) => {
+                let bundle = bundle();
+                $(
+                    let kind = EventKind::$i.to_string();
+                    let reference = pointer(&bundle, &format!("/$defs/{kind}/$ref"));
+                    let name = concat!(stringify!($i), "Payload");
+                    assert_eq!(reference, &format!("#/$defs/{name}"));
+                    assert!(bundle.pointer(&format!("/$defs/{name}")).is_some());
+                )*
+            };
+        }
+
+        all_events!(check_refs);
+    }
+
+    #[test]
+    fn camel_case_and_formats() {
+        let schema = event_schema(EventKind::MessageCreated);
+        assert_eq!(
+            pointer(&schema, "/required"),
+            &serde_json::json!(["eventTime", "message"])
+        );
+        assert_eq!(
+            pointer(&schema, "/properties/eventTime/format"),
+            "date-time"
+        );
+        let message = pointer(&schema, "/$defs/Message/properties");
+        assert_eq!(message["channelId"]["format"], "uuid");
+        assert_eq!(message["plainText"]["type"], "string");
+        assert!(message.get("plain_text").is_none());
+        let embedded = pointer(&schema, "/$defs/EmbeddedInfo/properties");
+        assert!(embedded.get("type").is_some());
+    }
+
+    #[test]
+    fn description() {
+        let schema = event_schema(EventKind::Ping);
+        let description = pointer(&schema, "/description").as_str().unwrap();
+        assert!(description.starts_with("`PING`ペイロード"));
+        assert!(!description.contains("Example"));
+        assert!(!description.ends_with('\n'));
+    }
+
+    #[test]
+    fn event() {
+        let mut generator = new_generator();
+        let schema = generator.root_schema_for::<Event>();
+        let any_of = pointer(&schema, "/anyOf").as_array().unwrap();
+        assert_eq!(any_of.len(), all_events!(count_events));
+    }
+
+    macro_rules! count_events {
+        ($($i:ident),*) => {
+            [$( stringify!($i) ),*].len()
+        };
+    }
+
+    use count_events;
+
+    /// `testdata`のペイロードがスキーマと矛盾しないことを、必須のプロパティについて確かめる
+    #[test]
+    fn testdata_has_required_properties() {
+        let data = std::fs::read_to_string("testdata/message/message_created.json").unwrap();
+        let value: Value = serde_json::from_str(&data).unwrap();
+        let schema = event_schema(EventKind::MessageCreated);
+        for key in pointer(&schema, "/required").as_array().unwrap() {
+            assert!(value.get(key.as_str().unwrap()).is_some(), "{key}");
+        }
+        for key in pointer(&schema, "/$defs/Message/required")
+            .as_array()
+            .unwrap()
+        {
+            assert!(
+                value["message"].get(key.as_str().unwrap()).is_some(),
+                "{key}"
+            );
+        }
+    }
+}
@@ -1,4 +1,7 @@
-use crate::payloads::types::{Channel, EmbeddedInfo, TimeStamp, User, Uuid};
+use std::fmt::Debug;
+use std::str::FromStr;
+
+use crate::payloads::types::{Channel, EmbeddedInfo, Timestamp, User, Uuid};
 use crate::RequestParser;
 
 use http::header::{HeaderMap, CONTENT_TYPE};
@@ -17,10 +20,18 @@ pub fn make_headers(event: &str) -> HeaderMap {
     headers
 }
 
-pub fn timestamp(v: &'static str) -> TimeStamp {
-    use serde::de::value::{Error, StrDeserializer};
-    let de = StrDeserializer::<'_, Error>::new(v);
-    crate::payloads::serde::timestamp::deserialize(de).unwrap()
+/// `testdata/{path}`を読み込んで`P`として解釈する
+pub fn testdata<P>(path: &str) -> P
+where
+    P: FromStr,
+    P::Err: Debug,
+{
+    let data = std::fs::read_to_string(format!("testdata/{path}")).unwrap();
+    data.parse().unwrap()
+}
+
+pub fn timestamp(v: &'static str) -> Timestamp {
+    v.parse().unwrap()
 }
 
 pub fn uuid(v: &'static str) -> Uuid {
@@ -0,0 +1,696 @@
+//! テスト用のペイロードのビルダー
+//!
+//! 各ペイロードと、その内部で使われる型に`builder()`が追加されます。
+//! ビルダーの既定値には[traQ-bot-consoleのリファレンス](https://github.com/traPtitech/traQ-bot-console/blob/dev/src/docs/bot/events)
+//! の例と同じ値が使われ、必要なフィールドだけを上書きできます。
+//!
+//! ## Example
+//! ```
+//! use traq_bot_http::payloads::{types::User, MessageCreatedPayload};
+//!
+//! let user = User::builder().name("bot_po").bot(true).build();
+//! let payload = MessageCreatedPayload::builder()
+//!     .text("hi")
+//!     .author(user.clone())
+//!     .build();
+//! assert_eq!(payload.message.text, "hi");
+//! assert_eq!(payload.message.plain_text, "hi");
+//! assert_eq!(payload.message.user, user);
+//! ```
+
+use crate::payloads::types::{
+    Channel, DeletedDirectMessage, DeletedMessage, EmbeddedInfo, GroupMember, Message,
+    MessageStamp, Timestamp, User, UserGroup, UserGroupAdmin, UserGroupMember, UserGroupRole,
+    UserGroupType, Uuid,
+};
+use crate::payloads::{
+    BotMessageStampsUpdatedPayload, ChannelCreatedPayload, ChannelTopicChangedPayload,
+    DirectMessageCreatedPayload, DirectMessageDeletedPayload, DirectMessageUpdatedPayload,
+    JoinedPayload, LeftPayload, MessageCreatedPayload, MessageDeletedPayload,
+    MessageUpdatedPayload, PingPayload, StampCreatedPayload, TagAddedPayload, TagRemovedPayload,
+    UserActivatedPayload, UserCreatedPayload, UserGroupAdminAddedPayload,
+    UserGroupAdminRemovedPayload, UserGroupCreatedPayload, UserGroupDeletedPayload,
+    UserGroupMemberAddedPayload, UserGroupMemberRemovedPayload, UserGroupMemberUpdatedPayload,
+    UserGroupUpdatedPayload,
+};
+
+fn uuid(v: &str) -> Uuid {
+    v.parse().expect("invalid uuid literal")
+}
+
+fn timestamp(v: &str) -> Timestamp {
+    v.parse().expect("invalid timestamp literal")
+}
+
+fn direct_message() -> Message {
+    Message::builder()
+        .id(uuid("2d7ff3f5-c313-4f4a-a9bb-0b5f84d2b6f8"))
+        .channel_id(uuid("c5a5a697-3bad-4540-b2da-93dc88181d34"))
+        .created_at(timestamp("2019-05-08T13:36:09.365393261Z"))
+        .updated_at(timestamp("2019-05-08T13:36:09.365393261Z"))
+        .build()
+}
+
+/// `T`のビルダーを定義し、`T::builder()`を追加する
+///
+/// ビルダーは既定値`$default`を保持し、列挙したフィールドごとに上書き用のメソッドを持つ
+macro_rules! builders {
+    ($(
+        $t:ident => $builder:ident {
+            $( $field:ident : $fty:ty ),* $(,)?
+        } = $default:expr;
+    )*) => {$(
+        #[doc = concat!("[`", stringify!($t), "`]のビルダー")]
+        #[must_use]
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        pub struct $builder {
+            inner: $t,
+        }
+
+        impl $builder {
+            $(
+                #[doc = concat!("`", stringify!($field), "`を設定します。")]
+                pub fn $field(mut self, $field: impl Into<$fty>) -> Self {
+                    self.inner.$field = $field.into();
+                    self
+                }
+            )*
+
+            #[doc = concat!("[`", stringify!($t), "`]を返します。")]
+            #[must_use]
+            pub fn build(self) -> $t {
+                self.inner
+            }
+        }
+
+        impl Default for $builder {
+            fn default() -> Self {
+                Self { inner: $default }
+            }
+        }
+
+        impl From<$builder> for $t {
+            fn from(builder: $builder) -> Self {
+                builder.build()
+            }
+        }
+
+        impl $t {
+            #[doc = concat!("[`", stringify!($builder), "`]を作成します。")]
+            ///
+            /// `testing` featureが必要です。
+            pub fn builder() -> $builder {
+                $builder::default()
+            }
+        }
+    )*};
+}
+
+builders! {
+    User => UserBuilder {
+        id: Uuid,
+        name: String,
+        display_name: String,
+        icon_id: Uuid,
+        bot: bool,
+    } = User {
+        id: uuid("dfdff0c9-5de0-46ee-9721-2525e8bb3d45"),
+        name: "takashi_trap".to_string(),
+        display_name: "寺田 健二".to_string(),
+        icon_id: uuid("2bc06cda-bdb9-4a68-8000-62f907f36a92"),
+        bot: false,
+    };
+
+    Channel => ChannelBuilder {
+        id: Uuid,
+        name: String,
+        path: String,
+        parent_id: Uuid,
+        creator: User,
+        created_at: Timestamp,
+        updated_at: Timestamp,
+    } = Channel {
+        id: uuid("f86c925c-3002-4ba5-939a-c92344e534f9"),
+        name: "po".to_string(),
+        path: "#a/po".to_string(),
+        parent_id: uuid("ea452867-553b-4808-a14f-a47ee0009ee6"),
+        creator: User::builder().build(),
+        created_at: timestamp("2018-04-25T12:22:02Z"),
+        updated_at: timestamp("2018-04-25T12:22:02Z"),
+    };
+
+    EmbeddedInfo => EmbeddedInfoBuilder {
+        raw: String,
+        r#type: String,
+        id: Uuid,
+    } = EmbeddedInfo {
+        raw: "@takashi_trap".to_string(),
+        r#type: "user".to_string(),
+        id: uuid("dfdff0c9-5de0-46ee-9721-2525e8bb3d45"),
+    };
+
+    Message => MessageTypeBuilder {
+        id: Uuid,
+        user: User,
+        channel_id: Uuid,
+        plain_text: String,
+        embedded: Vec<EmbeddedInfo>,
+        created_at: Timestamp,
+        updated_at: Timestamp,
+    } = Message {
+        id: uuid("bc9106b3-f9b2-4eca-9ba1-72b39b40954e"),
+        user: User::builder().build(),
+        channel_id: uuid("9aba50da-f605-4cd0-a428-5e4558cb911e"),
+        text: String::new(),
+        plain_text: String::new(),
+        embedded: Vec::new(),
+        created_at: timestamp("2019-05-08T13:33:51.632149265Z"),
+        updated_at: timestamp("2019-05-08T13:33:51.632149265Z"),
+    }
+    .with_text(
+        r#"!{"type": "user", "raw": "@takashi_trap", "id": "dfdff0c9-5de0-46ee-9721-2525e8bb3d45"} こんにちは"#
+            .to_string(),
+    );
+
+    DeletedMessage => DeletedMessageBuilder {
+        id: Uuid,
+        channel_id: Uuid,
+    } = DeletedMessage {
+        id: uuid("bc9106b3-f9b2-4eca-9ba1-72b39b40954e"),
+        channel_id: uuid("9aba50da-f605-4cd0-a428-5e4558cb911e"),
+    };
+
+    DeletedDirectMessage => DeletedDirectMessageBuilder {
+        id: Uuid,
+        user_id: Uuid,
+        channel_id: Uuid,
+    } = DeletedDirectMessage {
+        id: uuid("2d7ff3f5-c313-4f4a-a9bb-0b5f84d2b6f8"),
+        user_id: uuid("dfdff0c9-5de0-46ee-9721-2525e8bb3d45"),
+        channel_id: uuid("c5a5a697-3bad-4540-b2da-93dc88181d34"),
+    };
+
+    MessageStamp => MessageStampBuilder {
+        stamp_id: Uuid,
+        user_id: Uuid,
+        count: i32,
+        created_at: Timestamp,
+        updated_at: Timestamp,
+    } = MessageStamp {
+        stamp_id: uuid("b77fad4e-b63f-42a2-916c-5cfe5af3d8b9"),
+        user_id: uuid("b80551a5-2768-4d29-ad78-8e0e92330c8d"),
+        count: 24,
+        created_at: timestamp("2020-10-17T03:34:56.575099Z"),
+        updated_at: timestamp("2020-10-17T03:35:34Z"),
+    };
+
+    GroupMember => GroupMemberBuilder {
+        group_id: Uuid,
+        user_id: Uuid,
+    } = GroupMember {
+        group_id: uuid("f265bde2-04cc-4856-9008-3db1d953a539"),
+        user_id: uuid("8e6a088f-9274-42c0-bb20-cee7913d144b"),
+    };
+
+    UserGroupAdmin => UserGroupAdminBuilder {
+        group_id: Uuid,
+        user_id: Uuid,
+    } = GroupMember::builder().build().into();
+
+    UserGroupMember => UserGroupMemberBuilder {
+        group_id: Uuid,
+        user_id: Uuid,
+        role: UserGroupRole,
+    } = UserGroupMember {
+        group_id: uuid("f265bde2-04cc-4856-9008-3db1d953a539"),
+        user_id: uuid("8e6a088f-9274-42c0-bb20-cee7913d144b"),
+        role: UserGroupRole::Unspecified,
+    };
+
+    UserGroup => UserGroupBuilder {
+        id: Uuid,
+        name: String,
+        description: String,
+        r#type: UserGroupType,
+        icon: Uuid,
+        admins: Vec<UserGroupAdmin>,
+        members: Vec<UserGroupMember>,
+        created_at: Timestamp,
+        updated_at: Timestamp,
+    } = UserGroup {
+        id: uuid("f265bde2-04cc-4856-9008-3db1d953a539"),
+        name: "fugafuga".to_string(),
+        description: "FUGA_FUGA".to_string(),
+        r#type: "ふがふが".into(),
+        icon: uuid("81f6da0d-eaab-4c42-84ac-74f5111e1eaa"),
+        admins: vec![UserGroupAdmin::builder().build()],
+        members: vec![UserGroupMember::builder().build()],
+        created_at: timestamp("2023-08-25T04:04:32.912312Z"),
+        updated_at: timestamp("2023-08-25T04:04:32.912312Z"),
+    };
+
+    PingPayload => PingPayloadBuilder {
+        event_time: Timestamp,
+    } = PingPayload {
+        event_time: timestamp("2019-05-07T04:50:48.582586882Z"),
+    };
+
+    JoinedPayload => JoinedPayloadBuilder {
+        event_time: Timestamp,
+        channel: Channel,
+    } = JoinedPayload {
+        event_time: timestamp("2019-05-08T13:49:13.769110201Z"),
+        channel: Channel::builder().build(),
+    };
+
+    LeftPayload => LeftPayloadBuilder {
+        event_time: Timestamp,
+        channel: Channel,
+    } = LeftPayload {
+        event_time: timestamp("2019-05-08T13:49:16.497848449Z"),
+        channel: Channel::builder().build(),
+    };
+
+    MessageCreatedPayload => MessageCreatedPayloadBuilder {
+        event_time: Timestamp,
+        message: Message,
+    } = MessageCreatedPayload {
+        event_time: timestamp("2019-05-08T13:33:51.690308239Z"),
+        message: Message::builder().build(),
+    };
+
+    MessageDeletedPayload => MessageDeletedPayloadBuilder {
+        event_time: Timestamp,
+        message: DeletedMessage,
+    } = MessageDeletedPayload {
+        event_time: timestamp("2019-05-08T13:33:51.690308239Z"),
+        message: DeletedMessage::builder().build(),
+    };
+
+    MessageUpdatedPayload => MessageUpdatedPayloadBuilder {
+        event_time: Timestamp,
+        message: Message,
+    } = MessageUpdatedPayload {
+        event_time: timestamp("2019-05-08T13:33:51.690308239Z"),
+        message: Message::builder().build(),
+    };
+
+    DirectMessageCreatedPayload => DirectMessageCreatedPayloadBuilder {
+        event_time: Timestamp,
+        message: Message,
+    } = DirectMessageCreatedPayload {
+        event_time: timestamp("2019-05-08T13:36:09.421492525Z"),
+        message: direct_message(),
+    };
+
+    DirectMessageDeletedPayload => DirectMessageDeletedPayloadBuilder {
+        event_time: Timestamp,
+        message: DeletedDirectMessage,
+    } = DirectMessageDeletedPayload {
+        event_time: timestamp("2019-05-08T13:36:09.421492525Z"),
+        message: DeletedDirectMessage::builder().build(),
+    };
+
+    DirectMessageUpdatedPayload => DirectMessageUpdatedPayloadBuilder {
+        event_time: Timestamp,
+        message: Message,
+    } = DirectMessageUpdatedPayload {
+        event_time: timestamp("2019-05-08T13:36:09.421492525Z"),
+        message: direct_message(),
+    };
+
+    BotMessageStampsUpdatedPayload => BotMessageStampsUpdatedPayloadBuilder {
+        event_time: Timestamp,
+        message_id: Uuid,
+        stamps: Vec<MessageStamp>,
+    } = BotMessageStampsUpdatedPayload {
+        event_time: timestamp("2020-10-17T03:35:34.5326265Z"),
+        message_id: uuid("200b6600-b2cd-4c1e-b366-9c40308cc087"),
+        stamps: vec![
+            MessageStamp::builder()
+                .stamp_id(uuid("1cd58034-8998-4b1c-afe4-fcd591354a97"))
+                .count(22)
+                .created_at(timestamp("2020-10-17T03:35:17.89545Z"))
+                .build(),
+            MessageStamp::builder()
+                .stamp_id(uuid("6fc62b49-dea0-45b8-8c0c-38035082b111"))
+                .count(23)
+                .created_at(timestamp("2020-10-17T03:35:17.737127Z"))
+                .build(),
+            MessageStamp::builder().build(),
+        ],
+    };
+
+    ChannelCreatedPayload => ChannelCreatedPayloadBuilder {
+        event_time: Timestamp,
+        channel: Channel,
+    } = ChannelCreatedPayload {
+        event_time: timestamp("2019-05-08T13:45:51.506206852Z"),
+        channel: Channel::builder()
+            .id(uuid("711afb4c-23e7-46dc-b845-5160f7088ce9"))
+            .name("yamada")
+            .path("#gps/yamada")
+            .created_at(timestamp("2019-05-08T13:45:51.487718Z"))
+            .updated_at(timestamp("2019-05-08T13:45:51.487718Z"))
+            .build(),
+    };
+
+    ChannelTopicChangedPayload => ChannelTopicChangedPayloadBuilder {
+        event_time: Timestamp,
+        channel: Channel,
+        topic: String,
+        updater: User,
+    } = ChannelTopicChangedPayload {
+        event_time: timestamp("2019-05-09T11:32:49.505357701Z"),
+        channel: Channel::builder()
+            .id(uuid("9aba50da-f605-4cd0-a428-5e4558cb911e"))
+            .name("bot")
+            .path("#a/bot")
+            .created_at(timestamp("2019-04-02T06:31:16.229419Z"))
+            .updated_at(timestamp("2019-05-09T11:32:49.475127Z"))
+            .build(),
+        topic: "トピック".to_string(),
+        updater: User::builder().build(),
+    };
+
+    UserCreatedPayload => UserCreatedPayloadBuilder {
+        event_time: Timestamp,
+        user: User,
+    } = UserCreatedPayload {
+        event_time: timestamp("2019-05-08T08:31:06.566228282Z"),
+        user: User::builder().display_name("").build(),
+    };
+
+    UserActivatedPayload => UserActivatedPayloadBuilder {
+        event_time: Timestamp,
+        user: User,
+    } = UserActivatedPayload {
+        event_time: timestamp("2019-05-08T08:31:06.566228282Z"),
+        user: User::builder().display_name("").build(),
+    };
+
+    StampCreatedPayload => StampCreatedPayloadBuilder {
+        event_time: Timestamp,
+        id: Uuid,
+        name: String,
+        file_id: Uuid,
+        creator: User,
+    } = StampCreatedPayload {
+        event_time: timestamp("2019-05-08T08:31:06.566228282Z"),
+        id: uuid("2bc06cda-bdb9-4a68-8000-62f907f36a92"),
+        name: "naruhodo".to_string(),
+        file_id: uuid("2bc06cda-bdb9-4a68-8000-62f907f36a92"),
+        creator: User::builder().display_name("").build(),
+    };
+
+    TagAddedPayload => TagAddedPayloadBuilder {
+        event_time: Timestamp,
+        tag_id: Uuid,
+        tag: String,
+    } = TagAddedPayload {
+        event_time: timestamp("2019-05-08T08:31:06.566228282Z"),
+        tag_id: uuid("2bc06cda-bdb9-4a68-8000-62f907f36a92"),
+        tag: "全強".to_string(),
+    };
+
+    TagRemovedPayload => TagRemovedPayloadBuilder {
+        event_time: Timestamp,
+        tag_id: Uuid,
+        tag: String,
+    } = TagAddedPayload::builder().build().into();
+
+    UserGroupCreatedPayload => UserGroupCreatedPayloadBuilder {
+        event_time: Timestamp,
+        group: UserGroup,
+    } = UserGroupCreatedPayload {
+        event_time: timestamp("2023-08-25T04:04:32.912312Z"),
+        group: UserGroup::builder().build(),
+    };
+
+    UserGroupUpdatedPayload => UserGroupUpdatedPayloadBuilder {
+        event_time: Timestamp,
+        group_id: Uuid,
+    } = UserGroupUpdatedPayload {
+        event_time: timestamp("2023-08-25T04:04:32.962264Z"),
+        group_id: uuid("f265bde2-04cc-4856-9008-3db1d953a539"),
+    };
+
+    UserGroupDeletedPayload => UserGroupDeletedPayloadBuilder {
+        event_time: Timestamp,
+        group_id: Uuid,
+    } = UserGroupDeletedPayload {
+        event_time: timestamp("2023-08-25T06:40:35.971142Z"),
+        group_id: uuid("f265bde2-04cc-4856-9008-3db1d953a539"),
+    };
+
+    UserGroupMemberAddedPayload => UserGroupMemberAddedPayloadBuilder {
+        event_time: Timestamp,
+        group_member: GroupMember,
+    } = UserGroupMemberAddedPayload {
+        event_time: timestamp("2023-08-25T04:04:32.962264Z"),
+        group_member: GroupMember::builder().build(),
+    };
+
+    UserGroupMemberUpdatedPayload => UserGroupMemberUpdatedPayloadBuilder {
+        event_time: Timestamp,
+        group_member: GroupMember,
+    } = UserGroupMemberAddedPayload::builder().build().into();
+
+    UserGroupMemberRemovedPayload => UserGroupMemberRemovedPayloadBuilder {
+        event_time: Timestamp,
+        group_member: GroupMember,
+    } = UserGroupMemberAddedPayload::builder().build().into();
+
+    UserGroupAdminAddedPayload => UserGroupAdminAddedPayloadBuilder {
+        event_time: Timestamp,
+        group_member: GroupMember,
+    } = UserGroupMemberAddedPayload::builder().build().into();
+
+    UserGroupAdminRemovedPayload => UserGroupAdminRemovedPayloadBuilder {
+        event_time: Timestamp,
+        group_member: GroupMember,
+    } = UserGroupMemberRemovedPayload::from(UserGroupMemberAddedPayload::builder().build())
+        .into();
+}
+
+impl MessageTypeBuilder {
+    /// 本文を設定します。
+    ///
+    /// `plain_text`と`embedded`は本文から計算されます。
+    /// 埋め込みを含む本文の組み立てには[`MessageBuilder`]が使えます。
+    ///
+    /// [`MessageBuilder`]: crate::message::MessageBuilder
+    pub fn text(mut self, text: impl Into<String>) -> Self {
+        self.inner = self.inner.with_text(text.into());
+        self
+    }
+}
+
+/// メッセージを含むペイロードのビルダーに、メッセージの内容を設定するメソッドを追加する
+macro_rules! message_payload_builders {
+    ($($builder:ident),*) => {$(
+        impl $builder {
+            /// メッセージの本文を設定します。
+            ///
+            /// ref: [`MessageTypeBuilder::text`]
+            pub fn text(mut self, text: impl Into<String>) -> Self {
+                self.inner.message = self.inner.message.with_text(text.into());
+                self
+            }
+
+            /// メッセージの投稿者を設定します。
+            pub fn author(mut self, author: impl Into<User>) -> Self {
+                self.inner.message.user = author.into();
+                self
+            }
+
+            /// メッセージが投稿されたチャンネルのUUIDを設定します。
+            pub fn channel_id(mut self, channel_id: impl Into<Uuid>) -> Self {
+                self.inner.message.channel_id = channel_id.into();
+                self
+            }
+        }
+    )*};
+}
+
+message_payload_builders! {
+    MessageCreatedPayloadBuilder,
+    MessageUpdatedPayloadBuilder,
+    DirectMessageCreatedPayloadBuilder,
+    DirectMessageUpdatedPayloadBuilder
+}
+
+/// `group_member`を含むペイロードのビルダーに、その内容を設定するメソッドを追加する
+macro_rules! group_member_payload_builders {
+    ($($builder:ident),*) => {$(
+        impl $builder {
+            /// ユーザーグループのUUIDを設定します。
+            pub fn group_id(mut self, group_id: impl Into<Uuid>) -> Self {
+                self.inner.group_member.group_id = group_id.into();
+                self
+            }
+
+            /// ユーザーのUUIDを設定します。
+            pub fn user_id(mut self, user_id: impl Into<Uuid>) -> Self {
+                self.inner.group_member.user_id = user_id.into();
+                self
+            }
+        }
+    )*};
+}
+
+group_member_payload_builders! {
+    UserGroupMemberAddedPayloadBuilder,
+    UserGroupMemberUpdatedPayloadBuilder,
+    UserGroupMemberRemovedPayloadBuilder,
+    UserGroupAdminAddedPayloadBuilder,
+    UserGroupAdminRemovedPayloadBuilder
+}
+
+impl UserGroupBuilder {
+    /// 管理者を追加します。
+    ///
+    /// `group_id`には呼び出し時点の`id`が使われます。
+    pub fn admin(mut self, user_id: impl Into<Uuid>) -> Self {
+        self.inner.admins.push(UserGroupAdmin {
+            group_id: self.inner.id.clone(),
+            user_id: user_id.into(),
+        });
+        self
+    }
+
+    /// メンバーを追加します。
+    ///
+    /// `group_id`には呼び出し時点の`id`が使われます。
+    pub fn member(mut self, user_id: impl Into<Uuid>, role: impl Into<UserGroupRole>) -> Self {
+        self.inner.members.push(UserGroupMember {
+            group_id: self.inner.id.clone(),
+            user_id: user_id.into(),
+            role: role.into(),
+        });
+        self
+    }
+}
+
+impl BotMessageStampsUpdatedPayloadBuilder {
+    /// スタンプを追加します。
+    pub fn stamp(mut self, stamp: impl Into<MessageStamp>) -> Self {
+        self.inner.stamps.push(stamp.into());
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs::read_to_string;
+
+    use crate::message::MessageBuilder;
+
+    /// ビルダーの既定値が`testdata`のペイロードと一致することを確かめる
+    macro_rules! default_matches_testdata {
+        ($($t:ident => $path:literal),* $(,)?) => {
+            #[test]
+            fn default_matches_testdata() {
+                $(
+                    let data = read_to_string(concat!("testdata/", $path)).unwrap();
+                    let expected: $t = data.parse().unwrap();
+                    assert_eq!($t::builder().build(), expected, "{}", $path);
+                )*
+            }
+        };
+    }
+
+    default_matches_testdata! {
+        PingPayload => "system/ping.json",
+        JoinedPayload => "system/joined.json",
+        LeftPayload => "system/left.json",
+        MessageCreatedPayload => "message/message_created.json",
+        MessageDeletedPayload => "message/message_deleted.json",
+        MessageUpdatedPayload => "message/message_updated.json",
+        DirectMessageCreatedPayload => "message/direct_message_created.json",
+        DirectMessageDeletedPayload => "message/direct_message_deleted.json",
+        DirectMessageUpdatedPayload => "message/direct_message_updated.json",
+        BotMessageStampsUpdatedPayload => "message/bot_message_stamps_updated.json",
+        ChannelCreatedPayload => "channel/channel_created.json",
+        ChannelTopicChangedPayload => "channel/channel_topic_changed.json",
+        UserCreatedPayload => "user/user_created.json",
+        UserActivatedPayload => "user/user_activated.json",
+        StampCreatedPayload => "stamp/stamp_created.json",
+        TagAddedPayload => "tag/tag_added.json",
+        TagRemovedPayload => "tag/tag_removed.json",
+        UserGroupCreatedPayload => "user-group/user_group_created.json",
+        UserGroupUpdatedPayload => "user-group/user_group_updated.json",
+        UserGroupDeletedPayload => "user-group/user_group_deleted.json",
+        UserGroupMemberAddedPayload => "user-group/user_group_member_added.json",
+        UserGroupMemberUpdatedPayload => "user-group/user_group_member_updated.json",
+        UserGroupMemberRemovedPayload => "user-group/user_group_member_removed.json",
+        UserGroupAdminAddedPayload => "user-group/user_group_admin_added.json",
+        UserGroupAdminRemovedPayload => "user-group/user_group_admin_removed.json",
+    }
+
+    #[test]
+    fn message_text() {
+        let channel = Channel::builder().build();
+        let text = MessageBuilder::new().channel(&channel).text(" hi").build();
+        let message = Message::builder().text(text.clone()).build();
+        assert_eq!(message.text, text);
+        assert_eq!(message.plain_text, "#a/po hi");
+        assert_eq!(message.embedded.len(), 1);
+        assert_eq!(message.embedded[0].id, channel.id);
+    }
+
+    #[test]
+    fn message_payload() {
+        let user = User::builder().name("bot_po").bot(true).build();
+        let channel_id = uuid("f86c925c-3002-4ba5-939a-c92344e534f9");
+        let payload = DirectMessageCreatedPayload::builder()
+            .text("hi")
+            .author(user.clone())
+            .channel_id(channel_id.clone())
+            .build();
+        assert_eq!(payload.message.text, "hi");
+        assert_eq!(payload.message.plain_text, "hi");
+        assert!(payload.message.embedded.is_empty());
+        assert_eq!(payload.message.user, user);
+        assert_eq!(payload.message.channel_id, channel_id);
+    }
+
+    #[test]
+    fn user_group() {
+        let user_id = uuid("dfdff0c9-5de0-46ee-9721-2525e8bb3d45");
+        let group = UserGroup::builder()
+            .admins(vec![])
+            .members(vec![])
+            .admin(user_id.clone())
+            .member(user_id.clone(), "leader")
+            .build();
+        assert!(group.is_admin(&user_id));
+        assert_eq!(group.member_role(&user_id), Some(&"leader".into()));
+        assert_eq!(group.admins[0].group_id, group.id);
+    }
+
+    #[test]
+    fn group_member_payload() {
+        let user_id = uuid("dfdff0c9-5de0-46ee-9721-2525e8bb3d45");
+        let payload = UserGroupAdminAddedPayload::builder()
+            .user_id(user_id.clone())
+            .build();
+        assert_eq!(payload.group_member.user_id, user_id);
+        assert_eq!(
+            payload.group_member.group_id,
+            GroupMember::builder().build().group_id
+        );
+    }
+
+    #[test]
+    fn stamps() {
+        let payload = BotMessageStampsUpdatedPayload::builder()
+            .stamps(vec![])
+            .stamp(MessageStamp::builder().count(1))
+            .build();
+        assert_eq!(payload.stamps.len(), 1);
+        assert_eq!(payload.stamp_totals().into_values().sum::<i64>(), 1);
+    }
+}